@@ -0,0 +1,214 @@
+use std::fmt::Write;
+use std::path::Path;
+
+/// Starts every line of the report the core writes to stderr when the script
+/// fails during initialization. Keep in sync with `crates/core/src/diagnostic.rs`.
+const PREFIX: &str = "extism-js:diagnostic:";
+
+/// The file name the core evaluates the script under.
+const SCRIPT_NAME: &str = "script.js";
+
+/// An exception the user's script threw while the core was being wizened.
+#[derive(Debug, Default)]
+pub struct Diagnostic {
+    name: Option<String>,
+    message: String,
+    stack: Vec<String>,
+    /// Line and column in what the core evaluated, prefix included
+    location: Option<(usize, usize)>,
+}
+
+impl Diagnostic {
+    /// Splits the core's stderr into the output that came before the report
+    /// and the report itself, if there is one.
+    pub fn parse(stderr: &str) -> Option<(String, Diagnostic)> {
+        let start = stderr.find(PREFIX)?;
+        let mut diagnostic = Diagnostic::default();
+        let mut message = Vec::new();
+        for line in stderr[start..].lines() {
+            let Some(entry) = line.strip_prefix(PREFIX) else {
+                continue;
+            };
+            let (key, value) = entry.split_once(' ').unwrap_or((entry, ""));
+            match key {
+                "name" => diagnostic.name = Some(value.to_string()),
+                "message" => message.push(value),
+                "stack" => diagnostic.stack.push(value.to_string()),
+                "location" => {
+                    diagnostic.location = value
+                        .split_once(':')
+                        .and_then(|(line, column)| Some((line.parse().ok()?, column.parse().ok()?)))
+                }
+                _ => {}
+            }
+        }
+        diagnostic.message = message.join("\n");
+        Some((stderr[..start].to_string(), diagnostic))
+    }
+
+    /// Formats the diagnostic like a compiler error, with the offending line
+    /// of `source` underlined. `prefix_lines` is the number of lines the CLI
+    /// added ahead of the user's code.
+    pub fn render(&self, path: &Path, source: &str, prefix_lines: usize) -> String {
+        let path = path.display().to_string();
+        let mut out = String::new();
+        match &self.name {
+            Some(name) => writeln!(out, "error: {}: {}", name, self.message),
+            None => writeln!(out, "error: {}", self.message),
+        }
+        .unwrap();
+
+        let location = self
+            .location
+            .filter(|(line, _)| *line > prefix_lines)
+            .map(|(line, column)| (line - prefix_lines, column));
+        if let Some((line, column)) = location {
+            writeln!(out, "  --> {}:{}:{}", path, line, column).unwrap();
+            if let Some(code) = source.lines().nth(line - 1) {
+                let number = line.to_string();
+                let gutter = " ".repeat(number.len());
+                // keep tabs so the caret lines up with the code above it
+                let indent: String = code
+                    .chars()
+                    .take(column.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                writeln!(out, "{} |", gutter).unwrap();
+                writeln!(out, "{} | {}", number, code).unwrap();
+                writeln!(out, "{} | {}^", gutter, indent).unwrap();
+            }
+        }
+
+        if !self.stack.is_empty() {
+            writeln!(out, "Stack:").unwrap();
+            for frame in &self.stack {
+                writeln!(out, "{}", user_frame(frame, &path, prefix_lines)).unwrap();
+            }
+        }
+        out
+    }
+}
+
+/// Rewrites a stack frame's `script.js:line:column` to point into the user's
+/// file. Frames in the prefix or the prelude are left as they are.
+fn user_frame(frame: &str, path: &str, prefix_lines: usize) -> String {
+    let file = format!("{}:", SCRIPT_NAME);
+    let Some((before, position)) = frame.split_once(&file) else {
+        return frame.to_string();
+    };
+    let digits = position
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(position.len());
+    match position[..digits].parse::<usize>() {
+        Ok(line) if line > prefix_lines => format!(
+            "{}{}:{}{}",
+            before,
+            path,
+            line - prefix_lines,
+            &position[digits..]
+        ),
+        _ => frame.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(lines: &[&str]) -> String {
+        lines
+            .iter()
+            .map(|line| format!("{}{}\n", PREFIX, line))
+            .collect()
+    }
+
+    #[test]
+    fn parse_keeps_output_before_the_report() {
+        let stderr = format!("loading\n{}", report(&["name Error", "message boom"]));
+        let (before, diagnostic) = Diagnostic::parse(&stderr).unwrap();
+        assert_eq!(before, "loading\n");
+        assert_eq!(diagnostic.name.as_deref(), Some("Error"));
+        assert_eq!(diagnostic.message, "boom");
+    }
+
+    #[test]
+    fn parse_without_a_report() {
+        assert!(Diagnostic::parse("wasm trap: unreachable\n").is_none());
+    }
+
+    #[test]
+    fn parse_skips_malformed_lines() {
+        let stderr = format!(
+            "{}not a report line\n{}",
+            report(&["message first", "bogus key", "location nonsense"]),
+            report(&["location 3", "message second", "name"]),
+        );
+        let (_, diagnostic) = Diagnostic::parse(&stderr).unwrap();
+        assert_eq!(diagnostic.message, "first\nsecond");
+        assert_eq!(diagnostic.location, None);
+        assert_eq!(diagnostic.name.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn render_points_into_the_users_file() {
+        let (_, diagnostic) = Diagnostic::parse(&report(&[
+            "name TypeError",
+            "message x is undefined",
+            "location 3:5",
+            "stack     at f (script.js:3:5)",
+        ]))
+        .unwrap();
+        let rendered = diagnostic.render(Path::new("plugin.js"), "let a;\nlet x = y.z;\n", 1);
+        assert_eq!(
+            rendered,
+            "error: TypeError: x is undefined\n  --> plugin.js:2:5\n  |\n2 | let x = y.z;\n  |     ^\nStack:\n    at f (plugin.js:2:5)\n"
+        );
+    }
+
+    #[test]
+    fn render_leaves_errors_in_the_prefix_alone() {
+        let (_, diagnostic) = Diagnostic::parse(&report(&[
+            "message bad host functions",
+            "location 1:12",
+            "stack     at <eval> (script.js:1:12)",
+        ]))
+        .unwrap();
+        let rendered = diagnostic.render(Path::new("plugin.js"), "export {};\n", 1);
+        assert_eq!(
+            rendered,
+            "error: bad host functions\nStack:\n    at <eval> (script.js:1:12)\n"
+        );
+    }
+
+    #[test]
+    fn render_first_line_of_the_users_code() {
+        let (_, diagnostic) = Diagnostic::parse(&report(&[
+            "message boom",
+            "location 2:1",
+            "stack     at <eval> (script.js:2:1)",
+            "stack     at native (native)",
+        ]))
+        .unwrap();
+        let rendered = diagnostic.render(Path::new("plugin.js"), "\tthrow 1;\n", 1);
+        assert_eq!(
+            rendered,
+            "error: boom\n  --> plugin.js:1:1\n  |\n1 | \tthrow 1;\n  | ^\nStack:\n    at <eval> (plugin.js:1:1)\n    at native (native)\n"
+        );
+    }
+
+    #[test]
+    fn user_frame_with_several_prefix_lines() {
+        assert_eq!(
+            user_frame("    at g (script.js:4:2)", "plugin.js", 3),
+            "    at g (plugin.js:1:2)"
+        );
+        assert_eq!(
+            user_frame("    at g (script.js:3:2)", "plugin.js", 3),
+            "    at g (script.js:3:2)"
+        );
+        assert_eq!(
+            user_frame("    at h (script.js:", "plugin.js", 3),
+            "    at h (script.js:"
+        );
+    }
+}
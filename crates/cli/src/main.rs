@@ -1,8 +1,12 @@
+mod diagnostic;
 mod opt;
 mod options;
 mod shims;
+#[path = "../../core/src/signature.rs"]
+mod signature;
 mod ts_parser;
 
+use crate::diagnostic::Diagnostic;
 use crate::options::Options;
 use crate::ts_parser::parse_interface_file;
 use anyhow::{bail, Result};
@@ -17,6 +21,9 @@ use tempfile::TempDir;
 
 const CORE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/engine.wasm"));
 
+/// The exit status when the user's script throws while it's initialized
+const SCRIPT_ERROR_STATUS: i32 = 2;
+
 fn main() -> Result<()> {
     let mut builder = env_logger::Builder::new();
     builder
@@ -60,7 +67,27 @@ fn main() -> Result<()> {
     }
 
     contents
-        .extend_from_slice(format!("Host.__hostFunctions = [{}];\n", names.join(", ")).as_bytes());
+        .extend_from_slice(format!("Host.__hostFunctions = [{}];", names.join(", ")).as_bytes());
+    // The shim calls exports by their index in this list, sorted by name
+    let export_names: Vec<_> = plugin_interface
+        .exports
+        .functions
+        .iter()
+        .map(|f| format!("'{}'", &f.name))
+        .collect();
+    contents.extend_from_slice(
+        format!(" Host.__exportNames = [{}];", export_names.join(", ")).as_bytes(),
+    );
+    // Kept on one line so the user's line numbers shift as little as possible
+    if opts.isolate {
+        contents.extend_from_slice(b" globalThis.__isolateCalls = true;");
+    }
+    if opts.fail_on_unhandled_rejection {
+        contents.extend_from_slice(b" globalThis.__failOnUnhandledRejection = true;");
+    }
+    contents.push(b'\n');
+    // Lines added ahead of the user's code, which shift its line numbers
+    let prefix_lines = contents.iter().filter(|&&byte| byte == b'\n').count();
     contents.append(&mut user_code);
 
     // Create a tmp dir to hold all the library objects
@@ -78,16 +105,31 @@ fn main() -> Result<()> {
             .arg("-o")
             .arg(&core_path)
             .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
             .spawn()?;
         command
             .stdin
             .take()
             .expect("Expected to get writeable stdin")
             .write_all(&contents)?;
-        let status = command.wait()?;
-        if !status.success() {
+        let output = command.wait_with_output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            // The core reports exceptions from the script; the Wasm trap
+            // that follows isn't worth showing
+            if let Some((before, diagnostic)) = Diagnostic::parse(&stderr) {
+                let source = fs::read_to_string(&opts.input_js)?;
+                eprint!("{}", before);
+                eprint!(
+                    "{}",
+                    diagnostic.render(&opts.input_js, &source, prefix_lines)
+                );
+                std::process::exit(SCRIPT_ERROR_STATUS);
+            }
+            eprint!("{}", stderr);
             bail!("Couldn't create wasm from input");
         }
+        eprint!("{}", stderr);
     }
 
     // Create our shim file given our parsed TS module object
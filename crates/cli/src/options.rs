@@ -31,4 +31,16 @@ pub struct Options {
 
     #[structopt(long = "--skip-opt", about = "Skip final optimization pass")]
     pub skip_opt: bool,
+
+    #[structopt(
+        long = "--isolate",
+        about = "Run every call against a fresh copy of the initialized state"
+    )]
+    pub isolate: bool,
+
+    #[structopt(
+        long = "--fail-on-unhandled-rejection",
+        about = "Fail a call when a promise is rejected without a handler"
+    )]
+    pub fail_on_unhandled_rejection: bool,
 }
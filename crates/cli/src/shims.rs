@@ -1,17 +1,112 @@
+use crate::signature::{MAX_HOST_PARAMS, SIGNATURE_COUNT_BITS, SIGNATURE_TYPE_BITS};
 use crate::ts_parser::Interface;
 use anyhow::Result;
 use std::path::Path;
 use wagen::{BlockType, Instr, ValType};
 
+/// Type codes shared with the core, 0 is reserved for void
 #[derive(PartialEq)]
 enum TypeCode {
-    Void = 0,
     I32 = 1,
     I64 = 2,
     F32 = 3,
     F64 = 4,
 }
 
+impl TypeCode {
+    fn from_val_type(val_type: &ValType) -> Result<TypeCode> {
+        match val_type {
+            ValType::I32 => Ok(TypeCode::I32),
+            ValType::I64 => Ok(TypeCode::I64),
+            ValType::F32 => Ok(TypeCode::F32),
+            ValType::F64 => Ok(TypeCode::F64),
+            r => anyhow::bail!("Unsupported type: {:?}", r),
+        }
+    }
+}
+
+/// Packs a host function signature into a single i64 for the core to decode:
+/// the parameter count, the result count, then one type code per parameter
+/// followed by one per result.
+fn pack_signature(name: &str, params: &[ValType], results: &[ValType]) -> Result<i64> {
+    if params.len() > MAX_HOST_PARAMS {
+        anyhow::bail!(
+            "Host function {} has {} parameters, but at most {} are supported",
+            name,
+            params.len(),
+            MAX_HOST_PARAMS
+        );
+    }
+    let max_types = (64 - 2 * SIGNATURE_COUNT_BITS) / SIGNATURE_TYPE_BITS;
+    let max_count = (1 << SIGNATURE_COUNT_BITS) - 1;
+    if params.len() + results.len() > max_types as usize
+        || params.len() > max_count
+        || results.len() > max_count
+    {
+        anyhow::bail!(
+            "Host function {} has too many parameters and results (at most {} combined)",
+            name,
+            max_types
+        );
+    }
+
+    let mut packed = params.len() as u64 | (results.len() as u64) << SIGNATURE_COUNT_BITS;
+    let mut shift = 2 * SIGNATURE_COUNT_BITS;
+    for val_type in params.iter().chain(results) {
+        packed |= (TypeCode::from_val_type(val_type)? as u64) << shift;
+        shift += SIGNATURE_TYPE_BITS;
+    }
+    Ok(packed as i64)
+}
+
+/// Emits a `br_table` on local 0 with one arm per index. Each arm must leave
+/// the function (e.g. with `return`); out of range indices fall through to
+/// whatever the caller pushes afterwards.
+fn push_dispatch(
+    builder: &mut wagen::Builder,
+    count: usize,
+    mut arm: impl FnMut(&mut wagen::Builder, usize) -> Result<()>,
+) -> Result<()> {
+    // One block per arm plus an outer one for the default case
+    for _ in 0..=count {
+        builder.push(Instr::Block(BlockType::Empty));
+    }
+    builder.push(Instr::LocalGet(0));
+    let targets: Vec<u32> = (0..count as u32).collect();
+    builder.push(Instr::BrTable(targets.into(), count as u32));
+    for i in 0..count {
+        builder.push(Instr::End);
+        arm(builder, i)?;
+    }
+    builder.push(Instr::End);
+    Ok(())
+}
+
+/// Converts the value on top of the stack to its i64 bit pattern for the core
+fn push_result_to_bits(builder: &mut wagen::Builder, result: &ValType) -> Result<()> {
+    match result {
+        ValType::I32 => {
+            builder.push(Instr::I64ExtendI32U);
+        }
+        ValType::I64 => {
+            // Already i64, no conversion needed
+        }
+        ValType::F32 => {
+            // Convert f32 to its bit pattern
+            builder.push(Instr::I32ReinterpretF32);
+            builder.push(Instr::I64ExtendI32U);
+        }
+        ValType::F64 => {
+            // Convert f64 to its bit pattern
+            builder.push(Instr::I64ReinterpretF64);
+        }
+        r => {
+            anyhow::bail!("Unsupported result type: {:?}", r);
+        }
+    }
+    Ok(())
+}
+
 pub fn generate_wasm_shims(
     path: impl AsRef<Path>,
     exports: &Interface,
@@ -25,10 +120,24 @@ pub fn generate_wasm_shims(
     let __arg_f32 = module.import("core", "__arg_f32", None, [ValType::F32], []);
     let __arg_f64 = module.import("core", "__arg_f64", None, [ValType::F64], []);
     let __invoke_i32 = module.import("core", "__invoke_i32", None, [ValType::I32], [ValType::I32]);
+    let __invoke_i32_or_void = module.import(
+        "core",
+        "__invoke_i32_or_void",
+        None,
+        [ValType::I32],
+        [ValType::I32],
+    );
     let __invoke_i64 = module.import("core", "__invoke_i64", None, [ValType::I32], [ValType::I64]);
     let __invoke_f32 = module.import("core", "__invoke_f32", None, [ValType::I32], [ValType::F32]);
     let __invoke_f64 = module.import("core", "__invoke_f64", None, [ValType::I32], [ValType::F64]);
     let __invoke = module.import("core", "__invoke", None, [ValType::I32], []);
+    let __invoke_multi = module.import("core", "__invoke_multi", None, [ValType::I32], []);
+    let __result_type = module.import("core", "__result_type", None, [ValType::I32], []);
+    let __result_i32 = module.import("core", "__result_i32", None, [], [ValType::I32]);
+    let __result_i64 = module.import("core", "__result_i64", None, [], [ValType::I64]);
+    let __result_f32 = module.import("core", "__result_f32", None, [], [ValType::F32]);
+    let __result_f64 = module.import("core", "__result_f64", None, [], [ValType::F64]);
+    let __host_result = module.import("core", "__host_result", None, [ValType::I64], []);
 
     let mut import_elements = Vec::new();
     let mut import_items = vec![];
@@ -54,86 +163,45 @@ pub fn generate_wasm_shims(
         maximum: None,
     });
 
-    let mut get_function_return_type_builder = wagen::Builder::default();
-
-    for (func_idx, (_name, _index, _params, results)) in import_items.iter().enumerate() {
-        let type_code = results.first().map_or(TypeCode::Void, |val_type| match val_type {
-            ValType::I32 => TypeCode::I32,
-            ValType::I64 => TypeCode::I64,
-            ValType::F32 => TypeCode::F32,
-            ValType::F64 => TypeCode::F64,
-            _ => TypeCode::Void,
-        });
-        
-        if type_code == TypeCode::Void {
-            continue;
-        }
-
-        // Compare the input function index with the current index.
-        get_function_return_type_builder.push(Instr::LocalGet(0)); // load requested function index
-        get_function_return_type_builder.push(Instr::I32Const(func_idx as i32)); // load func_idx
-        get_function_return_type_builder.push(Instr::I32Eq); // compare
-        get_function_return_type_builder.push(Instr::If(BlockType::Empty)); // if true
-        get_function_return_type_builder.push(Instr::I32Const(type_code as i32)); // load type code
-        get_function_return_type_builder.push(Instr::Return); // early return if match
-        get_function_return_type_builder.push(Instr::End);
-    }
-
-    get_function_return_type_builder.push(Instr::I32Const(0)); // Default to 0
-    get_function_return_type_builder.push(Instr::Return);
+    // The core reads every signature once and caches them, so calls into
+    // host functions don't have to come back here for type information.
+    let mut get_function_count_builder = wagen::Builder::default();
+    get_function_count_builder.push(Instr::I32Const(import_items.len() as i32));
+    get_function_count_builder.push(Instr::Return);
 
-    let get_function_return_type_func = module.func(
-        "__get_function_return_type",
-        vec![ValType::I32], // takes function index
-        vec![ValType::I32], // returns type code
+    let get_function_count_func = module.func(
+        "__get_function_count",
+        vec![],             // takes nothing
+        vec![ValType::I32], // returns number of host functions
         vec![],
     );
-    get_function_return_type_func.export("__get_function_return_type");
-    get_function_return_type_func.body = get_function_return_type_builder;
-
-    let mut get_function_arg_type_builder = wagen::Builder::default();
-
-    for (func_idx, (_name, _index, params, _results)) in import_items.iter().enumerate() {
-        for (arg_idx, param) in params.iter().enumerate() {
-            let type_code = match param {
-                ValType::I32 => TypeCode::I32,
-                ValType::I64 => TypeCode::I64,
-                ValType::F32 => TypeCode::F32,
-                ValType::F64 => TypeCode::F64,
-                _ => panic!("Unsupported argument type for function {} at index {}", func_idx, arg_idx),
-            };
-
-            // Compare both function index and argument index
-            get_function_arg_type_builder.push(Instr::LocalGet(0)); // function index
-            get_function_arg_type_builder.push(Instr::I32Const(func_idx as i32));
-            get_function_arg_type_builder.push(Instr::I32Eq);
-
-            get_function_arg_type_builder.push(Instr::LocalGet(1)); // argument index
-            get_function_arg_type_builder.push(Instr::I32Const(arg_idx as i32));
-            get_function_arg_type_builder.push(Instr::I32Eq);
-
-            get_function_arg_type_builder.push(Instr::I32And); // Both must match
-
-            // If both match, return the type code
-            get_function_arg_type_builder.push(Instr::If(BlockType::Empty));
-            get_function_arg_type_builder.push(Instr::I32Const(type_code as i32));
-            get_function_arg_type_builder.push(Instr::Return);
-            get_function_arg_type_builder.push(Instr::End);
-        }
+    get_function_count_func.export("__get_function_count");
+    get_function_count_func.body = get_function_count_builder;
+
+    let mut signatures = Vec::with_capacity(import_items.len() * 8);
+    for (name, _index, params, results) in &import_items {
+        signatures.extend_from_slice(&pack_signature(name, params, results)?.to_le_bytes());
     }
 
-    // Default return if no match
-    get_function_arg_type_builder.push(Instr::I32Const(0));
-    get_function_arg_type_builder.push(Instr::Return);
+    // Copies the packed signatures, one little-endian u64 per host function,
+    // from the passive segment added by `add_signature_segment`.
+    let mut copy_function_signatures_builder = wagen::Builder::default();
+    copy_function_signatures_builder.push(Instr::LocalGet(0));
+    copy_function_signatures_builder.push(Instr::I32Const(0));
+    copy_function_signatures_builder.push(Instr::I32Const(signatures.len() as i32));
+    copy_function_signatures_builder.push(Instr::MemoryInit {
+        mem: 0,
+        data_index: 0,
+    });
 
-    let get_function_arg_type_func = module.func(
-        "__get_function_arg_type",
-        vec![ValType::I32, ValType::I32], // takes (function_index, arg_index)
-        vec![ValType::I32],               // returns type code
+    let copy_function_signatures_func = module.func(
+        "__copy_function_signatures",
+        vec![ValType::I32], // takes the destination in the core's memory
+        vec![],
         vec![],
     );
-    get_function_arg_type_func.export("__get_function_arg_type");
-    get_function_arg_type_func.body = get_function_arg_type_builder;
+    copy_function_signatures_func.export("__copy_function_signatures");
+    copy_function_signatures_func.body = copy_function_signatures_builder;
 
     // Create converters for each host function to reinterpret the I64 bit pattern as the expected type
     let mut converter_indices = Vec::new();
@@ -180,30 +248,30 @@ pub fn generate_wasm_shims(
         });
 
         // Convert result back to i64 bits for JS
-        if let Some(result) = results.first() {
-            match result {
-                ValType::I32 => {
-                    builder.push(Instr::I64ExtendI32U);
-                }
-                ValType::I64 => {
-                    // Already i64, no conversion needed
-                }
-                ValType::F32 => {
-                    // Convert f32 to its bit pattern
-                    builder.push(Instr::I32ReinterpretF32);
-                    builder.push(Instr::I64ExtendI32U);
-                }
-                ValType::F64 => {
-                    // Convert f64 to its bit pattern
-                    builder.push(Instr::I64ReinterpretF64);
+        let mut locals = vec![];
+        match results.as_slice() {
+            [] => {
+                // No return value, push 0
+                builder.push(Instr::I64Const(0));
+            }
+            [result] => {
+                push_result_to_bits(&mut builder, result)?;
+            }
+            results => {
+                // The last result is on top of the stack, so stash them in
+                // locals back to front and hand them to the core in order.
+                let first_local = (params.len() + 1) as u32;
+                for (i, result) in results.iter().enumerate().rev() {
+                    push_result_to_bits(&mut builder, result)?;
+                    builder.push(Instr::LocalSet(first_local + i as u32));
                 }
-                r => {
-                    anyhow::bail!("Unsupported result type: {:?}", r);
+                for i in 0..results.len() {
+                    builder.push(Instr::LocalGet(first_local + i as u32));
+                    builder.push(Instr::Call(__host_result.index()));
                 }
+                builder.push(Instr::I64Const(0));
+                locals = vec![ValType::I64; results.len()];
             }
-        } else {
-            // No return value, push 0
-            builder.push(Instr::I64Const(0));
         }
 
         // Create the converter function
@@ -214,7 +282,7 @@ pub fn generate_wasm_shims(
             format!("__conv_{}", name),
             shim_params,
             vec![ValType::I64],
-            vec![],
+            locals,
         );
         conv_func.export(format!("__conv_{}", name));
         conv_func.body = builder;
@@ -222,41 +290,38 @@ pub fn generate_wasm_shims(
         converter_indices.push(conv_func.index);
     }
 
+    // The function index, then the arguments
+    let mut router_params = vec![ValType::I32];
+    router_params.extend(std::iter::repeat_n(ValType::I64, MAX_HOST_PARAMS));
     let router = module.func(
         "__invokeHostFunc",
-        vec![
-            ValType::I32, // func_idx
-            ValType::I64, // args[0]
-            ValType::I64, // args[1]
-            ValType::I64, // args[2]
-            ValType::I64, // args[3]
-            ValType::I64, // args[4]
-        ],
+        router_params,
         vec![ValType::I64],
         vec![],
     );
 
-    // Similar builder logic as before but simplified to one function
+    // Jump straight to the converter for the requested function
     let mut router_builder = wagen::Builder::default();
 
-    for (func_idx, (_name, _index, params, _results)) in import_items.iter().enumerate() {
-        router_builder.push(Instr::LocalGet(0)); // func index
-        router_builder.push(Instr::I32Const(func_idx as i32));
-        router_builder.push(Instr::I32Eq);
-        router_builder.push(Instr::If(BlockType::Empty));
+    push_dispatch(
+        &mut router_builder,
+        import_items.len(),
+        |builder, func_idx| {
+            let (_name, _index, params, _results) = &import_items[func_idx];
 
-        // First push func_idx for converter
-        router_builder.push(Instr::LocalGet(0));
+            // First push func_idx for converter
+            builder.push(Instr::LocalGet(0));
 
-        // Then push remaining args from router's inputs
-        for (i, _) in params.iter().enumerate() {
-            router_builder.push(Instr::LocalGet((i + 1) as u32));
-        }
+            // Then push remaining args from router's inputs
+            for (i, _) in params.iter().enumerate() {
+                builder.push(Instr::LocalGet((i + 1) as u32));
+            }
 
-        router_builder.push(Instr::Call(converter_indices[func_idx]));
-        router_builder.push(Instr::Return);
-        router_builder.push(Instr::End);
-    }
+            builder.push(Instr::Call(converter_indices[func_idx]));
+            builder.push(Instr::Return);
+            Ok(())
+        },
+    )?;
 
     router_builder.push(Instr::I64Const(0));
     router_builder.push(Instr::Return);
@@ -274,12 +339,6 @@ pub fn generate_wasm_shims(
     for (idx, export) in exports.functions.iter().enumerate() {
         let params: Vec<_> = export.params.iter().map(|x| x.ptype).collect();
         let results: Vec<_> = export.results.iter().map(|x| x.ptype).collect();
-        if results.len() > 1 {
-            anyhow::bail!(
-                "Multiple return arguments are not currently supported but used in exported function {}",
-                export.name
-            );
-        }
 
         let mut builder = wagen::Builder::default();
         builder.push(Instr::Call(__arg_start.index()));
@@ -306,25 +365,53 @@ pub fn generate_wasm_shims(
             }
         }
 
-        builder.push(Instr::I32Const(idx as i32));
-        match results.first() {
-            None => {
+        match results.as_slice() {
+            [] => {
+                builder.push(Instr::I32Const(idx as i32));
                 builder.push(Instr::Call(__invoke.index()));
             }
-            Some(ValType::I32) => {
+            [ValType::I32] if export.optional_result => {
+                builder.push(Instr::I32Const(idx as i32));
+                builder.push(Instr::Call(__invoke_i32_or_void.index()));
+            }
+            [ValType::I32] => {
+                builder.push(Instr::I32Const(idx as i32));
                 builder.push(Instr::Call(__invoke_i32.index()));
             }
-            Some(ValType::I64) => {
+            [ValType::I64] => {
+                builder.push(Instr::I32Const(idx as i32));
                 builder.push(Instr::Call(__invoke_i64.index()));
             }
-            Some(ValType::F32) => {
+            [ValType::F32] => {
+                builder.push(Instr::I32Const(idx as i32));
                 builder.push(Instr::Call(__invoke_f32.index()));
             }
-            Some(ValType::F64) => {
+            [ValType::F64] => {
+                builder.push(Instr::I32Const(idx as i32));
                 builder.push(Instr::Call(__invoke_f64.index()));
             }
-            Some(r) => {
-                anyhow::bail!("Unsupported result type: {:?}", r);
+            results => {
+                // Declare the expected result types, run the export, then
+                // collect each converted value onto the stack in order.
+                for result in results {
+                    let type_code = TypeCode::from_val_type(result)?;
+                    builder.push(Instr::I32Const(type_code as i32));
+                    builder.push(Instr::Call(__result_type.index()));
+                }
+
+                builder.push(Instr::I32Const(idx as i32));
+                builder.push(Instr::Call(__invoke_multi.index()));
+
+                for result in results {
+                    let getter = match result {
+                        ValType::I32 => __result_i32.index(),
+                        ValType::I64 => __result_i64.index(),
+                        ValType::F32 => __result_f32.index(),
+                        ValType::F64 => __result_f64.index(),
+                        r => anyhow::bail!("Unsupported result type: {:?}", r),
+                    };
+                    builder.push(Instr::Call(getter));
+                }
             }
         }
 
@@ -333,12 +420,72 @@ pub fn generate_wasm_shims(
         f.body = builder;
     }
 
+    let shim = add_signature_segment(&module.finish(), &signatures)?;
+
     // Validation with debug output
-    if let Err(error) = module.clone().validate_save(path.as_ref()) {
+    if let Err(error) = wagen::validate(&shim) {
         eprintln!("Validation failed: {:?}", error);
-        module.save("/tmp/wizer/incomplete_shim.wasm")?;
-        return Err(error);
+        std::fs::write("/tmp/wizer/incomplete_shim.wasm", &shim)?;
+        return Err(error.into());
     }
+    std::fs::write(path, shim)?;
 
     Ok(())
 }
+
+/// Rewrites the finished shim to import the core's memory and carry
+/// `signatures` as a passive data segment, neither of which wagen can emit.
+fn add_signature_segment(shim: &[u8], signatures: &[u8]) -> Result<Vec<u8>> {
+    use wagen::encoder::{
+        DataCountSection, DataSection, EntityType, ImportSection, MemoryType, RawSection,
+    };
+    use wagen::parser::{Parser, Payload, TypeRef};
+
+    let mut module = wagen::encoder::Module::new();
+    for payload in Parser::new(0).parse_all(shim) {
+        match payload? {
+            Payload::ImportSection(reader) => {
+                let mut imports = ImportSection::new();
+                for import in reader {
+                    let import = import?;
+                    let TypeRef::Func(ty) = import.ty else {
+                        anyhow::bail!("Unexpected non-function import {}", import.name);
+                    };
+                    imports.import(import.module, import.name, EntityType::Function(ty));
+                }
+                imports.import(
+                    "core",
+                    "memory",
+                    MemoryType {
+                        minimum: 0,
+                        maximum: None,
+                        memory64: false,
+                        shared: false,
+                    },
+                );
+                module.section(&imports);
+            }
+            Payload::CodeSectionStart { range, .. } => {
+                module.section(&DataCountSection { count: 1 });
+                module.section(&RawSection {
+                    id: 10,
+                    data: &shim[range],
+                });
+                let mut data = DataSection::new();
+                data.passive(signatures.iter().copied());
+                module.section(&data);
+            }
+            // Replaced by the passive segment above
+            Payload::DataSection(_) => {}
+            payload => {
+                if let Some((id, range)) = payload.as_section() {
+                    module.section(&RawSection {
+                        id,
+                        data: &shim[range],
+                    });
+                }
+            }
+        }
+    }
+    Ok(module.finish())
+}
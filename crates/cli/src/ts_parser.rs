@@ -34,6 +34,8 @@ pub struct Signature {
     pub name: String,
     pub params: Vec<Param>,
     pub results: Vec<Param>,
+    /// Declared `I32 | void`: the export may return nothing, which counts as 0
+    pub optional_result: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -72,7 +74,51 @@ pub fn param_type(params: &mut Vec<Param>, vn: &str, t: &TsType) -> Result<()> {
     Ok(())
 }
 
+/// Whether an export's return type is `I32 | void`, the explicit opt-in for
+/// exports that don't return anything.
+fn is_optional_i32(return_type: &TsType) -> bool {
+    let Some(union) = return_type
+        .as_ts_union_or_intersection_type()
+        .and_then(|t| t.as_ts_union_type())
+    else {
+        return false;
+    };
+    let is_i32 = |t: &TsType| {
+        t.as_ts_type_ref()
+            .and_then(|t| t.type_name.as_ident())
+            .is_some_and(|id| id.sym.eq_ignore_ascii_case("i32"))
+    };
+    let is_void = |t: &TsType| {
+        t.as_ts_keyword_type()
+            .is_some_and(|t| t.kind == TsKeywordTypeKind::TsVoidKeyword)
+    };
+    union.types.len() == 2
+        && union.types.iter().any(|t| is_i32(t))
+        && union.types.iter().any(|t| is_void(t))
+}
+
 pub fn result_type(results: &mut Vec<Param>, return_type: &TsType) -> Result<()> {
+    // Tuples such as `[I64, I32]` declare multiple results
+    if let Some(tuple) = return_type.as_ts_tuple_type() {
+        // A one-element tuple would be passed as a plain value, not an array
+        if tuple.elem_types.len() < 2 {
+            bail!(
+                "Tuple return types need at least two elements, declare a single result without the brackets"
+            );
+        }
+        for (i, elem) in tuple.elem_types.iter().enumerate() {
+            let typ = elem
+                .ty
+                .as_ts_type_ref()
+                .and_then(|t| t.type_name.as_ident())
+                .context("Illegal tuple element in return type")?
+                .sym
+                .as_str();
+            results.push(Param::new(&format!("result{}", i), val_type(typ)?));
+        }
+        return Ok(());
+    }
+
     let return_type = if let Some(return_type) = return_type.as_ts_type_ref() {
         Some(
             return_type
@@ -123,6 +169,7 @@ fn parse_user_interface(i: &TsInterfaceDecl) -> Result<Interface> {
                     name,
                     params,
                     results,
+                    optional_result: false,
                 };
                 signatures.push(signature);
             }
@@ -175,8 +222,14 @@ fn parse_module_decl(tsmod: &TsModuleDecl) -> Result<Interface> {
                         let name = fndecl.ident.sym.as_str().to_string();
                         let mut params = vec![];
                         let mut results = vec![];
+                        let mut optional_result = false;
                         if let Some(return_type) = fndecl.function.clone().return_type.clone() {
-                            result_type(&mut results, &return_type.type_ann)?;
+                            if is_optional_i32(&return_type.type_ann) {
+                                results.push(Param::new("result", ValType::I32));
+                                optional_result = true;
+                            } else {
+                                result_type(&mut results, &return_type.type_ann)?;
+                            }
                         }
 
                         for param in fndecl.function.params.iter() {
@@ -193,6 +246,7 @@ fn parse_module_decl(tsmod: &TsModuleDecl) -> Result<Interface> {
                             name,
                             params,
                             results,
+                            optional_result,
                         };
 
                         signatures.push(signature);
@@ -263,6 +317,18 @@ pub fn parse_interface_file(interface_path: impl AsRef<Path>) -> Result<PluginIn
 
     exports.functions.sort_by_key(|x| x.name.to_string());
 
+    // The engine calls lifecycle hooks itself, so they aren't Wasm exports
+    if let Some(hook) = exports
+        .functions
+        .iter()
+        .find(|f| matches!(f.name.as_str(), "onInit" | "onCall"))
+    {
+        bail!(
+            "`{}` is a lifecycle hook the engine calls itself, remove it from the 'main' module",
+            hook.name
+        );
+    }
+
     let imports = interfaces
         .into_iter()
         .filter(|i| i.name != "main")
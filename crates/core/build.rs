@@ -1,3 +1,38 @@
+use std::env;
+use std::path::PathBuf;
+
 fn main() {
     println!("cargo:rerun-if-changed=src/prelude/dist/index.js");
+
+    if env::var_os("CARGO_FEATURE_INTL").is_some() {
+        intl_data();
+    }
+}
+
+// The `intl` feature embeds the locale data generated by `make intl-data`, passed
+// in through EXTISM_JS_INTL_DATA. Without it (e.g. under clippy) an empty stub is
+// embedded and the `Intl` constructors throw.
+fn intl_data() {
+    println!("cargo:rerun-if-env-changed=EXTISM_JS_INTL_DATA");
+    println!("cargo:rerun-if-env-changed=EXTISM_JS_INTL_LOCALES");
+
+    let (path, locales) = match env::var("EXTISM_JS_INTL_DATA") {
+        Ok(path) => (
+            PathBuf::from(path),
+            env::var("EXTISM_JS_INTL_LOCALES").unwrap_or_default(),
+        ),
+        Err(_) => {
+            let stub = PathBuf::from(env::var("OUT_DIR").unwrap()).join("intl-data.postcard");
+            std::fs::write(&stub, []).expect("failed to write empty intl data stub");
+            println!("cargo:warning=building the intl feature without locale data...");
+            (stub, String::new())
+        }
+    };
+
+    println!("cargo:rerun-if-changed={}", path.display());
+    println!(
+        "cargo:rustc-env=EXTISM_JS_INTL_DATA_PATH={}",
+        path.display()
+    );
+    println!("cargo:rustc-env=EXTISM_JS_INTL_LOCALES={locales}");
 }
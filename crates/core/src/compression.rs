@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail, Context};
+use flate2::{write, Compression};
+use rquickjs::{function::MutFn, prelude::*, ArrayBuffer, Ctx, Function, Object, Value};
+
+use crate::globals::{number_arg, to_js_error};
+
+/// An open compressor or decompressor. Input is written in chunks and
+/// whatever output is ready is drained after each write.
+enum Codec {
+    GzipEncoder(write::GzEncoder<Vec<u8>>),
+    ZlibEncoder(write::ZlibEncoder<Vec<u8>>),
+    DeflateEncoder(write::DeflateEncoder<Vec<u8>>),
+    GzipDecoder(write::GzDecoder<Vec<u8>>),
+    ZlibDecoder(write::ZlibDecoder<Vec<u8>>),
+    DeflateDecoder(write::DeflateDecoder<Vec<u8>>),
+}
+
+/// Open codecs, by the id handed to JS. Ids aren't reused, so a stream kept
+/// past the call that opened its codec can't reach a newer one.
+static CODECS: Mutex<BTreeMap<usize, Codec>> = Mutex::new(BTreeMap::new());
+static NEXT_CODEC_ID: AtomicUsize = AtomicUsize::new(0);
+
+impl Codec {
+    /// `format` follows `CompressionStream`: "deflate" is the zlib format and
+    /// "deflate-raw" has no header or trailer.
+    fn new(format: &str, decompress: bool) -> anyhow::Result<Codec> {
+        let level = Compression::default();
+        let codec = match (format, decompress) {
+            ("gzip", false) => Codec::GzipEncoder(write::GzEncoder::new(Vec::new(), level)),
+            ("deflate", false) => Codec::ZlibEncoder(write::ZlibEncoder::new(Vec::new(), level)),
+            ("deflate-raw", false) => {
+                Codec::DeflateEncoder(write::DeflateEncoder::new(Vec::new(), level))
+            }
+            ("gzip", true) => Codec::GzipDecoder(write::GzDecoder::new(Vec::new())),
+            ("deflate", true) => Codec::ZlibDecoder(write::ZlibDecoder::new(Vec::new())),
+            ("deflate-raw", true) => Codec::DeflateDecoder(write::DeflateDecoder::new(Vec::new())),
+            _ => bail!("Unsupported compression format: {}", format),
+        };
+        Ok(codec)
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Codec::GzipEncoder(w) => w,
+            Codec::ZlibEncoder(w) => w,
+            Codec::DeflateEncoder(w) => w,
+            Codec::GzipDecoder(w) => w,
+            Codec::ZlibDecoder(w) => w,
+            Codec::DeflateDecoder(w) => w,
+        }
+    }
+
+    fn output(&mut self) -> &mut Vec<u8> {
+        match self {
+            Codec::GzipEncoder(w) => w.get_mut(),
+            Codec::ZlibEncoder(w) => w.get_mut(),
+            Codec::DeflateEncoder(w) => w.get_mut(),
+            Codec::GzipDecoder(w) => w.get_mut(),
+            Codec::ZlibDecoder(w) => w.get_mut(),
+            Codec::DeflateDecoder(w) => w.get_mut(),
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        self.writer()
+            .write_all(data)
+            .context("Invalid compressed data")?;
+        Ok(std::mem::take(self.output()))
+    }
+
+    /// Flushes the rest of the output. Decoders fail here if the input
+    /// stopped before the end of the compressed stream.
+    fn finish(self) -> anyhow::Result<Vec<u8>> {
+        let output = match self {
+            Codec::GzipEncoder(w) => w.finish(),
+            Codec::ZlibEncoder(w) => w.finish(),
+            Codec::DeflateEncoder(w) => w.finish(),
+            Codec::GzipDecoder(w) => w.finish(),
+            Codec::ZlibDecoder(w) => w.finish(),
+            Codec::DeflateDecoder(w) => w.finish(),
+        };
+        output.context("Compressed data ended unexpectedly")
+    }
+}
+
+fn codec_id(cx: &Ctx, args: &Rest<Value>) -> rquickjs::Result<usize> {
+    Ok(number_arg(cx, args, 0, "codec id")? as usize)
+}
+
+fn closed_codec(id: usize) -> anyhow::Error {
+    anyhow!(
+        "No open codec {} (a codec is freed when the call that opened it ends)",
+        id
+    )
+}
+
+fn take_codec(id: usize) -> anyhow::Result<Codec> {
+    CODECS
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| closed_codec(id))
+}
+
+/// Frees the codecs a call left open without finishing them.
+pub(crate) fn discard_codecs() {
+    CODECS.lock().unwrap().clear();
+}
+
+/// A set of open codecs, kept aside while another context's are in use.
+#[derive(Default)]
+pub(crate) struct Codecs(BTreeMap<usize, Codec>);
+
+/// Exchanges the open codecs with `codecs`.
+pub(crate) fn swap_codecs(codecs: &mut Codecs) {
+    std::mem::swap(&mut *CODECS.lock().unwrap(), &mut codecs.0);
+}
+
+/// Builds the `__compression` object the prelude uses for `Host.compress`,
+/// `Host.decompress`, `CompressionStream` and `DecompressionStream`.
+pub(crate) fn build_compression_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
+    let open = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let format = args
+                .first()
+                .and_then(|v| v.as_string())
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected format string")))?
+                .to_string()?;
+            let decompress = args.get(1).and_then(|v| v.as_bool()).unwrap_or(false);
+            let codec = Codec::new(&format, decompress).map_err(|e| to_js_error(cx.clone(), e))?;
+
+            let id = NEXT_CODEC_ID.fetch_add(1, Ordering::Relaxed);
+            CODECS.lock().unwrap().insert(id, codec);
+            Ok::<_, rquickjs::Error>(id as f64)
+        }),
+    )?;
+
+    let write = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let id = codec_id(&cx, &args)?;
+            let data = args
+                .get(1)
+                .and_then(|v| ArrayBuffer::from_value(v.clone()))
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected ArrayBuffer data")))?;
+            let bytes = data
+                .as_bytes()
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Could not read ArrayBuffer")))?;
+
+            let mut codecs = CODECS.lock().unwrap();
+            let codec = codecs
+                .get_mut(&id)
+                .ok_or_else(|| to_js_error(cx.clone(), closed_codec(id)))?;
+            match codec.write(bytes) {
+                Ok(output) => ArrayBuffer::new(cx, output),
+                Err(e) => {
+                    // a codec that has failed once can't be resumed
+                    codecs.remove(&id);
+                    Err(to_js_error(cx, e))
+                }
+            }
+        }),
+    )?;
+
+    let finish = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let id = codec_id(&cx, &args)?;
+            let output = take_codec(id)
+                .and_then(Codec::finish)
+                .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, output)
+        }),
+    )?;
+
+    let close = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let id = codec_id(&cx, &args)?;
+            // closing twice is harmless, e.g. when a stream is cancelled after an error
+            drop(take_codec(id));
+            Ok::<_, rquickjs::Error>(())
+        }),
+    )?;
+
+    let compression = Object::new(this)?;
+    compression.set("open", open)?;
+    compression.set("write", write)?;
+    compression.set("finish", finish)?;
+    compression.set("close", close)?;
+    Ok(compression)
+}
@@ -0,0 +1,1012 @@
+use aes::cipher::{
+    block_padding::Pkcs7, BlockDecryptMut, BlockEncrypt, BlockEncryptMut, BlockSizeUser, KeyIvInit,
+};
+use aes::{Aes128, Aes192, Aes256};
+use aes_gcm::aead::{consts, Aead, Payload};
+use aes_gcm::AesGcm;
+use anyhow::{anyhow, bail};
+use hkdf::Hkdf;
+use hmac::{digest::KeyInit, Hmac, Mac};
+use p256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use pbkdf2::pbkdf2_hmac;
+use rquickjs::{function::MutFn, prelude::*, ArrayBuffer, Ctx, Function, Object, Value};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+use rsa::signature::{RandomizedSigner, SignatureEncoding, Signer, Verifier};
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, RsaPrivateKey, RsaPublicKey};
+use sha1::Sha1;
+use sha2::digest::{const_oid::AssociatedOid, FixedOutputReset};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::determinism::EngineRng;
+use crate::globals::{number_arg, string_arg, to_js_error};
+
+fn buffer_arg<'js>(
+    cx: &Ctx<'js>,
+    args: &Rest<Value<'js>>,
+    idx: usize,
+    name: &str,
+) -> rquickjs::Result<ArrayBuffer<'js>> {
+    args.get(idx)
+        .and_then(|v| ArrayBuffer::from_value(v.clone()))
+        .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected {name} to be an ArrayBuffer")))
+}
+
+fn buffer_bytes<'a>(cx: &Ctx, buffer: &'a ArrayBuffer) -> rquickjs::Result<&'a [u8]> {
+    buffer
+        .as_bytes()
+        .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Could not read ArrayBuffer")))
+}
+
+fn bool_arg(args: &Rest<Value>, idx: usize) -> bool {
+    args.get(idx).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+fn mac<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> M {
+    let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac
+}
+
+fn sign<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    mac::<M>(key, data).finalize().into_bytes().to_vec()
+}
+
+fn hmac_sign(hash: &str, key: &[u8], data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let signature = match hash {
+        "SHA-1" => sign::<Hmac<Sha1>>(key, data),
+        "SHA-256" => sign::<Hmac<Sha256>>(key, data),
+        "SHA-384" => sign::<Hmac<Sha384>>(key, data),
+        "SHA-512" => sign::<Hmac<Sha512>>(key, data),
+        _ => bail!("Unsupported hash: {}", hash),
+    };
+    Ok(signature)
+}
+
+/// Compares in constant time, so a caller can't learn how much of a forged
+/// signature was right from how long the check took.
+fn hmac_verify(hash: &str, key: &[u8], data: &[u8], signature: &[u8]) -> anyhow::Result<bool> {
+    let valid = match hash {
+        "SHA-1" => mac::<Hmac<Sha1>>(key, data).verify_slice(signature),
+        "SHA-256" => mac::<Hmac<Sha256>>(key, data).verify_slice(signature),
+        "SHA-384" => mac::<Hmac<Sha384>>(key, data).verify_slice(signature),
+        "SHA-512" => mac::<Hmac<Sha512>>(key, data).verify_slice(signature),
+        _ => bail!("Unsupported hash: {}", hash),
+    };
+    Ok(valid.is_ok())
+}
+
+fn aes_key_error(key: &[u8]) -> anyhow::Error {
+    anyhow!(
+        "AES keys must be 128, 192 or 256 bits, got {}",
+        key.len() * 8
+    )
+}
+
+fn gcm_with<C: KeyInit + Aead>(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    decrypt: bool,
+) -> anyhow::Result<Vec<u8>> {
+    let cipher = C::new_from_slice(key).map_err(|_| aes_key_error(key))?;
+    let nonce = aes_gcm::aead::Nonce::<C>::from_slice(iv);
+    let payload = Payload { msg: data, aad };
+    let result = if decrypt {
+        cipher.decrypt(nonce, payload)
+    } else {
+        cipher.encrypt(nonce, payload)
+    };
+    // the aead error is deliberately opaque, so a bad tag and a bad key look alike
+    result.map_err(|_| anyhow!("The operation failed for an operation-specific reason"))
+}
+
+fn gcm_with_tag<T: aes_gcm::TagSize>(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    data: &[u8],
+    decrypt: bool,
+) -> anyhow::Result<Vec<u8>> {
+    match key.len() {
+        16 => gcm_with::<AesGcm<Aes128, consts::U12, T>>(key, iv, aad, data, decrypt),
+        24 => gcm_with::<AesGcm<Aes192, consts::U12, T>>(key, iv, aad, data, decrypt),
+        32 => gcm_with::<AesGcm<Aes256, consts::U12, T>>(key, iv, aad, data, decrypt),
+        _ => Err(aes_key_error(key)),
+    }
+}
+
+/// AES-GCM with a 96-bit IV. The tag, `tag_bits` long, is appended to the
+/// ciphertext as WebCrypto expects.
+fn aes_gcm(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    tag_bits: u32,
+    data: &[u8],
+    decrypt: bool,
+) -> anyhow::Result<Vec<u8>> {
+    if iv.len() != 12 {
+        bail!("AES-GCM needs a 96-bit iv, got {} bits", iv.len() * 8);
+    }
+    match tag_bits {
+        96 => gcm_with_tag::<consts::U12>(key, iv, aad, data, decrypt),
+        104 => gcm_with_tag::<consts::U13>(key, iv, aad, data, decrypt),
+        112 => gcm_with_tag::<consts::U14>(key, iv, aad, data, decrypt),
+        120 => gcm_with_tag::<consts::U15>(key, iv, aad, data, decrypt),
+        128 => gcm_with_tag::<consts::U16>(key, iv, aad, data, decrypt),
+        _ => bail!("Unsupported AES-GCM tag length: {}", tag_bits),
+    }
+}
+
+fn cbc_encrypt<E: KeyIvInit + BlockEncryptMut>(
+    key: &[u8],
+    iv: &[u8],
+    data: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let cipher = E::new_from_slices(key, iv).map_err(|_| anyhow!("AES-CBC needs a 128-bit iv"))?;
+    Ok(cipher.encrypt_padded_vec_mut::<Pkcs7>(data))
+}
+
+fn cbc_decrypt<D: KeyIvInit + BlockDecryptMut>(
+    key: &[u8],
+    iv: &[u8],
+    data: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let cipher = D::new_from_slices(key, iv).map_err(|_| anyhow!("AES-CBC needs a 128-bit iv"))?;
+    cipher
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .map_err(|_| anyhow!("Invalid AES-CBC padding"))
+}
+
+/// AES-CBC with PKCS#7 padding
+fn aes_cbc(key: &[u8], iv: &[u8], data: &[u8], decrypt: bool) -> anyhow::Result<Vec<u8>> {
+    match (key.len(), decrypt) {
+        (16, false) => cbc_encrypt::<cbc::Encryptor<Aes128>>(key, iv, data),
+        (24, false) => cbc_encrypt::<cbc::Encryptor<Aes192>>(key, iv, data),
+        (32, false) => cbc_encrypt::<cbc::Encryptor<Aes256>>(key, iv, data),
+        (16, true) => cbc_decrypt::<cbc::Decryptor<Aes128>>(key, iv, data),
+        (24, true) => cbc_decrypt::<cbc::Decryptor<Aes192>>(key, iv, data),
+        (32, true) => cbc_decrypt::<cbc::Decryptor<Aes256>>(key, iv, data),
+        _ => Err(aes_key_error(key)),
+    }
+}
+
+fn ctr_with<C: BlockEncrypt + BlockSizeUser<BlockSize = consts::U16> + KeyInit>(
+    key: &[u8],
+    counter: u128,
+    length: u32,
+    data: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let cipher = C::new_from_slice(key).map_err(|_| aes_key_error(key))?;
+    let mask = u128::MAX >> (128 - length);
+    let blocks = data.len().div_ceil(16) as u128;
+    if length < 128 && blocks > mask + 1 {
+        bail!("AES-CTR counter would wrap around and reuse key stream");
+    }
+
+    let mut counter = counter;
+    let mut output = data.to_vec();
+    for chunk in output.chunks_mut(16) {
+        let mut block: aes::Block = counter.to_be_bytes().into();
+        cipher.encrypt_block(&mut block);
+        for (byte, key_byte) in chunk.iter_mut().zip(block.iter()) {
+            *byte ^= key_byte;
+        }
+        counter = (counter & !mask) | (counter.wrapping_add(1) & mask);
+    }
+    Ok(output)
+}
+
+/// AES-CTR where only the rightmost `length` bits of the counter block are
+/// incremented, as in WebCrypto. Encrypting and decrypting are the same.
+fn aes_ctr(key: &[u8], counter: &[u8], length: u32, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let counter: [u8; 16] = counter
+        .try_into()
+        .map_err(|_| anyhow!("AES-CTR needs a 128-bit counter"))?;
+    if !(1..=128).contains(&length) {
+        bail!("AES-CTR counter length must be between 1 and 128 bits");
+    }
+    let counter = u128::from_be_bytes(counter);
+    match key.len() {
+        16 => ctr_with::<Aes128>(key, counter, length, data),
+        24 => ctr_with::<Aes192>(key, counter, length, data),
+        32 => ctr_with::<Aes256>(key, counter, length, data),
+        _ => Err(aes_key_error(key)),
+    }
+}
+
+fn digest(hash: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let digest = match hash {
+        "SHA-1" => Sha1::digest(data).to_vec(),
+        "SHA-256" => Sha256::digest(data).to_vec(),
+        "SHA-384" => Sha384::digest(data).to_vec(),
+        "SHA-512" => Sha512::digest(data).to_vec(),
+        _ => bail!("Unsupported hash: {}", hash),
+    };
+    Ok(digest)
+}
+
+fn invalid_key<E>(_: E) -> anyhow::Error {
+    anyhow!("Invalid key data")
+}
+
+/// The members of a JSON Web Key, already decoded from base64url.
+#[derive(Default)]
+struct Jwk {
+    x: Option<Vec<u8>>,
+    y: Option<Vec<u8>>,
+    d: Option<Vec<u8>>,
+    n: Option<Vec<u8>>,
+    e: Option<Vec<u8>>,
+    p: Option<Vec<u8>>,
+    q: Option<Vec<u8>>,
+}
+
+impl Jwk {
+    fn from_object(object: &Object) -> rquickjs::Result<Jwk> {
+        let field = |name: &str| -> rquickjs::Result<Option<Vec<u8>>> {
+            let buffer = object.get::<_, Option<ArrayBuffer>>(name)?;
+            Ok(buffer.and_then(|b| b.as_bytes().map(<[u8]>::to_vec)))
+        };
+        Ok(Jwk {
+            x: field("x")?,
+            y: field("y")?,
+            d: field("d")?,
+            n: field("n")?,
+            e: field("e")?,
+            p: field("p")?,
+            q: field("q")?,
+        })
+    }
+}
+
+/// An asymmetric key checked and re-encoded in the form the sign and verify
+/// functions take: a SEC1 point or scalar for ECDSA, the 32-byte key for
+/// Ed25519, and SPKI or PKCS#8 DER for RSA.
+struct ImportedKey {
+    private: bool,
+    material: Vec<u8>,
+    /// The modulus length in bits and the public exponent of RSA keys
+    rsa: Option<(usize, Vec<u8>)>,
+}
+
+impl ImportedKey {
+    fn public(material: Vec<u8>) -> ImportedKey {
+        ImportedKey {
+            private: false,
+            material,
+            rsa: None,
+        }
+    }
+
+    fn private(material: Vec<u8>) -> ImportedKey {
+        ImportedKey {
+            private: true,
+            material,
+            rsa: None,
+        }
+    }
+}
+
+fn import_ec(curve: &str, format: &str, data: &[u8], jwk: &Jwk) -> anyhow::Result<ImportedKey> {
+    let key = match (format, curve) {
+        ("raw", "P-256") => ImportedKey::public(
+            p256::PublicKey::from_sec1_bytes(data)
+                .map_err(invalid_key)?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+        ),
+        ("raw", "P-384") => ImportedKey::public(
+            p384::PublicKey::from_sec1_bytes(data)
+                .map_err(invalid_key)?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+        ),
+        ("spki", "P-256") => ImportedKey::public(
+            p256::PublicKey::from_public_key_der(data)
+                .map_err(invalid_key)?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+        ),
+        ("spki", "P-384") => ImportedKey::public(
+            p384::PublicKey::from_public_key_der(data)
+                .map_err(invalid_key)?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+        ),
+        ("pkcs8", "P-256") => ImportedKey::private(
+            p256::SecretKey::from_pkcs8_der(data)
+                .map_err(invalid_key)?
+                .to_bytes()
+                .to_vec(),
+        ),
+        ("pkcs8", "P-384") => ImportedKey::private(
+            p384::SecretKey::from_pkcs8_der(data)
+                .map_err(invalid_key)?
+                .to_bytes()
+                .to_vec(),
+        ),
+        ("jwk", _) => match (&jwk.d, &jwk.x, &jwk.y) {
+            (Some(d), _, _) => {
+                let valid = match curve {
+                    "P-256" => p256::SecretKey::from_slice(d).is_ok(),
+                    _ => p384::SecretKey::from_slice(d).is_ok(),
+                };
+                if !valid {
+                    bail!("Invalid key data");
+                }
+                ImportedKey::private(d.clone())
+            }
+            (None, Some(x), Some(y)) => {
+                let point = [&[0x04], x.as_slice(), y.as_slice()].concat();
+                return import_ec(curve, "raw", &point, jwk);
+            }
+            _ => bail!("EC JWKs need x and y, or d"),
+        },
+        _ => bail!("Unsupported {} key format: {}", curve, format),
+    };
+    Ok(key)
+}
+
+fn ed25519_bytes(data: &[u8]) -> anyhow::Result<[u8; 32]> {
+    data.try_into()
+        .map_err(|_| anyhow!("Ed25519 keys are 32 bytes"))
+}
+
+fn import_ed25519(format: &str, data: &[u8], jwk: &Jwk) -> anyhow::Result<ImportedKey> {
+    let key = match format {
+        "raw" => {
+            let key = ed25519_dalek::VerifyingKey::from_bytes(&ed25519_bytes(data)?)
+                .map_err(invalid_key)?;
+            ImportedKey::public(key.to_bytes().to_vec())
+        }
+        "spki" => {
+            let key =
+                ed25519_dalek::VerifyingKey::from_public_key_der(data).map_err(invalid_key)?;
+            ImportedKey::public(key.to_bytes().to_vec())
+        }
+        "pkcs8" => {
+            let key = ed25519_dalek::SigningKey::from_pkcs8_der(data).map_err(invalid_key)?;
+            ImportedKey::private(key.to_bytes().to_vec())
+        }
+        "jwk" => match (&jwk.d, &jwk.x) {
+            (Some(d), _) => ImportedKey::private(ed25519_bytes(d)?.to_vec()),
+            (None, Some(x)) => return import_ed25519("raw", x, jwk),
+            _ => bail!("OKP JWKs need x or d"),
+        },
+        _ => bail!("Unsupported Ed25519 key format: {}", format),
+    };
+    Ok(key)
+}
+
+/// X25519 keys have no parameters, so their SPKI and PKCS#8 encodings are a
+/// fixed prefix followed by the 32-byte key.
+const X25519_SPKI_PREFIX: &[u8] = &[
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x03, 0x21, 0x00,
+];
+const X25519_PKCS8_PREFIX: &[u8] = &[
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x04, 0x22, 0x04, 0x20,
+];
+
+fn x25519_bytes(data: &[u8]) -> anyhow::Result<[u8; 32]> {
+    data.try_into()
+        .map_err(|_| anyhow!("X25519 keys are 32 bytes"))
+}
+
+fn import_x25519(format: &str, data: &[u8], jwk: &Jwk) -> anyhow::Result<ImportedKey> {
+    let key = match format {
+        "raw" => ImportedKey::public(x25519_bytes(data)?.to_vec()),
+        "spki" => {
+            let key = data
+                .strip_prefix(X25519_SPKI_PREFIX)
+                .ok_or_else(|| anyhow!("Invalid key data"))?;
+            ImportedKey::public(x25519_bytes(key)?.to_vec())
+        }
+        "pkcs8" => {
+            let key = data
+                .strip_prefix(X25519_PKCS8_PREFIX)
+                .ok_or_else(|| anyhow!("Invalid key data"))?;
+            ImportedKey::private(x25519_bytes(key)?.to_vec())
+        }
+        "jwk" => match (&jwk.d, &jwk.x) {
+            (Some(d), _) => ImportedKey::private(x25519_bytes(d)?.to_vec()),
+            (None, Some(x)) => ImportedKey::public(x25519_bytes(x)?.to_vec()),
+            _ => bail!("OKP JWKs need x or d"),
+        },
+        _ => bail!("Unsupported X25519 key format: {}", format),
+    };
+    Ok(key)
+}
+
+fn import_rsa(format: &str, data: &[u8], jwk: &Jwk) -> anyhow::Result<ImportedKey> {
+    let (private, public) = match format {
+        "spki" => (
+            None,
+            RsaPublicKey::from_public_key_der(data).map_err(invalid_key)?,
+        ),
+        "pkcs8" => {
+            let key = RsaPrivateKey::from_pkcs8_der(data).map_err(invalid_key)?;
+            let public = key.to_public_key();
+            (Some(key), public)
+        }
+        "jwk" => {
+            let (Some(n), Some(e)) = (&jwk.n, &jwk.e) else {
+                bail!("RSA JWKs need n and e");
+            };
+            let n = BigUint::from_bytes_be(n);
+            let e = BigUint::from_bytes_be(e);
+            match (&jwk.d, &jwk.p, &jwk.q) {
+                (Some(d), Some(p), Some(q)) => {
+                    let primes = vec![BigUint::from_bytes_be(p), BigUint::from_bytes_be(q)];
+                    let key =
+                        RsaPrivateKey::from_components(n, e, BigUint::from_bytes_be(d), primes)
+                            .map_err(invalid_key)?;
+                    key.validate().map_err(invalid_key)?;
+                    let public = key.to_public_key();
+                    (Some(key), public)
+                }
+                (Some(_), _, _) => bail!("RSA private JWKs need p and q"),
+                _ => (None, RsaPublicKey::new(n, e).map_err(invalid_key)?),
+            }
+        }
+        _ => bail!("Unsupported RSA key format: {}", format),
+    };
+
+    let material = match &private {
+        Some(key) => key.to_pkcs8_der()?.as_bytes().to_vec(),
+        None => public.to_public_key_der()?.as_bytes().to_vec(),
+    };
+    Ok(ImportedKey {
+        private: private.is_some(),
+        material,
+        rsa: Some((public.n().bits(), public.e().to_bytes_be())),
+    })
+}
+
+fn import_key(
+    algorithm: &str,
+    curve: &str,
+    format: &str,
+    data: &[u8],
+    jwk: &Jwk,
+) -> anyhow::Result<ImportedKey> {
+    match algorithm {
+        "ECDSA" | "ECDH" => import_ec(curve, format, data, jwk),
+        "Ed25519" => import_ed25519(format, data, jwk),
+        "X25519" => import_x25519(format, data, jwk),
+        "RSASSA-PKCS1-v1_5" | "RSA-PSS" => import_rsa(format, data, jwk),
+        _ => bail!("Unsupported algorithm: {}", algorithm),
+    }
+}
+
+/// PKCS#1 v1.5 when `salt_length` is `None`, otherwise PSS
+fn rsa_sign_with<D: Digest + AssociatedOid + FixedOutputReset>(
+    key: &[u8],
+    salt_length: Option<usize>,
+    data: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let key = RsaPrivateKey::from_pkcs8_der(key).map_err(invalid_key)?;
+    let signature = match salt_length {
+        None => rsa::pkcs1v15::SigningKey::<D>::new(key)
+            .try_sign(data)?
+            .to_vec(),
+        Some(salt_length) => rsa::pss::SigningKey::<D>::new_with_salt_len(key, salt_length)
+            .try_sign_with_rng(&mut EngineRng, data)?
+            .to_vec(),
+    };
+    Ok(signature)
+}
+
+fn rsa_verify_with<D: Digest + AssociatedOid + FixedOutputReset>(
+    key: &[u8],
+    salt_length: Option<usize>,
+    data: &[u8],
+    signature: &[u8],
+) -> anyhow::Result<bool> {
+    let key = RsaPublicKey::from_public_key_der(key).map_err(invalid_key)?;
+    let valid = match salt_length {
+        None => rsa::pkcs1v15::Signature::try_from(signature).is_ok_and(|signature| {
+            rsa::pkcs1v15::VerifyingKey::<D>::new(key)
+                .verify(data, &signature)
+                .is_ok()
+        }),
+        Some(salt_length) => rsa::pss::Signature::try_from(signature).is_ok_and(|signature| {
+            rsa::pss::VerifyingKey::<D>::new_with_salt_len(key, salt_length)
+                .verify(data, &signature)
+                .is_ok()
+        }),
+    };
+    Ok(valid)
+}
+
+/// Signs with an imported private key. ECDSA signatures are the fixed-size
+/// `r || s` encoding WebCrypto uses, not DER.
+fn asymmetric_sign(
+    algorithm: &str,
+    curve: &str,
+    hash: &str,
+    salt_length: Option<usize>,
+    key: &[u8],
+    data: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let signature = match algorithm {
+        "ECDSA" => {
+            let prehash = digest(hash, data)?;
+            match curve {
+                "P-256" => {
+                    let key = p256::ecdsa::SigningKey::from_slice(key).map_err(invalid_key)?;
+                    let signature: p256::ecdsa::Signature = key.sign_prehash(&prehash)?;
+                    signature.to_bytes().to_vec()
+                }
+                "P-384" => {
+                    let key = p384::ecdsa::SigningKey::from_slice(key).map_err(invalid_key)?;
+                    let signature: p384::ecdsa::Signature = key.sign_prehash(&prehash)?;
+                    signature.to_bytes().to_vec()
+                }
+                _ => bail!("Unsupported curve: {}", curve),
+            }
+        }
+        "Ed25519" => {
+            let key = ed25519_dalek::SigningKey::from_bytes(&ed25519_bytes(key)?);
+            key.sign(data).to_bytes().to_vec()
+        }
+        "RSASSA-PKCS1-v1_5" | "RSA-PSS" => match hash {
+            "SHA-1" => rsa_sign_with::<Sha1>(key, salt_length, data)?,
+            "SHA-256" => rsa_sign_with::<Sha256>(key, salt_length, data)?,
+            "SHA-384" => rsa_sign_with::<Sha384>(key, salt_length, data)?,
+            "SHA-512" => rsa_sign_with::<Sha512>(key, salt_length, data)?,
+            _ => bail!("Unsupported hash: {}", hash),
+        },
+        _ => bail!("Unsupported algorithm: {}", algorithm),
+    };
+    Ok(signature)
+}
+
+/// Verifies with an imported public key. A malformed signature is reported
+/// as invalid rather than as an error, as WebCrypto does.
+fn asymmetric_verify(
+    algorithm: &str,
+    curve: &str,
+    hash: &str,
+    salt_length: Option<usize>,
+    key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> anyhow::Result<bool> {
+    let valid = match algorithm {
+        "ECDSA" => {
+            let prehash = digest(hash, data)?;
+            match curve {
+                "P-256" => {
+                    let key =
+                        p256::ecdsa::VerifyingKey::from_sec1_bytes(key).map_err(invalid_key)?;
+                    p256::ecdsa::Signature::from_slice(signature)
+                        .is_ok_and(|signature| key.verify_prehash(&prehash, &signature).is_ok())
+                }
+                "P-384" => {
+                    let key =
+                        p384::ecdsa::VerifyingKey::from_sec1_bytes(key).map_err(invalid_key)?;
+                    p384::ecdsa::Signature::from_slice(signature)
+                        .is_ok_and(|signature| key.verify_prehash(&prehash, &signature).is_ok())
+                }
+                _ => bail!("Unsupported curve: {}", curve),
+            }
+        }
+        "Ed25519" => {
+            let key = ed25519_dalek::VerifyingKey::from_bytes(&ed25519_bytes(key)?)
+                .map_err(invalid_key)?;
+            ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|signature| key.verify(data, &signature).is_ok())
+        }
+        "RSASSA-PKCS1-v1_5" | "RSA-PSS" => match hash {
+            "SHA-1" => rsa_verify_with::<Sha1>(key, salt_length, data, signature)?,
+            "SHA-256" => rsa_verify_with::<Sha256>(key, salt_length, data, signature)?,
+            "SHA-384" => rsa_verify_with::<Sha384>(key, salt_length, data, signature)?,
+            "SHA-512" => rsa_verify_with::<Sha512>(key, salt_length, data, signature)?,
+            _ => bail!("Unsupported hash: {}", hash),
+        },
+        _ => bail!("Unsupported algorithm: {}", algorithm),
+    };
+    Ok(valid)
+}
+
+/// The digest size of a hash `digest` supports, in bytes.
+fn hash_output_size(hash: &str) -> anyhow::Result<usize> {
+    let size = match hash {
+        "SHA-1" => <Sha1 as Digest>::output_size(),
+        "SHA-256" => <Sha256 as Digest>::output_size(),
+        "SHA-384" => <Sha384 as Digest>::output_size(),
+        "SHA-512" => <Sha512 as Digest>::output_size(),
+        _ => bail!("Unsupported hash: {}", hash),
+    };
+    Ok(size)
+}
+
+/// The most bytes PBKDF2 derives: WebCrypto takes the length in bits as an
+/// unsigned 32-bit integer.
+const PBKDF2_MAX_LENGTH: usize = u32::MAX as usize / 8;
+
+fn pbkdf2(
+    hash: &str,
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    length: usize,
+) -> anyhow::Result<Vec<u8>> {
+    if iterations == 0 {
+        bail!("PBKDF2 needs a positive iteration count");
+    }
+    if length == 0 || length > PBKDF2_MAX_LENGTH {
+        bail!(
+            "PBKDF2 can derive between 1 and {} bytes, not {}",
+            PBKDF2_MAX_LENGTH,
+            length
+        );
+    }
+    let mut output = vec![0; length];
+    match hash {
+        "SHA-1" => pbkdf2_hmac::<Sha1>(password, salt, iterations, &mut output),
+        "SHA-256" => pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut output),
+        "SHA-384" => pbkdf2_hmac::<Sha384>(password, salt, iterations, &mut output),
+        "SHA-512" => pbkdf2_hmac::<Sha512>(password, salt, iterations, &mut output),
+        _ => bail!("Unsupported hash: {}", hash),
+    }
+    Ok(output)
+}
+
+fn hkdf(
+    hash: &str,
+    key: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    length: usize,
+) -> anyhow::Result<Vec<u8>> {
+    // RFC 5869 caps the output at 255 blocks of the hash
+    let max_length = 255 * hash_output_size(hash)?;
+    if length == 0 || length > max_length {
+        bail!(
+            "HKDF can derive between 1 and {} bytes with {}, not {}",
+            max_length,
+            hash,
+            length
+        );
+    }
+    let mut output = vec![0; length];
+    let expanded = match hash {
+        "SHA-1" => Hkdf::<Sha1>::new(Some(salt), key).expand(info, &mut output),
+        "SHA-256" => Hkdf::<Sha256>::new(Some(salt), key).expand(info, &mut output),
+        "SHA-384" => Hkdf::<Sha384>::new(Some(salt), key).expand(info, &mut output),
+        "SHA-512" => Hkdf::<Sha512>::new(Some(salt), key).expand(info, &mut output),
+        _ => bail!("Unsupported hash: {}", hash),
+    };
+    expanded.map_err(|_| anyhow!("HKDF can't derive {} bytes with {}", length, hash))?;
+    Ok(output)
+}
+
+/// The raw shared secret of an ECDH or X25519 private key and a peer's
+/// public key, in the forms `import_key` produces.
+fn ecdh(curve: &str, private: &[u8], public: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let secret = match curve {
+        "P-256" => {
+            let private = p256::SecretKey::from_slice(private).map_err(invalid_key)?;
+            let public = p256::PublicKey::from_sec1_bytes(public).map_err(invalid_key)?;
+            p256::ecdh::diffie_hellman(private.to_nonzero_scalar(), public.as_affine())
+                .raw_secret_bytes()
+                .to_vec()
+        }
+        "P-384" => {
+            let private = p384::SecretKey::from_slice(private).map_err(invalid_key)?;
+            let public = p384::PublicKey::from_sec1_bytes(public).map_err(invalid_key)?;
+            p384::ecdh::diffie_hellman(private.to_nonzero_scalar(), public.as_affine())
+                .raw_secret_bytes()
+                .to_vec()
+        }
+        "X25519" => {
+            let private = x25519_dalek::StaticSecret::from(x25519_bytes(private)?);
+            let public = x25519_dalek::PublicKey::from(x25519_bytes(public)?);
+            let shared = private.diffie_hellman(&public);
+            // a small-order public key gives an all-zero secret
+            if !shared.was_contributory() {
+                bail!("X25519 public key has small order");
+            }
+            shared.as_bytes().to_vec()
+        }
+        _ => bail!("Unsupported curve: {}", curve),
+    };
+    Ok(secret)
+}
+
+/// A fresh key pair as (public, private) material, for ECDH and X25519.
+fn generate_key_pair(curve: &str) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let pair = match curve {
+        "P-256" => {
+            let private = p256::SecretKey::random(&mut EngineRng);
+            let public = private.public_key().to_encoded_point(false);
+            (public.as_bytes().to_vec(), private.to_bytes().to_vec())
+        }
+        "P-384" => {
+            let private = p384::SecretKey::random(&mut EngineRng);
+            let public = private.public_key().to_encoded_point(false);
+            (public.as_bytes().to_vec(), private.to_bytes().to_vec())
+        }
+        "X25519" => {
+            let private = x25519_dalek::StaticSecret::random_from_rng(EngineRng);
+            let public = x25519_dalek::PublicKey::from(&private);
+            (public.as_bytes().to_vec(), private.to_bytes().to_vec())
+        }
+        _ => bail!("Unsupported curve: {}", curve),
+    };
+    Ok(pair)
+}
+
+/// The PSS salt length, which is only passed for RSA-PSS
+fn salt_length_arg(args: &Rest<Value>, idx: usize) -> Option<usize> {
+    args.get(idx)
+        .and_then(|v| v.as_number())
+        .map(|n| n as usize)
+}
+
+/// Builds the `__subtle` object that backs the key operations of
+/// `crypto.subtle`.
+pub(crate) fn build_subtle_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
+    let hmac_sign_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let hash = string_arg(&cx, &args, 0, "hash")?;
+            let key = buffer_arg(&cx, &args, 1, "key")?;
+            let data = buffer_arg(&cx, &args, 2, "data")?;
+            let signature = hmac_sign(&hash, buffer_bytes(&cx, &key)?, buffer_bytes(&cx, &data)?)
+                .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, signature)
+        }),
+    )?;
+
+    let hmac_verify_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let hash = string_arg(&cx, &args, 0, "hash")?;
+            let key = buffer_arg(&cx, &args, 1, "key")?;
+            let data = buffer_arg(&cx, &args, 2, "data")?;
+            let signature = buffer_arg(&cx, &args, 3, "signature")?;
+            hmac_verify(
+                &hash,
+                buffer_bytes(&cx, &key)?,
+                buffer_bytes(&cx, &data)?,
+                buffer_bytes(&cx, &signature)?,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let aes_gcm_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let decrypt = bool_arg(&args, 0);
+            let key = buffer_arg(&cx, &args, 1, "key")?;
+            let iv = buffer_arg(&cx, &args, 2, "iv")?;
+            let aad = buffer_arg(&cx, &args, 3, "additionalData")?;
+            let tag_bits = number_arg(&cx, &args, 4, "tagLength")? as u32;
+            let data = buffer_arg(&cx, &args, 5, "data")?;
+            let output = aes_gcm(
+                buffer_bytes(&cx, &key)?,
+                buffer_bytes(&cx, &iv)?,
+                buffer_bytes(&cx, &aad)?,
+                tag_bits,
+                buffer_bytes(&cx, &data)?,
+                decrypt,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, output)
+        }),
+    )?;
+
+    let aes_cbc_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let decrypt = bool_arg(&args, 0);
+            let key = buffer_arg(&cx, &args, 1, "key")?;
+            let iv = buffer_arg(&cx, &args, 2, "iv")?;
+            let data = buffer_arg(&cx, &args, 3, "data")?;
+            let output = aes_cbc(
+                buffer_bytes(&cx, &key)?,
+                buffer_bytes(&cx, &iv)?,
+                buffer_bytes(&cx, &data)?,
+                decrypt,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, output)
+        }),
+    )?;
+
+    let aes_ctr_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let key = buffer_arg(&cx, &args, 0, "key")?;
+            let counter = buffer_arg(&cx, &args, 1, "counter")?;
+            let length = number_arg(&cx, &args, 2, "length")? as u32;
+            let data = buffer_arg(&cx, &args, 3, "data")?;
+            let output = aes_ctr(
+                buffer_bytes(&cx, &key)?,
+                buffer_bytes(&cx, &counter)?,
+                length,
+                buffer_bytes(&cx, &data)?,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, output)
+        }),
+    )?;
+
+    let import_key_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let algorithm = string_arg(&cx, &args, 0, "algorithm")?;
+            let curve = string_arg(&cx, &args, 1, "curve")?;
+            let format = string_arg(&cx, &args, 2, "format")?;
+            let data = args
+                .get(3)
+                .cloned()
+                .unwrap_or_else(|| Value::new_undefined(cx.clone()));
+
+            // JWKs arrive as an object of decoded members, the rest as bytes
+            let (bytes, jwk) = match ArrayBuffer::from_value(data.clone()) {
+                Some(buffer) => (buffer_bytes(&cx, &buffer)?.to_vec(), Jwk::default()),
+                None => {
+                    let object = data
+                        .as_object()
+                        .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected key data")))?;
+                    (Vec::new(), Jwk::from_object(object)?)
+                }
+            };
+            let imported = import_key(&algorithm, &curve, &format, &bytes, &jwk)
+                .map_err(|e| to_js_error(cx.clone(), e))?;
+
+            let key = Object::new(cx.clone())?;
+            key.set("private", imported.private)?;
+            key.set("material", ArrayBuffer::new(cx.clone(), imported.material)?)?;
+            if let Some((modulus_length, public_exponent)) = imported.rsa {
+                key.set("modulusLength", modulus_length as f64)?;
+                key.set(
+                    "publicExponent",
+                    ArrayBuffer::new(cx.clone(), public_exponent)?,
+                )?;
+            }
+            Ok::<_, rquickjs::Error>(key)
+        }),
+    )?;
+
+    let asymmetric_sign_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let algorithm = string_arg(&cx, &args, 0, "algorithm")?;
+            let curve = string_arg(&cx, &args, 1, "curve")?;
+            let hash = string_arg(&cx, &args, 2, "hash")?;
+            let salt_length = salt_length_arg(&args, 3);
+            let key = buffer_arg(&cx, &args, 4, "key")?;
+            let data = buffer_arg(&cx, &args, 5, "data")?;
+            let signature = asymmetric_sign(
+                &algorithm,
+                &curve,
+                &hash,
+                salt_length,
+                buffer_bytes(&cx, &key)?,
+                buffer_bytes(&cx, &data)?,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, signature)
+        }),
+    )?;
+
+    let asymmetric_verify_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let algorithm = string_arg(&cx, &args, 0, "algorithm")?;
+            let curve = string_arg(&cx, &args, 1, "curve")?;
+            let hash = string_arg(&cx, &args, 2, "hash")?;
+            let salt_length = salt_length_arg(&args, 3);
+            let key = buffer_arg(&cx, &args, 4, "key")?;
+            let data = buffer_arg(&cx, &args, 5, "data")?;
+            let signature = buffer_arg(&cx, &args, 6, "signature")?;
+            asymmetric_verify(
+                &algorithm,
+                &curve,
+                &hash,
+                salt_length,
+                buffer_bytes(&cx, &key)?,
+                buffer_bytes(&cx, &data)?,
+                buffer_bytes(&cx, &signature)?,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let pbkdf2_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let hash = string_arg(&cx, &args, 0, "hash")?;
+            let password = buffer_arg(&cx, &args, 1, "password")?;
+            let salt = buffer_arg(&cx, &args, 2, "salt")?;
+            let iterations = number_arg(&cx, &args, 3, "iterations")? as u32;
+            let length = number_arg(&cx, &args, 4, "length")? as usize;
+            let output = pbkdf2(
+                &hash,
+                buffer_bytes(&cx, &password)?,
+                buffer_bytes(&cx, &salt)?,
+                iterations,
+                length,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, output)
+        }),
+    )?;
+
+    let hkdf_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let hash = string_arg(&cx, &args, 0, "hash")?;
+            let key = buffer_arg(&cx, &args, 1, "key")?;
+            let salt = buffer_arg(&cx, &args, 2, "salt")?;
+            let info = buffer_arg(&cx, &args, 3, "info")?;
+            let length = number_arg(&cx, &args, 4, "length")? as usize;
+            let output = hkdf(
+                &hash,
+                buffer_bytes(&cx, &key)?,
+                buffer_bytes(&cx, &salt)?,
+                buffer_bytes(&cx, &info)?,
+                length,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, output)
+        }),
+    )?;
+
+    let ecdh_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let curve = string_arg(&cx, &args, 0, "curve")?;
+            let private = buffer_arg(&cx, &args, 1, "private key")?;
+            let public = buffer_arg(&cx, &args, 2, "public key")?;
+            let secret = ecdh(
+                &curve,
+                buffer_bytes(&cx, &private)?,
+                buffer_bytes(&cx, &public)?,
+            )
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, secret)
+        }),
+    )?;
+
+    let generate_key_pair_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let curve = string_arg(&cx, &args, 0, "curve")?;
+            let (public, private) =
+                generate_key_pair(&curve).map_err(|e| to_js_error(cx.clone(), e))?;
+            let pair = Object::new(cx.clone())?;
+            pair.set("publicKey", ArrayBuffer::new(cx.clone(), public)?)?;
+            pair.set("privateKey", ArrayBuffer::new(cx.clone(), private)?)?;
+            Ok::<_, rquickjs::Error>(pair)
+        }),
+    )?;
+
+    let subtle = Object::new(this)?;
+    subtle.set("hmacSign", hmac_sign_fn)?;
+    subtle.set("hmacVerify", hmac_verify_fn)?;
+    subtle.set("aesGcm", aes_gcm_fn)?;
+    subtle.set("aesCbc", aes_cbc_fn)?;
+    subtle.set("aesCtr", aes_ctr_fn)?;
+    subtle.set("importKey", import_key_fn)?;
+    subtle.set("asymmetricSign", asymmetric_sign_fn)?;
+    subtle.set("asymmetricVerify", asymmetric_verify_fn)?;
+    subtle.set("pbkdf2", pbkdf2_fn)?;
+    subtle.set("hkdf", hkdf_fn)?;
+    subtle.set("ecdh", ecdh_fn)?;
+    subtle.set("generateKeyPair", generate_key_pair_fn)?;
+    Ok(subtle)
+}
@@ -0,0 +1,259 @@
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, SecondsFormat, Utc};
+use extism_pdk::config;
+#[cfg(feature = "webcrypto")]
+use rand_core::{CryptoRng, RngCore};
+use rquickjs::{function::MutFn, prelude::*, Ctx, Function, Object, Value};
+
+/// A clock that only moves when it's read, by `step_ms` each time.
+#[derive(Clone)]
+struct VirtualClock {
+    now_ms: f64,
+    step_ms: f64,
+}
+
+/// SplitMix64: small, fast and fully determined by its seed. It is not a
+/// cryptographic generator, which is fine for reproducible test runs.
+#[derive(Clone)]
+struct SeededRng(u64);
+
+impl SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+// Engines built with the `deterministic` feature start at the epoch with seed
+// 0, so the snapshot itself is reproducible. Config can change both later.
+const fn default_clock() -> Option<VirtualClock> {
+    if cfg!(feature = "deterministic") {
+        Some(VirtualClock {
+            now_ms: 0.0,
+            step_ms: 0.0,
+        })
+    } else {
+        None
+    }
+}
+
+const fn default_rng() -> Option<SeededRng> {
+    if cfg!(feature = "deterministic") {
+        Some(SeededRng(0))
+    } else {
+        None
+    }
+}
+
+static CLOCK: Mutex<Option<VirtualClock>> = Mutex::new(default_clock());
+static RNG: Mutex<Option<SeededRng>> = Mutex::new(default_rng());
+static TIME_ORIGIN: Mutex<Option<f64>> = Mutex::new(None);
+/// `Math.random`'s generator outside deterministic mode. QuickJS seeds its own
+/// when the engine is wizened, which would give every instance the same
+/// sequence, so this one is reseeded from the OS when the engine resumes.
+static MATH_RNG: Mutex<SeededRng> = Mutex::new(SeededRng(0));
+/// The clock and seeded generator as `configure` left them, which every
+/// isolated call starts from.
+static CONFIGURED: Mutex<Option<(Option<VirtualClock>, Option<SeededRng>)>> = Mutex::new(None);
+
+/// Refreshes the state the snapshot froze: `Math.random`'s seed and the time
+/// `performance.now()` is measured from. Runs before the first call, ahead
+/// of `configure`.
+pub(crate) fn resume() -> anyhow::Result<()> {
+    let mut seed = [0u8; 8];
+    getrandom::getrandom(&mut seed).map_err(|e| anyhow!("getrandom failed: {}", e))?;
+    *MATH_RNG.lock().unwrap() = SeededRng(u64::from_le_bytes(seed));
+    let now = now_ms();
+    *TIME_ORIGIN.lock().unwrap() = Some(now);
+    Ok(())
+}
+
+/// Applies the `js.seed`, `js.fixed_time` and `js.time_step` config keys.
+/// Config can't be read while the engine is being wizened, so this runs
+/// before the first call.
+pub(crate) fn configure() -> anyhow::Result<()> {
+    if let Some(seed) = config::get("js.seed")? {
+        // Seeding would make crypto.getRandomValues and generated keys
+        // predictable, so release engines keep drawing from the OS
+        if cfg!(feature = "deterministic") {
+            let seed = seed
+                .trim()
+                .parse::<u64>()
+                .with_context(|| format!("Invalid js.seed {:?}: expected an integer", seed))?;
+            *RNG.lock().unwrap() = Some(SeededRng(seed));
+        } else {
+            extism_pdk::warn!(
+                "Ignoring js.seed: the engine wasn't built with the `deterministic` feature"
+            );
+        }
+    }
+
+    if let Some(time) = config::get("js.fixed_time")? {
+        let now_ms = parse_time(&time)?;
+        let mut clock = CLOCK.lock().unwrap();
+        let step_ms = clock.as_ref().map_or(0.0, |clock| clock.step_ms);
+        *clock = Some(VirtualClock { now_ms, step_ms });
+        *TIME_ORIGIN.lock().unwrap() = Some(now_ms);
+    }
+
+    if let Some(step) = config::get("js.time_step")? {
+        let step_ms = step
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|step| step.is_finite() && *step >= 0.0)
+            .ok_or_else(|| anyhow!("Invalid js.time_step {:?}: expected milliseconds", step))?;
+        match CLOCK.lock().unwrap().as_mut() {
+            Some(clock) => clock.step_ms = step_ms,
+            None => bail!("js.time_step needs js.fixed_time to be set"),
+        }
+    }
+
+    let configured = (CLOCK.lock().unwrap().clone(), RNG.lock().unwrap().clone());
+    *CONFIGURED.lock().unwrap() = Some(configured);
+    Ok(())
+}
+
+/// Runs `f` with the clock and seeded generator as they were configured, so
+/// an isolated call sees the same times and random values on every run. The
+/// ones other calls use pick up where they left off afterwards.
+pub(crate) fn isolated<R>(f: impl FnOnce() -> R) -> R {
+    let Some((clock, rng)) = CONFIGURED.lock().unwrap().clone() else {
+        return f();
+    };
+    let clock = std::mem::replace(&mut *CLOCK.lock().unwrap(), clock);
+    let rng = std::mem::replace(&mut *RNG.lock().unwrap(), rng);
+    let result = f();
+    *CLOCK.lock().unwrap() = clock;
+    *RNG.lock().unwrap() = rng;
+    result
+}
+
+/// Replaces QuickJS's `Math.random` with one the engine can reseed, which
+/// draws from the seeded generator in deterministic mode.
+pub(crate) fn install_math_random(cx: &Ctx) -> anyhow::Result<()> {
+    let random = Function::new(
+        cx.clone(),
+        MutFn::new(|| {
+            let bits = match RNG.lock().unwrap().as_mut() {
+                Some(rng) => rng.next_u64(),
+                None => MATH_RNG.lock().unwrap().next_u64(),
+            };
+            // the top 53 bits make a uniformly distributed double in [0, 1)
+            (bits >> 11) as f64 / (1u64 << 53) as f64
+        }),
+    )?;
+    let math: Object = cx.globals().get("Math")?;
+    math.set("random", random)?;
+    Ok(())
+}
+
+/// Parses `js.fixed_time`: milliseconds since the epoch or an RFC 3339 date-time.
+fn parse_time(value: &str) -> anyhow::Result<f64> {
+    let value = value.trim();
+    let ms = match value.parse::<f64>() {
+        Ok(ms) => ms.floor(),
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .map(|time| time.timestamp_millis() as f64)
+            .map_err(|_| {
+                anyhow!(
+                    "Invalid js.fixed_time {:?}: expected milliseconds since the epoch or an RFC 3339 date-time",
+                    value
+                )
+            })?,
+    };
+    if !ms.is_finite() || DateTime::from_timestamp_millis(ms as i64).is_none() {
+        bail!("js.fixed_time {:?} is out of range", value);
+    }
+    Ok(ms)
+}
+
+/// The current time in milliseconds since the epoch, from the virtual clock
+/// in deterministic mode.
+pub(crate) fn now_ms() -> f64 {
+    match CLOCK.lock().unwrap().as_mut() {
+        Some(clock) => {
+            let now = clock.now_ms;
+            clock.now_ms += clock.step_ms;
+            now
+        }
+        None => Utc::now().timestamp_millis() as f64,
+    }
+}
+
+/// The current time as an RFC 3339 string, which `Date` can parse.
+pub(crate) fn now_rfc3339() -> String {
+    DateTime::from_timestamp_millis(now_ms() as i64)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// The time `performance.now()` is measured from.
+pub(crate) fn time_origin_ms() -> f64 {
+    *TIME_ORIGIN.lock().unwrap().get_or_insert_with(now_ms)
+}
+
+/// Fills `buf` from the seeded generator in deterministic mode and from the
+/// OS otherwise.
+pub(crate) fn fill_random(buf: &mut [u8]) -> anyhow::Result<()> {
+    fill(buf).map_err(|e| anyhow!("getrandom failed: {}", e))
+}
+
+fn fill(buf: &mut [u8]) -> Result<(), getrandom::Error> {
+    match RNG.lock().unwrap().as_mut() {
+        Some(rng) => {
+            rng.fill(buf);
+            Ok(())
+        }
+        None => getrandom::getrandom(buf),
+    }
+}
+
+/// The random source for key generation and signing. Only as secure as
+/// `crypto.getRandomValues`: seeded, and so predictable, in deterministic mode.
+#[cfg(feature = "webcrypto")]
+pub(crate) struct EngineRng;
+
+#[cfg(feature = "webcrypto")]
+impl RngCore for EngineRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill(dest).expect("Couldn't get random bytes")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        fill(dest).map_err(rand_core::Error::from)
+    }
+}
+
+#[cfg(feature = "webcrypto")]
+impl CryptoRng for EngineRng {}
+
+/// Builds `__getTimeOrigin`, which `performance` reads its origin from.
+pub(crate) fn build_time_origin<'js>(this: Ctx<'js>) -> rquickjs::Result<Function<'js>> {
+    Function::new(
+        this,
+        MutFn::new(|cx: Ctx<'js>, _args: Rest<Value<'js>>| {
+            Ok::<_, rquickjs::Error>(Value::new_float(cx, time_origin_ms()))
+        }),
+    )
+}
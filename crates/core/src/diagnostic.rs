@@ -0,0 +1,111 @@
+use std::fmt;
+
+use rquickjs::{Ctx, Value};
+
+/// The file name the user's script is evaluated under, which its stack
+/// frames carry. The prelude's frames don't.
+pub(crate) const SCRIPT_NAME: &str = "script.js";
+
+/// Starts every line of the report the CLI reads back when initialization
+/// fails. Keep in sync with `crates/cli/src/diagnostic.rs`.
+const PREFIX: &str = "extism-js:diagnostic:";
+
+/// An error from running JavaScript, kept in parts so a failed build can
+/// point at the line it came from.
+pub(crate) enum ScriptError {
+    /// A thrown value. Only `Error` objects have a name and a stack.
+    Exception {
+        name: Option<String>,
+        message: String,
+        stack: Option<String>,
+    },
+    Other(String),
+}
+
+impl ScriptError {
+    pub(crate) fn from_caught(caught: Value) -> Self {
+        match caught.as_exception() {
+            Some(err) => ScriptError::Exception {
+                name: err.as_object().get::<_, String>("name").ok(),
+                message: err.message().unwrap_or_default(),
+                stack: Some(err.stack().unwrap_or_default()),
+            },
+            // The caught value is not a JS Error object. It could be a string,
+            // number, null, or an uninitialized value from an async context.
+            None => ScriptError::Exception {
+                name: None,
+                message: format!("{:?}", caught),
+                stack: None,
+            },
+        }
+    }
+
+    pub(crate) fn from_error(ctx: &Ctx, err: rquickjs::Error) -> Self {
+        match err {
+            rquickjs::Error::Exception => ScriptError::from_caught(ctx.catch()),
+            err => ScriptError::Other(err.to_string()),
+        }
+    }
+
+    /// The line and column of the innermost stack frame in the user's script,
+    /// counted from the start of what the engine evaluated.
+    fn location(&self) -> Option<(u32, u32)> {
+        let ScriptError::Exception {
+            stack: Some(stack), ..
+        } = self
+        else {
+            return None;
+        };
+        let file = format!("{}:", SCRIPT_NAME);
+        stack.lines().find_map(|frame| {
+            let (_, position) = frame.split_once(&file)?;
+            let mut numbers = position.split(|c: char| !c.is_ascii_digit());
+            let line = numbers.next()?.parse().ok()?;
+            let column = numbers.next()?.parse().ok()?;
+            Some((line, column))
+        })
+    }
+
+    /// Writes the error to stderr as `<prefix><key> <value>` lines, one per
+    /// line of each value, for the CLI to print as a diagnostic.
+    pub(crate) fn report(&self) {
+        let write = |key: &str, value: &str| {
+            for line in value.lines() {
+                eprintln!("{}{} {}", PREFIX, key, line);
+            }
+        };
+        match self {
+            ScriptError::Exception {
+                name,
+                message,
+                stack,
+            } => {
+                if let Some(name) = name {
+                    write("name", name);
+                }
+                write("message", message);
+                if let Some(stack) = stack {
+                    write("stack", stack);
+                }
+            }
+            ScriptError::Other(message) => write("message", message),
+        }
+        if let Some((line, column)) = self.location() {
+            write("location", &format!("{}:{}", line, column));
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Exception {
+                message,
+                stack: Some(stack),
+                ..
+            } => write!(f, "Exception: {}\n{}", message, stack),
+            ScriptError::Exception { message, .. } => write!(f, "Exception: {}", message),
+            ScriptError::Other(message) => f.write_str(message),
+        }
+    }
+}
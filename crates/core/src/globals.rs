@@ -1,7 +1,6 @@
 use std::{borrow::Cow, str::from_utf8};
 
-use anyhow::{anyhow, Context};
-use chrono::{SecondsFormat, Utc};
+use anyhow::{anyhow, bail, Context};
 use extism_pdk::extism::load_input;
 use extism_pdk::*;
 use rquickjs::{
@@ -9,6 +8,8 @@ use rquickjs::{
     Function, IntoJs, Null, Object, Undefined, Value,
 };
 
+use crate::signature::{MAX_HOST_PARAMS, SIGNATURE_COUNT_BITS, SIGNATURE_TYPE_BITS};
+
 static PRELUDE: &[u8] = include_bytes!("prelude/dist/index.js"); // if this panics, run `make` from the root
 
 pub fn inject_globals(context: &JSContext) -> anyhow::Result<()> {
@@ -37,10 +38,46 @@ pub fn inject_globals(context: &JSContext) -> anyhow::Result<()> {
         global.set("Memory", mem)?;
         global.set("__decodeUtf8BufferToString", decoder)?;
         global.set("__encodeStringToUtf8Buffer", encoder)?;
+        global.set(
+            "__textDecoding",
+            build_text_decoding_object(this.clone()).map_err(|e| to_js_error(this.clone(), e))?,
+        )?;
         global.set("__getTime", clock)?;
         global.set("__getTimeMs", clock_ms)?;
+        global.set(
+            "__getTimeOrigin",
+            crate::determinism::build_time_origin(this.clone())?,
+        )?;
         global.set("__getRandomBytes", random_bytes)?;
         global.set("__shaDigest", sha_digest)?;
+        #[cfg(feature = "hashes")]
+        global.set(
+            "__hash",
+            crate::hash::build_hash_object(this.clone())
+                .map_err(|e| to_js_error(this.clone(), e))?,
+        )?;
+        #[cfg(feature = "webcrypto")]
+        global.set(
+            "__subtle",
+            crate::crypto::build_subtle_object(this.clone())
+                .map_err(|e| to_js_error(this.clone(), e))?,
+        )?;
+        global.set(
+            "__tz",
+            crate::tz::build_tz_object(this.clone()).map_err(|e| to_js_error(this.clone(), e))?,
+        )?;
+        #[cfg(feature = "compression")]
+        global.set(
+            "__compression",
+            crate::compression::build_compression_object(this.clone())
+                .map_err(|e| to_js_error(this.clone(), e))?,
+        )?;
+        #[cfg(feature = "intl")]
+        global.set(
+            "__intl",
+            crate::intl::build_intl_object(this.clone())
+                .map_err(|e| to_js_error(this.clone(), e))?,
+        )?;
 
         add_host_functions(this.clone()).map_err(|e| to_js_error(this.clone(), e))?;
 
@@ -48,6 +85,7 @@ pub fn inject_globals(context: &JSContext) -> anyhow::Result<()> {
         // need a *global* var for polyfills to work
         this.eval::<(), _>("var global = globalThis")?;
         this.eval::<(), _>(from_utf8(PRELUDE).map_err(rquickjs::Error::Utf8)?)?;
+        crate::determinism::install_math_random(&this).map_err(|e| to_js_error(this.clone(), e))?;
 
         Ok::<_, rquickjs::Error>(())
     })?;
@@ -65,11 +103,59 @@ extern "C" {
         arg3: u64,
         arg4: u64,
     ) -> u64;
-    fn __get_function_return_type(func_idx: u32) -> u32;
-    fn __get_function_arg_type(func_idx: u32, arg_idx: u32) -> u32;
+    fn __get_function_count() -> u32;
+    fn __copy_function_signatures(dest: *mut u64);
+}
+
+/// Parameter and result type codes of a host function, as packed by the shim
+struct HostSignature {
+    params: Vec<u32>,
+    results: Vec<u32>,
+}
+
+impl HostSignature {
+    fn unpack(packed: u64) -> HostSignature {
+        let count_mask = (1 << SIGNATURE_COUNT_BITS) - 1;
+        let type_mask = (1 << SIGNATURE_TYPE_BITS) - 1;
+        let param_count = (packed & count_mask) as usize;
+        let result_count = ((packed >> SIGNATURE_COUNT_BITS) & count_mask) as usize;
+        let mut codes = (0..param_count + result_count).map(|i| {
+            let shift = 2 * SIGNATURE_COUNT_BITS + SIGNATURE_TYPE_BITS * i as u32;
+            ((packed >> shift) & type_mask) as u32
+        });
+        HostSignature {
+            params: codes.by_ref().take(param_count).collect(),
+            results: codes.collect(),
+        }
+    }
+}
+
+/// Signatures of every host function, read from the shim on the first host call
+static HOST_SIGNATURES: std::sync::OnceLock<Vec<HostSignature>> = std::sync::OnceLock::new();
+
+fn host_signature(func_id: u32) -> Option<&'static HostSignature> {
+    let signatures = HOST_SIGNATURES.get_or_init(|| {
+        let count = unsafe { __get_function_count() };
+        let mut packed = vec![0u64; count as usize];
+        unsafe { __copy_function_signatures(packed.as_mut_ptr()) };
+        packed
+            .into_iter()
+            .map(|p| HostSignature::unpack(u64::from_le(p)))
+            .collect()
+    });
+    signatures.get(func_id as usize)
+}
+
+static HOST_RESULTS: std::sync::Mutex<Vec<u64>> = std::sync::Mutex::new(vec![]);
+
+/// Called by the shim once per result, in order, for host functions that
+/// return more than one value.
+#[no_mangle]
+pub extern "C" fn __host_result(bits: u64) {
+    HOST_RESULTS.lock().unwrap().push(bits);
 }
 
-fn to_js_error(cx: Ctx, e: anyhow::Error) -> rquickjs::Error {
+pub(crate) fn to_js_error(cx: Ctx, e: anyhow::Error) -> rquickjs::Error {
     match e.downcast::<rquickjs::Error>() {
         Ok(e) => e,
         Err(e) => cx.throw(rquickjs::Value::from_string(
@@ -159,6 +245,89 @@ fn build_host_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
         }),
     )?;
 
+    let host_input_length = Function::new(
+        this.clone(),
+        MutFn::new(move || unsafe { extism::input_length() } as f64),
+    )?;
+    let host_input_read = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let offset = number_arg(&cx, &args, 0, "offset")? as u64;
+            let buffer = args
+                .get(1)
+                .and_then(|b| ArrayBuffer::from_value(b.clone()))
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected an ArrayBuffer")))?;
+            let raw = buffer
+                .as_raw()
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("ArrayBuffer was detached")))?;
+            let start = number_arg(&cx, &args, 2, "byteOffset")? as usize;
+            let len = number_arg(&cx, &args, 3, "byteLength")? as usize;
+            if start.saturating_add(len) > raw.len {
+                return Err(to_js_error(
+                    cx,
+                    anyhow!("Range is outside of the ArrayBuffer"),
+                ));
+            }
+
+            // Copy straight from the plugin input into the JS buffer
+            let dest = unsafe { std::slice::from_raw_parts_mut(raw.ptr.as_ptr().add(start), len) };
+            Ok::<_, rquickjs::Error>(read_input_into(offset, dest) as f64)
+        }),
+    )?;
+    let host_output_open = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let size_hint = number_arg(&cx, &args, 0, "sizeHint").unwrap_or_default() as u64;
+            let mut writer = OUTPUT_WRITER.lock().unwrap();
+            if writer.is_some() {
+                return Err(to_js_error(cx, anyhow!("An output writer is already open")));
+            }
+            *writer = Some(OutputWriter::new(size_hint));
+            Ok(Undefined)
+        }),
+    )?;
+    let host_output_write = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let mut writer = OUTPUT_WRITER.lock().unwrap();
+            let writer = writer
+                .as_mut()
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("The output writer is closed")))?;
+            let data = args
+                .first()
+                .cloned()
+                .unwrap_or_else(|| Undefined.into_value(cx.clone()));
+
+            if let Some(s) = data.as_string() {
+                writer.write(s.to_string()?.as_bytes());
+                return Ok(Undefined);
+            }
+
+            let buffer = ArrayBuffer::from_value(data);
+            let bytes = buffer.as_ref().and_then(|b| b.as_bytes()).ok_or_else(|| {
+                to_js_error(cx.clone(), anyhow!("Expected a string or an ArrayBuffer"))
+            })?;
+            let start = number_arg(&cx, &args, 1, "byteOffset")? as usize;
+            let len = number_arg(&cx, &args, 2, "byteLength")? as usize;
+            let chunk = bytes.get(start..start.saturating_add(len)).ok_or_else(|| {
+                to_js_error(cx.clone(), anyhow!("Range is outside of the ArrayBuffer"))
+            })?;
+            writer.write(chunk);
+            Ok::<_, rquickjs::Error>(Undefined)
+        }),
+    )?;
+    let host_output_close = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>| {
+            let writer =
+                OUTPUT_WRITER.lock().unwrap().take().ok_or_else(|| {
+                    to_js_error(cx.clone(), anyhow!("The output writer is closed"))
+                })?;
+            Ok::<_, rquickjs::Error>(writer.finish() as f64)
+        }),
+    )?;
+    let host_output_discard = Function::new(this.clone(), MutFn::new(discard_output_writer))?;
+
     let to_base64 = Function::new(
         this.clone(),
         MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
@@ -212,11 +381,133 @@ fn build_host_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
     host_object.set("inputString", host_input_string)?;
     host_object.set("outputBytes", host_output_bytes)?;
     host_object.set("outputString", host_output_string)?;
+    host_object.set("_inputLength", host_input_length)?;
+    host_object.set("_inputRead", host_input_read)?;
+    host_object.set("_outputOpen", host_output_open)?;
+    host_object.set("_outputWrite", host_output_write)?;
+    host_object.set("_outputClose", host_output_close)?;
+    host_object.set("_outputDiscard", host_output_discard)?;
     host_object.set("arrayBufferToBase64", to_base64)?;
     host_object.set("base64ToArrayBuffer", from_base64)?;
     Ok(host_object)
 }
 
+pub(crate) fn number_arg(
+    cx: &Ctx,
+    args: &Rest<Value>,
+    idx: usize,
+    name: &str,
+) -> rquickjs::Result<f64> {
+    args.get(idx)
+        .and_then(|v| v.as_number())
+        .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected {name} to be a number")))
+}
+
+pub(crate) fn string_arg(
+    cx: &Ctx,
+    args: &Rest<Value>,
+    idx: usize,
+    name: &str,
+) -> rquickjs::Result<String> {
+    args.get(idx)
+        .and_then(|v| v.as_string())
+        .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected {name} to be a string")))?
+        .to_string()
+}
+
+/// Copies plugin input starting at `offset` into `dest` without loading the
+/// rest of it, returning how many bytes were copied.
+fn read_input_into(offset: u64, dest: &mut [u8]) -> usize {
+    let input_len = unsafe { extism::input_length() };
+    let n = input_len.saturating_sub(offset).min(dest.len() as u64) as usize;
+
+    let mut i = 0;
+    while i + 8 <= n {
+        let word = unsafe { extism::input_load_u64(offset + i as u64) };
+        dest[i..i + 8].copy_from_slice(&word.to_le_bytes());
+        i += 8;
+    }
+    while i < n {
+        dest[i] = unsafe { extism::input_load_u8(offset + i as u64) };
+        i += 1;
+    }
+    n
+}
+
+/// Output built incrementally by `Host.outputWriter()`, written straight into
+/// an Extism memory block that becomes the plugin output once closed.
+struct OutputWriter {
+    block: u64,
+    capacity: u64,
+    len: u64,
+}
+
+static OUTPUT_WRITER: std::sync::Mutex<Option<OutputWriter>> = std::sync::Mutex::new(None);
+
+impl OutputWriter {
+    const MIN_CAPACITY: u64 = 4096;
+    const COPY_CHUNK: u64 = 64 * 1024;
+
+    fn new(size_hint: u64) -> OutputWriter {
+        let capacity = size_hint.max(Self::MIN_CAPACITY);
+        OutputWriter {
+            block: unsafe { extism::alloc(capacity) },
+            capacity,
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        let needed = self.len + data.len() as u64;
+        if needed > self.capacity {
+            self.grow(needed);
+        }
+        unsafe { extism::store(self.block + self.len, data) };
+        self.len = needed;
+    }
+
+    /// Moves the written bytes to a block at least twice as large, in
+    /// bounded chunks so growing never needs a buffer the size of the output.
+    fn grow(&mut self, needed: u64) {
+        let capacity = needed.max(self.capacity * 2);
+        let block = unsafe { extism::alloc(capacity) };
+
+        let mut chunk = vec![0u8; self.len.min(Self::COPY_CHUNK) as usize];
+        let mut copied = 0;
+        while copied < self.len {
+            let n = (self.len - copied).min(Self::COPY_CHUNK) as usize;
+            unsafe {
+                extism::load(self.block + copied, &mut chunk[..n]);
+                extism::store(block + copied, &chunk[..n]);
+            }
+            copied += n as u64;
+        }
+
+        unsafe { extism::free(self.block) };
+        self.block = block;
+        self.capacity = capacity;
+    }
+
+    /// Sets the written bytes as the plugin output and returns their length
+    fn finish(self) -> u64 {
+        unsafe { extism::output_set(self.block, self.len) };
+        self.len
+    }
+
+    /// Drops everything written without touching the plugin output
+    fn discard(self) {
+        unsafe { extism::free(self.block) };
+    }
+}
+
+/// Frees the output writer if one is still open. Output that was never
+/// closed is not the plugin's output.
+pub(crate) fn discard_output_writer() {
+    if let Some(writer) = OUTPUT_WRITER.lock().unwrap().take() {
+        writer.discard();
+    }
+}
+
 fn add_host_functions<'a>(this: Ctx<'a>) -> anyhow::Result<()> {
     let globals = this.globals();
     let host_object = globals.get::<_, Object>("Host")?;
@@ -225,15 +516,31 @@ fn add_host_functions<'a>(this: Ctx<'a>) -> anyhow::Result<()> {
         this.clone(),
         move |cx: Ctx<'a>, args: Rest<Value<'a>>| -> Result<Value<'a>, rquickjs::Error> {
             let func_id = args.first().unwrap().as_int().unwrap() as u32;
-            let mut params = [0u64; 5];
+            let signature = host_signature(func_id).ok_or_else(|| {
+                to_js_error(cx.clone(), anyhow!("Unknown host function {}", func_id))
+            })?;
+            if args.len() - 1 > MAX_HOST_PARAMS {
+                return Err(to_js_error(
+                    cx.clone(),
+                    anyhow!(
+                        "Host function {} was called with {} arguments, but at most {} are supported",
+                        func_id,
+                        args.len() - 1,
+                        MAX_HOST_PARAMS
+                    ),
+                ));
+            }
+            let mut params = [0u64; MAX_HOST_PARAMS];
 
             // Skip the first argument which is the function id
             // and convert the rest of the arguments to their 64-bit representation
             for i in 1..args.len() {
                 let arg = args.get(i).unwrap();
-                params[i - 1] = convert_to_u64_bits(arg, func_id, (i - 1) as u32);
+                let arg_type = signature.params.get(i - 1).copied().unwrap_or(TYPE_VOID);
+                params[i - 1] = convert_to_u64_bits(arg, arg_type, func_id, (i - 1) as u32);
             }
 
+            HOST_RESULTS.lock().unwrap().clear();
             let result = unsafe {
                 __invokeHostFunc(
                     func_id, params[0], params[1], params[2], params[3], params[4],
@@ -241,15 +548,19 @@ fn add_host_functions<'a>(this: Ctx<'a>) -> anyhow::Result<()> {
             };
 
             // Return the result as the appropriate JS value
-            let return_type = unsafe { __get_function_return_type(func_id) };
-            Ok(match return_type {
-                TYPE_VOID => Undefined.into_value(cx.clone()),
-                TYPE_I32 => Value::new_float(cx, (result & 0xFFFFFFFF) as i32 as f64),
-                TYPE_I64 => Value::new_float(cx, result as f64),
-                TYPE_F32 => Value::new_float(cx, f32::from_bits(result as u32) as f64),
-                TYPE_F64 => Value::new_float(cx, f64::from_bits(result)),
-                _ => panic!("Unsupported return type: {:?}", return_type),
-            })
+            if signature.results.len() <= 1 {
+                let return_type = signature.results.first().copied().unwrap_or(TYPE_VOID);
+                return Ok(result_to_js(cx, return_type, result));
+            }
+
+            // Multiple results were handed over through `__host_result`
+            let results = std::mem::take(&mut *HOST_RESULTS.lock().unwrap());
+            let array = rquickjs::Array::new(cx.clone())?;
+            for (i, (bits, return_type)) in results.into_iter().zip(&signature.results).enumerate()
+            {
+                array.set(i, result_to_js(cx.clone(), *return_type, bits))?;
+            }
+            Ok(array.into_value())
         },
     )?;
 
@@ -257,14 +568,25 @@ fn add_host_functions<'a>(this: Ctx<'a>) -> anyhow::Result<()> {
     Ok(())
 }
 
-const TYPE_VOID: u32 = 0;
-const TYPE_I32: u32 = 1;
-const TYPE_I64: u32 = 2;
-const TYPE_F32: u32 = 3;
-const TYPE_F64: u32 = 4;
+pub(crate) const TYPE_VOID: u32 = 0;
+pub(crate) const TYPE_I32: u32 = 1;
+pub(crate) const TYPE_I64: u32 = 2;
+pub(crate) const TYPE_F32: u32 = 3;
+pub(crate) const TYPE_F64: u32 = 4;
+
+fn result_to_js(cx: Ctx, return_type: u32, bits: u64) -> Value {
+    match return_type {
+        TYPE_VOID => Undefined.into_value(cx),
+        TYPE_I32 => Value::new_float(cx, (bits & 0xFFFFFFFF) as i32 as f64),
+        TYPE_I64 => Value::new_float(cx, bits as f64),
+        TYPE_F32 => Value::new_float(cx, f32::from_bits(bits as u32) as f64),
+        TYPE_F64 => Value::new_float(cx, f64::from_bits(bits)),
+        _ => panic!("Unsupported return type: {:?}", return_type),
+    }
+}
 
-fn convert_to_u64_bits(value: &Value, func_id: u32, arg_idx: u32) -> u64 {
-    match unsafe { __get_function_arg_type(func_id, arg_idx) } {
+fn convert_to_u64_bits(value: &Value, arg_type: u32, func_id: u32, arg_idx: u32) -> u64 {
+    match arg_type {
         TYPE_I32 => value.as_number().unwrap_or_default() as i32 as u64,
         TYPE_I64 => value
             .as_big_int()
@@ -463,31 +785,32 @@ fn build_http_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
             }
         }
 
-        let body_args = args.get(1);
-        let http_body = match body_args {
+        let http_body = match args.get(1) {
             None => None,
+            Some(body) if body.is_undefined() || body.is_null() => None,
             Some(body) => {
-                let body = body.as_string();
-                if let Some(body_string) = body {
-                    Some(body_string.to_string()?)
+                if let Some(body_string) = body.as_string() {
+                    Some(body_string.to_string()?.into_bytes())
+                } else if let Some(body_buffer) = ArrayBuffer::from_value(body.clone()) {
+                    let bytes = body_buffer.as_bytes().ok_or_else(|| {
+                        to_js_error(cx.clone(), anyhow!("Request body buffer was detached"))
+                    })?;
+                    Some(bytes.to_vec())
                 } else {
-                    None
+                    return Err(to_js_error(
+                        cx,
+                        anyhow!("Expected body to be a string or an ArrayBuffer"),
+                    ));
                 }
             }
         };
 
         let res = http::request(&http_req, http_body).map_err(|e| to_js_error(cx.clone(), e))?;
-        let body = res.body();
-        let body = from_utf8(&body).map_err(|e| to_js_error(cx.clone(), anyhow::Error::from(e)))?;
 
         let resp_obj = Object::new(cx.clone())?;
 
-        resp_obj.set(
-            "body",
-            rquickjs::String::from_str(cx.clone(), body)?
-                .as_value()
-                .clone(),
-        )?;
+        // The prelude decodes this into `body` only when it's read
+        resp_obj.set("bodyBytes", ArrayBuffer::new(cx.clone(), res.body())?)?;
 
         resp_obj.set(
             "status",
@@ -700,11 +1023,30 @@ fn build_sha_digest(this: Ctx) -> rquickjs::Result<Function> {
     Function::new(this, sha_digest())
 }
 
+/// Hashes a whole buffer at once. The `hashes` feature adds the algorithms
+/// beyond SHA-1 and SHA-2.
+fn digest(algorithm: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    use sha2::Digest;
+
+    let digest = match algorithm {
+        "SHA-1" => sha1::Sha1::digest(data).to_vec(),
+        "SHA-256" => sha2::Sha256::digest(data).to_vec(),
+        "SHA-384" => sha2::Sha384::digest(data).to_vec(),
+        "SHA-512" => sha2::Sha512::digest(data).to_vec(),
+        #[cfg(feature = "hashes")]
+        _ => crate::hash::digest(algorithm, data)?,
+        #[cfg(not(feature = "hashes"))]
+        _ => bail!(
+            "Unsupported algorithm: {} (build the engine with the `hashes` feature)",
+            algorithm
+        ),
+    };
+    Ok(digest)
+}
+
 fn sha_digest<'js>(
 ) -> MutFn<impl Fn(Ctx<'js>, Rest<Value<'js>>) -> rquickjs::Result<Value<'js>>> {
     MutFn::new(|cx: Ctx<'js>, args: Rest<Value<'js>>| {
-        use sha2::Digest;
-
         let algo = args
             .first()
             .and_then(|v| v.as_string())
@@ -720,34 +1062,7 @@ fn sha_digest<'js>(
             .as_bytes()
             .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Could not read ArrayBuffer")))?;
 
-        let result: Vec<u8> = match algo.as_str() {
-            "SHA-1" => {
-                let mut hasher = sha1::Sha1::new();
-                hasher.update(bytes);
-                hasher.finalize().to_vec()
-            }
-            "SHA-256" => {
-                let mut hasher = sha2::Sha256::new();
-                hasher.update(bytes);
-                hasher.finalize().to_vec()
-            }
-            "SHA-384" => {
-                let mut hasher = sha2::Sha384::new();
-                hasher.update(bytes);
-                hasher.finalize().to_vec()
-            }
-            "SHA-512" => {
-                let mut hasher = sha2::Sha512::new();
-                hasher.update(bytes);
-                hasher.finalize().to_vec()
-            }
-            _ => {
-                return Err(to_js_error(
-                    cx,
-                    anyhow!("Unsupported algorithm: {}", algo),
-                ))
-            }
-        };
+        let result = digest(&algo, bytes).map_err(|e| to_js_error(cx.clone(), e))?;
 
         Ok(ArrayBuffer::new(cx, result)?.into_value())
     })
@@ -762,8 +1077,7 @@ fn get_random_bytes<'js>(
             .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected byte count argument")))?
             as usize;
         let mut buf = vec![0u8; n];
-        getrandom::getrandom(&mut buf)
-            .map_err(|e| to_js_error(cx.clone(), anyhow!("getrandom failed: {}", e)))?;
+        crate::determinism::fill_random(&mut buf).map_err(|e| to_js_error(cx.clone(), e))?;
         Ok(ArrayBuffer::new(cx, buf)?.into_value())
     })
 }
@@ -778,10 +1092,7 @@ fn build_clock_ms(this: Ctx) -> rquickjs::Result<Function> {
 
 fn get_time_ms<'js>(
 ) -> MutFn<impl Fn(Ctx<'js>, Rest<Value<'js>>) -> rquickjs::Result<Value<'js>>> {
-    MutFn::new(|cx: Ctx<'js>, _args| {
-        let now = Utc::now();
-        Ok(Value::new_float(cx, now.timestamp_millis() as f64))
-    })
+    MutFn::new(|cx: Ctx<'js>, _args| Ok(Value::new_float(cx, crate::determinism::now_ms())))
 }
 
 fn build_decoder(this: Ctx) -> rquickjs::Result<Function> {
@@ -795,9 +1106,8 @@ fn build_encoder(this: Ctx) -> rquickjs::Result<Function> {
 fn get_time<'js>(
 ) -> MutFn<impl Fn(Ctx<'js>, Rest<Value<'js>>) -> rquickjs::Result<rquickjs::String<'js>>> {
     MutFn::new(|cx: Ctx<'js>, _args| {
-        let now = Utc::now();
         // This format is compatible with JavaScript's Date constructor
-        let formatted = now.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let formatted = crate::determinism::now_rfc3339();
         rquickjs::String::from_str(cx.clone(), &formatted)
     })
 }
@@ -877,4 +1187,135 @@ fn encode_js_string_to_utf8_buffer<'js>(
         let buffer = rust_string.as_bytes();
         Vec::from_bytes(buffer).unwrap().into_js(&cx)
     })
-}
\ No newline at end of file
+}
+
+/// Decoders for the legacy encodings, indexed by the id handed to JS. Freed
+/// slots are reused. UTF-8 goes through `__decodeUtf8BufferToString` instead.
+static TEXT_DECODERS: std::sync::Mutex<Vec<Option<(encoding_rs::Decoder, bool)>>> =
+    std::sync::Mutex::new(Vec::new());
+
+/// A set of legacy decoders, kept aside while another context's are in use.
+#[derive(Default)]
+pub(crate) struct TextDecoders(Vec<Option<(encoding_rs::Decoder, bool)>>);
+
+/// Exchanges the legacy decoders with `decoders`.
+pub(crate) fn swap_text_decoders(decoders: &mut TextDecoders) {
+    std::mem::swap(&mut *TEXT_DECODERS.lock().unwrap(), &mut decoders.0);
+}
+
+/// Builds the `__textDecoding` object `TextDecoder` uses for every WHATWG
+/// encoding other than UTF-8.
+fn build_text_decoding_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
+    let encoding_for_label = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let label = string_arg(&cx, &args, 0, "label")?;
+            // the replacement encoding only exists to stop these labels from
+            // being decoded, so the constructor rejects it
+            let name = encoding_rs::Encoding::for_label(label.as_bytes())
+                .filter(|encoding| *encoding != encoding_rs::REPLACEMENT)
+                .map(|encoding| encoding.name().to_ascii_lowercase());
+            Ok::<_, rquickjs::Error>(name)
+        }),
+    )?;
+
+    let open = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let name = string_arg(&cx, &args, 0, "encoding")?;
+            let ignore_bom = args.get(1).and_then(|v| v.as_bool()).unwrap_or(false);
+            let fatal = args.get(2).and_then(|v| v.as_bool()).unwrap_or(false);
+            let encoding = encoding_rs::Encoding::for_label(name.as_bytes())
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Unknown encoding {}", name)))?;
+            // only a BOM for the decoder's own encoding is removed
+            let decoder = if ignore_bom {
+                encoding.new_decoder_without_bom_handling()
+            } else {
+                encoding.new_decoder_with_bom_removal()
+            };
+
+            let mut decoders = TEXT_DECODERS.lock().unwrap();
+            let id = match decoders.iter().position(Option::is_none) {
+                Some(id) => {
+                    decoders[id] = Some((decoder, fatal));
+                    id
+                }
+                None => {
+                    decoders.push(Some((decoder, fatal)));
+                    decoders.len() - 1
+                }
+            };
+            Ok::<_, rquickjs::Error>(id as f64)
+        }),
+    )?;
+
+    let decode = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let id = number_arg(&cx, &args, 0, "decoder id")? as usize;
+            let buffer = args
+                .get(1)
+                .and_then(|v| ArrayBuffer::from_value(v.clone()))
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected an ArrayBuffer")))?;
+            let offset = number_arg(&cx, &args, 2, "byte offset")? as usize;
+            let length = number_arg(&cx, &args, 3, "byte length")? as usize;
+            // the last call of a stream flushes the decoder and frees it
+            let last = !args.get(4).and_then(|v| v.as_bool()).unwrap_or(false);
+
+            let bytes = buffer
+                .as_bytes()
+                .and_then(|bytes| bytes.get(offset..offset + length))
+                .ok_or_else(|| {
+                    to_js_error(cx.clone(), anyhow!("Invalid offset and length for buffer"))
+                })?;
+
+            let mut decoders = TEXT_DECODERS.lock().unwrap();
+            let slot = decoders
+                .get_mut(id)
+                .filter(|slot| slot.is_some())
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("No open decoder {}", id)))?;
+            let (decoder, fatal) = slot.as_mut().unwrap();
+            let result = decode_legacy(decoder, *fatal, bytes, last);
+            if last || result.is_err() {
+                *slot = None;
+            }
+            result.map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let decoding = Object::new(this)?;
+    decoding.set("encodingForLabel", encoding_for_label)?;
+    decoding.set("open", open)?;
+    decoding.set("decode", decode)?;
+    Ok(decoding)
+}
+
+fn decode_legacy(
+    decoder: &mut encoding_rs::Decoder,
+    fatal: bool,
+    bytes: &[u8],
+    last: bool,
+) -> anyhow::Result<String> {
+    let mut output = String::new();
+    if fatal {
+        let capacity = decoder
+            .max_utf8_buffer_length_without_replacement(bytes.len())
+            .ok_or_else(|| anyhow!("Input is too long to decode"))?;
+        output.reserve(capacity);
+        let (result, _) = decoder.decode_to_string_without_replacement(bytes, &mut output, last);
+        if let encoding_rs::DecoderResult::Malformed(_, _) = result {
+            bail!(
+                "The encoded data was not valid {}",
+                decoder.encoding().name().to_ascii_lowercase()
+            );
+        }
+    } else {
+        let capacity = decoder
+            .max_utf8_buffer_length(bytes.len())
+            .ok_or_else(|| anyhow!("Input is too long to decode"))?;
+        output.reserve(capacity);
+        // with that much room the whole input is always consumed
+        let _ = decoder.decode_to_string(bytes, &mut output, last);
+    }
+    Ok(output)
+}
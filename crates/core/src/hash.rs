@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail};
+use rquickjs::{function::MutFn, prelude::*, ArrayBuffer, Ctx, Function, Object, Value};
+use sha2::digest::DynDigest;
+use xxhash_rust::{xxh3::Xxh3, xxh64::Xxh64};
+
+use crate::globals::{number_arg, to_js_error};
+
+/// An incremental hash. Checksums are returned big-endian, the way they are
+/// usually printed.
+enum Hasher {
+    Digest(Box<dyn DynDigest + Send>),
+    Blake3(Box<blake3::Hasher>),
+    Crc32(crc32fast::Hasher),
+    Xxh64(Xxh64),
+    Xxh3(Box<Xxh3>),
+}
+
+/// Open hashers, by the id handed to JS. Ids aren't reused, so a `Hash` kept
+/// past the call that opened it can't reach a newer hasher.
+static HASHERS: Mutex<BTreeMap<usize, Hasher>> = Mutex::new(BTreeMap::new());
+static NEXT_HASHER_ID: AtomicUsize = AtomicUsize::new(0);
+
+impl Hasher {
+    /// `algorithm` is one of the canonical names the prelude normalizes to.
+    fn new(algorithm: &str) -> anyhow::Result<Hasher> {
+        let hasher = match algorithm {
+            "SHA-1" => Hasher::Digest(Box::new(sha1::Sha1::default())),
+            "SHA-256" => Hasher::Digest(Box::new(sha2::Sha256::default())),
+            "SHA-384" => Hasher::Digest(Box::new(sha2::Sha384::default())),
+            "SHA-512" => Hasher::Digest(Box::new(sha2::Sha512::default())),
+            "SHA3-256" => Hasher::Digest(Box::new(sha3::Sha3_256::default())),
+            "SHA3-384" => Hasher::Digest(Box::new(sha3::Sha3_384::default())),
+            "SHA3-512" => Hasher::Digest(Box::new(sha3::Sha3_512::default())),
+            "BLAKE2b" => Hasher::Digest(Box::new(blake2::Blake2b512::default())),
+            "MD5" => Hasher::Digest(Box::new(md5::Md5::default())),
+            "BLAKE3" => Hasher::Blake3(Box::default()),
+            "CRC32" => Hasher::Crc32(crc32fast::Hasher::new()),
+            "XXH64" => Hasher::Xxh64(Xxh64::new(0)),
+            "XXH3" => Hasher::Xxh3(Box::new(Xxh3::new())),
+            _ => bail!("Unsupported algorithm: {}", algorithm),
+        };
+        Ok(hasher)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Digest(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Crc32(h) => h.update(data),
+            Hasher::Xxh64(h) => h.update(data),
+            Hasher::Xxh3(h) => h.update(data),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            Hasher::Digest(h) => h.finalize().to_vec(),
+            Hasher::Blake3(h) => h.finalize().as_bytes().to_vec(),
+            Hasher::Crc32(h) => h.finalize().to_be_bytes().to_vec(),
+            Hasher::Xxh64(h) => h.digest().to_be_bytes().to_vec(),
+            Hasher::Xxh3(h) => h.digest().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+/// Hashes a whole buffer at once.
+pub(crate) fn digest(algorithm: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut hasher = Hasher::new(algorithm)?;
+    hasher.update(data);
+    Ok(hasher.finish())
+}
+
+fn hasher_id(cx: &Ctx, args: &Rest<Value>) -> rquickjs::Result<usize> {
+    Ok(number_arg(cx, args, 0, "hasher id")? as usize)
+}
+
+fn closed_hasher(id: usize) -> anyhow::Error {
+    anyhow!(
+        "No open hasher {} (a Hash is freed when the call that created it ends)",
+        id
+    )
+}
+
+fn take_hasher(id: usize) -> anyhow::Result<Hasher> {
+    HASHERS
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| closed_hasher(id))
+}
+
+/// Frees the hashers a call left open without digesting them.
+pub(crate) fn discard_hashers() {
+    HASHERS.lock().unwrap().clear();
+}
+
+/// A set of open hashers, kept aside while another context's are in use.
+#[derive(Default)]
+pub(crate) struct Hashers(BTreeMap<usize, Hasher>);
+
+/// Exchanges the open hashers with `hashers`.
+pub(crate) fn swap_hashers(hashers: &mut Hashers) {
+    std::mem::swap(&mut *HASHERS.lock().unwrap(), &mut hashers.0);
+}
+
+/// Builds the `__hash` object the prelude uses for `Host.hash` and
+/// `Host.createHash`.
+pub(crate) fn build_hash_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
+    let open = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let algorithm = args
+                .first()
+                .and_then(|v| v.as_string())
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected algorithm name")))?
+                .to_string()?;
+            let hasher = Hasher::new(&algorithm).map_err(|e| to_js_error(cx.clone(), e))?;
+
+            let id = NEXT_HASHER_ID.fetch_add(1, Ordering::Relaxed);
+            HASHERS.lock().unwrap().insert(id, hasher);
+            Ok::<_, rquickjs::Error>(id as f64)
+        }),
+    )?;
+
+    let update = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let id = hasher_id(&cx, &args)?;
+            let data = args
+                .get(1)
+                .and_then(|v| ArrayBuffer::from_value(v.clone()))
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Expected ArrayBuffer data")))?;
+            let bytes = data
+                .as_bytes()
+                .ok_or_else(|| to_js_error(cx.clone(), anyhow!("Could not read ArrayBuffer")))?;
+
+            HASHERS
+                .lock()
+                .unwrap()
+                .get_mut(&id)
+                .ok_or_else(|| to_js_error(cx.clone(), closed_hasher(id)))?
+                .update(bytes);
+            Ok::<_, rquickjs::Error>(())
+        }),
+    )?;
+
+    let finish = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let id = hasher_id(&cx, &args)?;
+            let output = take_hasher(id).map_err(|e| to_js_error(cx.clone(), e))?;
+            ArrayBuffer::new(cx, output.finish())
+        }),
+    )?;
+
+    let hash = Object::new(this)?;
+    hash.set("open", open)?;
+    hash.set("update", update)?;
+    hash.set("finish", finish)?;
+    Ok(hash)
+}
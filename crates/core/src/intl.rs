@@ -0,0 +1,441 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{anyhow, bail};
+use fixed_decimal::{FixedDecimal, FloatPrecision};
+use icu::calendar::DateTime;
+use icu::collator::{AlternateHandling, CaseLevel, Collator, CollatorOptions, Numeric, Strength};
+use icu::datetime::{options::length, DateTimeFormatter};
+use icu::decimal::options::{FixedDecimalFormatterOptions, GroupingStrategy};
+use icu::decimal::FixedDecimalFormatter;
+use icu::locid::Locale;
+use icu::plurals::{PluralCategory, PluralRules};
+use icu_experimental::dimension::currency::formatter::CurrencyFormatter;
+use icu_experimental::dimension::currency::CurrencyCode;
+use icu_experimental::dimension::percent::formatter::PercentFormatter;
+use icu_provider::DataLocale;
+use icu_provider_adapters::fallback::LocaleFallbackProvider;
+use icu_provider_blob::BlobDataProvider;
+use rquickjs::{function::MutFn, prelude::*, Ctx, Function, Object, Value};
+use writeable::Writeable;
+
+use crate::globals::{number_arg, string_arg, to_js_error};
+
+/// Locale data generated for the locales chosen at build time, or empty when
+/// none was generated. See `build.rs`.
+static INTL_DATA: &[u8] = include_bytes!(env!("EXTISM_JS_INTL_DATA_PATH"));
+
+/// The locales in `INTL_DATA`, comma-separated.
+const INTL_LOCALES: &str = env!("EXTISM_JS_INTL_LOCALES");
+
+static PROVIDER: OnceLock<Option<LocaleFallbackProvider<BlobDataProvider>>> = OnceLock::new();
+
+/// The formatters used most recently, least recent first, keyed by their
+/// kind, locale and options. Creating one loads its locale data, so
+/// `toLocaleString` calls with the same arguments share a formatter instead of
+/// loading it again. JS passes the key with every call, so a formatter that
+/// was dropped to make room is simply made again.
+static FORMATTERS: Mutex<Vec<(String, Formatter)>> = Mutex::new(Vec::new());
+
+/// How many formatters `FORMATTERS` keeps.
+const MAX_FORMATTERS: usize = 16;
+
+enum Formatter {
+    DateTime(DateTimeFormatter),
+    Decimal(FixedDecimalFormatter),
+    Currency(CurrencyFormatter, CurrencyCode),
+    Percent(PercentFormatter<FixedDecimalFormatter>),
+    Collator(Collator),
+    Plural(PluralRules),
+}
+
+fn icu_error(e: impl Display) -> anyhow::Error {
+    anyhow!("{}", e)
+}
+
+/// Locales missing from the data, such as "de-AT" when only "de" was
+/// generated, fall back to their parent locale.
+fn provider() -> anyhow::Result<&'static LocaleFallbackProvider<BlobDataProvider>> {
+    PROVIDER
+        .get_or_init(|| {
+            let blob = BlobDataProvider::try_new_from_static_blob(INTL_DATA).ok()?;
+            LocaleFallbackProvider::try_new_with_buffer_provider(blob).ok()
+        })
+        .as_ref()
+        .ok_or_else(|| anyhow!("The engine was built without Intl locale data"))
+}
+
+fn parse_locale(tag: &str) -> anyhow::Result<Locale> {
+    Locale::from_str(tag).map_err(|_| anyhow!("Invalid language tag: {}", tag))
+}
+
+fn date_style(style: &str) -> anyhow::Result<Option<length::Date>> {
+    let style = match style {
+        "" => None,
+        "full" => Some(length::Date::Full),
+        "long" => Some(length::Date::Long),
+        "medium" => Some(length::Date::Medium),
+        "short" => Some(length::Date::Short),
+        _ => bail!("Invalid dateStyle: {}", style),
+    };
+    Ok(style)
+}
+
+fn time_style(style: &str) -> anyhow::Result<Option<length::Time>> {
+    let style = match style {
+        "" => None,
+        "full" => Some(length::Time::Full),
+        "long" => Some(length::Time::Long),
+        "medium" => Some(length::Time::Medium),
+        "short" => Some(length::Time::Short),
+        _ => bail!("Invalid timeStyle: {}", style),
+    };
+    Ok(style)
+}
+
+fn date_time_formatter(locale: &DataLocale, date: &str, time: &str) -> anyhow::Result<Formatter> {
+    let bag = match (date_style(date)?, time_style(time)?) {
+        (Some(date), Some(time)) => length::Bag::from_date_time_style(date, time),
+        (Some(date), None) => length::Bag::from_date_style(date),
+        (None, Some(time)) => length::Bag::from_time_style(time),
+        (None, None) => bail!("Expected a date or time style"),
+    };
+    let formatter =
+        DateTimeFormatter::try_new_with_buffer_provider(provider()?, locale, bag.into())
+            .map_err(icu_error)?;
+    Ok(Formatter::DateTime(formatter))
+}
+
+fn number_formatter(
+    locale: &DataLocale,
+    style: &str,
+    currency: &str,
+    grouping: bool,
+) -> anyhow::Result<Formatter> {
+    let mut options = FixedDecimalFormatterOptions::default();
+    if !grouping {
+        options.grouping_strategy = GroupingStrategy::Never;
+    }
+    let formatter = match style {
+        "decimal" => Formatter::Decimal(
+            FixedDecimalFormatter::try_new_with_buffer_provider(provider()?, locale, options)
+                .map_err(icu_error)?,
+        ),
+        "currency" => {
+            let code = currency
+                .parse()
+                .map_err(|_| anyhow!("Invalid currency code: {}", currency))?;
+            let formatter = CurrencyFormatter::try_new_with_buffer_provider(
+                provider()?,
+                locale,
+                Default::default(),
+            )
+            .map_err(icu_error)?;
+            Formatter::Currency(formatter, CurrencyCode(code))
+        }
+        "percent" => Formatter::Percent(
+            PercentFormatter::try_new_with_buffer_provider(provider()?, locale, Default::default())
+                .map_err(icu_error)?,
+        ),
+        _ => bail!("Unsupported number style: {}", style),
+    };
+    Ok(formatter)
+}
+
+fn collator(
+    locale: &DataLocale,
+    sensitivity: &str,
+    numeric: bool,
+    ignore_punctuation: bool,
+) -> anyhow::Result<Formatter> {
+    let mut options = CollatorOptions::new();
+    // "case" compares base letters and case, but not accents
+    options.strength = Some(match sensitivity {
+        "base" | "case" => Strength::Primary,
+        "accent" => Strength::Secondary,
+        _ => Strength::Tertiary,
+    });
+    if sensitivity == "case" {
+        options.case_level = Some(CaseLevel::On);
+    }
+    if numeric {
+        options.numeric = Some(Numeric::On);
+    }
+    if ignore_punctuation {
+        options.alternate_handling = Some(AlternateHandling::Shifted);
+    }
+    let collator =
+        Collator::try_new_with_buffer_provider(provider()?, locale, options).map_err(icu_error)?;
+    Ok(Formatter::Collator(collator))
+}
+
+fn plural_rules(locale: &DataLocale, kind: &str) -> anyhow::Result<Formatter> {
+    let rules = match kind {
+        "ordinal" => PluralRules::try_new_ordinal_with_buffer_provider(provider()?, locale),
+        _ => PluralRules::try_new_cardinal_with_buffer_provider(provider()?, locale),
+    };
+    Ok(Formatter::Plural(rules.map_err(icu_error)?))
+}
+
+/// Makes the formatter a key describes. Keys are built by `open`'s callers,
+/// and the locale has been checked, so it can't contain the separator.
+fn create_formatter(key: &str) -> anyhow::Result<Formatter> {
+    let locale = |tag: &str| parse_locale(tag).map(|locale| DataLocale::from(&locale));
+    match key.split('|').collect::<Vec<_>>().as_slice() {
+        ["datetime", tag, date, time] => date_time_formatter(&locale(tag)?, date, time),
+        ["number", tag, style, currency, grouping] => {
+            number_formatter(&locale(tag)?, style, currency, *grouping == "true")
+        }
+        ["collator", tag, sensitivity, numeric, punctuation] => collator(
+            &locale(tag)?,
+            sensitivity,
+            *numeric == "true",
+            *punctuation == "true",
+        ),
+        ["plural", tag, kind] => plural_rules(&locale(tag)?, kind),
+        _ => bail!("Invalid formatter key: {}", key),
+    }
+}
+
+/// Runs `f` with the formatter for `key`, making it if it isn't kept.
+fn with_formatter<T>(
+    key: &str,
+    f: impl FnOnce(&Formatter) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let mut formatters = FORMATTERS.lock().unwrap();
+    let entry = match formatters.iter().position(|(k, _)| k == key) {
+        Some(i) => formatters.remove(i),
+        None => (key.to_string(), create_formatter(key)?),
+    };
+    if formatters.len() >= MAX_FORMATTERS {
+        formatters.remove(0);
+    }
+    formatters.push(entry);
+    f(&formatters.last().unwrap().1)
+}
+
+/// Checks that the formatter for `key` can be made, so bad options fail when
+/// the `Intl` object is constructed, and returns the key JS uses it by.
+fn open(locale: &str, key: String) -> anyhow::Result<String> {
+    parse_locale(locale)?;
+    with_formatter(&key, |_| Ok(()))?;
+    Ok(key)
+}
+
+fn key_arg(cx: &Ctx, args: &Rest<Value>) -> rquickjs::Result<String> {
+    string_arg(cx, args, 0, "formatter key")
+}
+
+/// Numbers arrive as JS numbers, BigInts as decimal strings.
+fn decimal_arg(cx: &Ctx, args: &Rest<Value>, idx: usize) -> rquickjs::Result<FixedDecimal> {
+    let value = args.get(idx);
+    let decimal = match value.and_then(|v| v.as_number()) {
+        Some(n) => FixedDecimal::try_from_f64(n, FloatPrecision::Floating).map_err(icu_error),
+        None => match value.and_then(|v| v.as_string()) {
+            Some(s) => FixedDecimal::from_str(&s.to_string()?).map_err(icu_error),
+            None => Err(anyhow!("Expected a number")),
+        },
+    };
+    decimal.map_err(|e| to_js_error(cx.clone(), e))
+}
+
+/// Rounds half away from zero, as `Intl.NumberFormat` does by default.
+fn round(value: &mut FixedDecimal, min_integer: i16, min_fraction: i16, max_fraction: i16) {
+    value.half_expand(-max_fraction);
+    value.pad_end(-min_fraction);
+    value.pad_start(min_integer);
+}
+
+fn format_number(
+    formatter: &Formatter,
+    mut value: FixedDecimal,
+    digits: [i16; 3],
+) -> anyhow::Result<String> {
+    let [min_integer, min_fraction, max_fraction] = digits;
+    let formatted = match formatter {
+        Formatter::Decimal(f) => {
+            round(&mut value, min_integer, min_fraction, max_fraction);
+            f.format_to_string(&value)
+        }
+        Formatter::Currency(f, code) => {
+            round(&mut value, min_integer, min_fraction, max_fraction);
+            f.format_fixed_decimal(&value, *code)
+                .write_to_string()
+                .into_owned()
+        }
+        Formatter::Percent(f) => {
+            value.multiply_pow10(2);
+            round(&mut value, min_integer, min_fraction, max_fraction);
+            f.format(&value).write_to_string().into_owned()
+        }
+        _ => bail!("Not a number formatter"),
+    };
+    Ok(formatted)
+}
+
+fn plural_category(category: PluralCategory) -> &'static str {
+    match category {
+        PluralCategory::Zero => "zero",
+        PluralCategory::One => "one",
+        PluralCategory::Two => "two",
+        PluralCategory::Few => "few",
+        PluralCategory::Many => "many",
+        PluralCategory::Other => "other",
+    }
+}
+
+/// Builds the `__intl` object behind the `Intl` namespace and the
+/// `toLocaleString` family of methods.
+pub(crate) fn build_intl_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
+    let canonicalize = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let tag = string_arg(&cx, &args, 0, "language tag")?;
+            let locale = parse_locale(&tag).map_err(|e| to_js_error(cx.clone(), e))?;
+            Ok::<_, rquickjs::Error>(locale.to_string())
+        }),
+    )?;
+
+    let date_time_format = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let locale = string_arg(&cx, &args, 0, "locale")?;
+            let date = string_arg(&cx, &args, 1, "dateStyle")?;
+            let time = string_arg(&cx, &args, 2, "timeStyle")?;
+            let key = format!("datetime|{locale}|{date}|{time}");
+            open(&locale, key).map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let number_format = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let locale = string_arg(&cx, &args, 0, "locale")?;
+            let style = string_arg(&cx, &args, 1, "style")?;
+            let currency = string_arg(&cx, &args, 2, "currency")?;
+            let grouping = args.get(3).and_then(|v| v.as_bool()).unwrap_or(true);
+            let key = format!("number|{locale}|{style}|{currency}|{grouping}");
+            open(&locale, key).map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let collator_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let locale = string_arg(&cx, &args, 0, "locale")?;
+            let sensitivity = string_arg(&cx, &args, 1, "sensitivity")?;
+            let numeric = args.get(2).and_then(|v| v.as_bool()).unwrap_or(false);
+            let punctuation = args.get(3).and_then(|v| v.as_bool()).unwrap_or(false);
+            let key = format!("collator|{locale}|{sensitivity}|{numeric}|{punctuation}");
+            open(&locale, key).map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let plural_rules_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let locale = string_arg(&cx, &args, 0, "locale")?;
+            let kind = string_arg(&cx, &args, 1, "type")?;
+            let key = format!("plural|{locale}|{kind}");
+            open(&locale, key).map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    // the prelude passes the date's UTC fields
+    let format_date = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let key = key_arg(&cx, &args)?;
+            let mut fields = [0; 6];
+            for (i, field) in fields.iter_mut().enumerate() {
+                *field = number_arg(&cx, &args, i + 1, "date field")? as i32;
+            }
+            let [year, month, day, hour, minute, second] = fields;
+            with_formatter(&key, |formatter| {
+                let Formatter::DateTime(formatter) = formatter else {
+                    bail!("Not a date formatter");
+                };
+                let datetime = DateTime::try_new_iso_datetime(
+                    year,
+                    month as u8,
+                    day as u8,
+                    hour as u8,
+                    minute as u8,
+                    second as u8,
+                )
+                .map_err(icu_error)?;
+                formatter
+                    .format_to_string(&datetime.to_any())
+                    .map_err(icu_error)
+            })
+            .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let format_number_fn = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let key = key_arg(&cx, &args)?;
+            let value = decimal_arg(&cx, &args, 1)?;
+            let mut digits = [0; 3];
+            for (i, digit) in digits.iter_mut().enumerate() {
+                *digit = number_arg(&cx, &args, i + 2, "digits")? as i16;
+            }
+            with_formatter(&key, |formatter| format_number(formatter, value, digits))
+                .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let compare = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let key = key_arg(&cx, &args)?;
+            let a = string_arg(&cx, &args, 1, "string")?;
+            let b = string_arg(&cx, &args, 2, "string")?;
+            let ordering = with_formatter(&key, |formatter| match formatter {
+                Formatter::Collator(collator) => Ok(collator.compare(&a, &b)),
+                _ => bail!("Not a collator"),
+            })
+            .map_err(|e| to_js_error(cx.clone(), e))?;
+            Ok::<_, rquickjs::Error>(match ordering {
+                Ordering::Less => -1,
+                Ordering::Equal => 0,
+                Ordering::Greater => 1,
+            })
+        }),
+    )?;
+
+    let select_plural = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let key = key_arg(&cx, &args)?;
+            let mut value = decimal_arg(&cx, &args, 1)?;
+            let min_fraction = number_arg(&cx, &args, 2, "digits")? as i16;
+            let max_fraction = number_arg(&cx, &args, 3, "digits")? as i16;
+            // "1.0" is plural in English, so the digits shown decide the category
+            round(&mut value, 1, min_fraction, max_fraction);
+            with_formatter(&key, |formatter| match formatter {
+                Formatter::Plural(rules) => Ok(plural_category(rules.category_for(&value))),
+                _ => bail!("Not plural rules"),
+            })
+            .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let locales: Vec<&str> = INTL_LOCALES.split(',').filter(|l| !l.is_empty()).collect();
+
+    let intl = Object::new(this)?;
+    intl.set("locales", locales)?;
+    intl.set("canonicalize", canonicalize)?;
+    intl.set("dateTimeFormat", date_time_format)?;
+    intl.set("numberFormat", number_format)?;
+    intl.set("collator", collator_fn)?;
+    intl.set("pluralRules", plural_rules_fn)?;
+    intl.set("formatDate", format_date)?;
+    intl.set("formatNumber", format_number_fn)?;
+    intl.set("compare", compare)?;
+    intl.set("selectPlural", select_plural)?;
+    Ok(intl)
+}
@@ -0,0 +1,47 @@
+//! Isolated calls each run in a runtime of their own, made for the call and
+//! dropped after it. The prelude and the user's script are evaluated again
+//! every time, which costs about as much as the engine's initialization, but
+//! nothing one call leaves behind can reach another.
+
+use rquickjs::{Context, Runtime};
+
+use crate::{determinism, globals};
+
+/// Native state for objects in the JS heap: each context needs its own,
+/// because JS holds ids into it.
+#[derive(Default)]
+struct NativeState {
+    text_decoders: globals::TextDecoders,
+    #[cfg(feature = "compression")]
+    codecs: crate::compression::Codecs,
+    #[cfg(feature = "hashes")]
+    hashers: crate::hash::Hashers,
+}
+
+impl NativeState {
+    /// Exchanges this state with the one in use.
+    fn swap(&mut self) {
+        globals::swap_text_decoders(&mut self.text_decoders);
+        #[cfg(feature = "compression")]
+        crate::compression::swap_codecs(&mut self.codecs);
+        #[cfg(feature = "hashes")]
+        crate::hash::swap_hashers(&mut self.hashers);
+    }
+}
+
+/// Runs `f` against a context `load` makes on a new runtime. The call starts
+/// with empty native state, which is freed along with the runtime, and with
+/// the clock and seeded generator where `configure` left them.
+pub(crate) fn call<R, E>(
+    load: impl FnOnce(&Runtime) -> Result<Context, E>,
+    f: impl FnOnce(&Context) -> R,
+) -> Result<R, E> {
+    let mut native = NativeState::default();
+    native.swap();
+    let result = determinism::isolated(|| {
+        let runtime = Runtime::new().expect("Couldn't make a runtime");
+        load(&runtime).map(|context| f(&context))
+    });
+    native.swap();
+    result
+}
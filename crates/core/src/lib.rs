@@ -1,11 +1,28 @@
+use diagnostic::ScriptError;
 use rquickjs::{
-    function::Args, object::ObjectKeysIter, Context, Ctx, Function, Object, Runtime, Undefined,
-    Value,
+    context::EvalOptions,
+    function::{IntoArgs, Rest},
+    promise::PromiseState,
+    Coerced, Context, Ctx, Function, IntoJs, Object, Persistent, Promise, Runtime, Type, Value,
 };
 use std::io;
 use std::io::Read;
+use std::sync::Arc;
 
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "webcrypto")]
+mod crypto;
+mod determinism;
+mod diagnostic;
 mod globals;
+#[cfg(feature = "hashes")]
+mod hash;
+#[cfg(feature = "intl")]
+mod intl;
+mod isolate;
+mod signature;
+mod tz;
 
 struct Cx(Context);
 
@@ -14,49 +31,99 @@ unsafe impl Sync for Cx {}
 
 static CONTEXT: std::sync::OnceLock<Cx> = std::sync::OnceLock::new();
 static CALL_ARGS: std::sync::Mutex<Vec<Vec<ArgType>>> = std::sync::Mutex::new(vec![]);
+static RESULT_TYPES: std::sync::Mutex<Vec<u32>> = std::sync::Mutex::new(vec![]);
+static CALL_RESULTS: std::sync::Mutex<Vec<ArgType>> = std::sync::Mutex::new(vec![]);
+/// The exports declared in the d.ts, indexed like the shim's exports (sorted
+/// by name).
+static EXPORTS: std::sync::Mutex<Vec<Export>> = std::sync::Mutex::new(Vec::new());
+static RESUMED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+static STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+/// Set once the deprecation warning for `I32` exports returning nothing has
+/// been logged, so it's logged once per instance.
+static WARNED_UNDEFINED_I32: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+/// The user's script, kept to load it again for each isolated call.
+static SOURCE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// An export the shim calls, with the function last found for it in
+/// `module.exports`.
+struct Export {
+    name: Arc<str>,
+    function: Option<Persistent<Function<'static>>>,
+}
+
+unsafe impl Send for Export {}
 
 fn caught_to_string(caught: Value) -> String {
-    match caught.as_exception() {
-        Some(err) => {
-            let msg = err.message().unwrap_or_default();
-            format!("Exception: {}\n{}", msg, err.stack().unwrap_or_default())
-        }
-        None => {
-            // The caught value is not a JS Error object. It could be a string,
-            // number, null, or an uninitialized value from an async context.
-            format!("Exception: {:?}", caught)
-        }
-    }
+    ScriptError::from_caught(caught).to_string()
 }
 
 fn err_into_string(this: &Ctx, err: rquickjs::Error) -> String {
-    match err {
-        rquickjs::Error::Exception => caught_to_string(this.catch()),
-        err => err.to_string(),
+    ScriptError::from_error(this, err).to_string()
+}
+
+fn set_error(s: &str) {
+    let mem = extism_pdk::Memory::from_bytes(s).unwrap();
+    unsafe {
+        extism_pdk::extism::error_set(mem.offset());
     }
 }
 
 #[export_name = "wizer.initialize"]
 extern "C" fn init() {
-    let runtime = Runtime::new().expect("Couldn't make a runtime");
-    let context = Context::full(&runtime).expect("Couldnt make a context");
-    globals::inject_globals(&context).expect("Failed to initialize globals");
-
     let mut code = String::new();
     io::stdin().read_to_string(&mut code).unwrap();
+    let _ = SOURCE.set(code.clone());
 
-    context
-        .with(|this| -> Result<rquickjs::Undefined, anyhow::Error> {
-            if let Err(err) = this.eval::<(), _>(code) {
-                panic!("{}", err_into_string(&this, err).to_string());
-            }
+    // Wizer reports a trap, not the exception, so hand the error to the CLI
+    // and exit
+    let log = |message: &str| eprintln!("{}", message);
+    let exit = |err: ScriptError| -> ! {
+        err.report();
+        std::process::exit(1);
+    };
 
-            Ok(Undefined)
-        })
-        .unwrap();
+    let runtime = Runtime::new().expect("Couldn't make a runtime");
+    let context = load_script(&runtime, &code, log).unwrap_or_else(|err| exit(err));
+    context.with(|this| {
+        // The CLI lists the exports in the shim's order
+        let names: Vec<String> = this
+            .globals()
+            .get::<_, Object>("Host")
+            .and_then(|host| host.get("__exportNames"))
+            .unwrap_or_default();
+        *EXPORTS.lock().unwrap() = names
+            .into_iter()
+            .map(|name| Export {
+                name: name.into(),
+                function: None,
+            })
+            .collect();
+    });
     let _ = CONTEXT.set(Cx(context));
 }
 
+/// Makes a context on `runtime` with the prelude and the user's script loaded.
+fn load_script(
+    runtime: &Runtime,
+    source: &str,
+    log: impl Fn(&str),
+) -> Result<Context, ScriptError> {
+    // Rejections are collected by the prelude and reported after each call
+    runtime.set_host_promise_rejection_tracker(Some(Box::new(|ctx, promise, reason, handled| {
+        if let Ok(track) = ctx.globals().get::<_, Function>("__trackRejection") {
+            if track.call::<_, ()>((promise, reason, handled)).is_err() {
+                ctx.catch();
+            }
+        }
+    })));
+    let context = Context::full(runtime).expect("Couldnt make a context");
+    globals::inject_globals(&context)
+        .map_err(|err| ScriptError::Other(format!("Failed to initialize globals: {}", err)))?;
+    context.with(|this| evaluate_script(&this, source, log))?;
+    Ok(context)
+}
+
 fn js_context() -> Context {
     if CONTEXT.get().is_none() {
         init()
@@ -65,79 +132,357 @@ fn js_context() -> Context {
     context.0.clone()
 }
 
-fn invoke<'a, T, F: for<'b> Fn(Ctx<'b>, Value<'b>) -> T>(
-    idx: i32,
-    conv: F,
-) -> Result<T, anyhow::Error> {
-    let call_args = CALL_ARGS.lock().unwrap().pop();
-    let context = js_context();
-    context.with(|ctx| {
-        let call_args = call_args.unwrap();
-        let args: Args = call_args.iter().fold(
-            Args::new(ctx.clone(), call_args.len()),
-            |mut args, rust_arg| {
-                match rust_arg {
-                    ArgType::I32(v) => args
-                        .push_arg(v)
-                        .expect("Should be able to convert i32 to JS arg"),
-                    ArgType::I64(v) => args
-                        .push_arg(rquickjs::BigInt::from_i64(ctx.clone(), *v))
-                        .expect("Should be able to convert i64 to JS arg"),
-                    ArgType::F32(v) => args
-                        .push_arg(v)
-                        .expect("Should be able to convert f32 to JS arg"),
-                    ArgType::F64(v) => args
-                        .push_arg(v)
-                        .expect("Should be able to convert f64 to JS arg"),
-                }
-                args
-            },
-        );
-        let global = ctx.globals();
+/// Picks up where the Wizer snapshot left off, before the first call. The
+/// snapshot froze whatever the engine computed at build time, and config
+/// can't be read until now.
+fn resume() -> anyhow::Result<()> {
+    if RESUMED.load(std::sync::atomic::Ordering::Relaxed) {
+        return Ok(());
+    }
+    determinism::resume()?;
+    determinism::configure()?;
+    RESUMED.store(true, std::sync::atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+/// Readies a context for its first call: once for the snapshot's context,
+/// before any export is found, and before every isolated call for the context
+/// it runs in.
+fn start(ctx: &Ctx) -> anyhow::Result<()> {
+    // The prelude's own snapshot-sensitive state
+    let hooks: Function = ctx.globals().get("__resume")?;
+    hooks
+        .call::<_, ()>(())
+        .map_err(|err| anyhow::Error::msg(err_into_string(ctx, err)))?;
+
+    if let Some(on_init) = export_function(ctx, "onInit")? {
+        call_settled(ctx, "onInit", &on_init, ()).map_err(anyhow::Error::msg)?;
+    }
+    Ok(())
+}
+
+/// Evaluates the user's script, which may `await` at the top level, and runs
+/// the job queue dry so promise-based setup finishes before the snapshot.
+fn evaluate_script(ctx: &Ctx, source: &str, log: impl Fn(&str)) -> Result<(), ScriptError> {
+    let mut options = EvalOptions::default();
+    options.promise = true;
+    options.filename = Some(diagnostic::SCRIPT_NAME.to_string());
+    let promise: Promise = ctx
+        .eval_with_options(source, options)
+        .map_err(|err| ScriptError::from_error(ctx, err))?;
+
+    run_jobs(ctx, Some(&promise)).map_err(|err| ScriptError::from_error(ctx, err))?;
+
+    match promise.result::<Value>() {
+        Some(Ok(_)) => {}
+        Some(Err(err)) => return Err(ScriptError::from_error(ctx, err)),
+        None => {
+            return Err(ScriptError::Other(
+                "The script's top-level await never settled".to_string(),
+            ))
+        }
+    }
+    report_rejections(ctx, Some(promise.into_value()), log).map_err(ScriptError::Other)
+}
 
-        let module: Object = global.get("module")?;
-        let exports: Object = module.get("exports")?;
+/// Whether `function` runs against a fresh copy of the initial state: every
+/// export does when built with `--isolate`, others opt in with `isolated = true`.
+fn is_isolated(ctx: &Ctx, function: &Function) -> rquickjs::Result<bool> {
+    let all: Value = ctx.globals().get("__isolateCalls")?;
+    let this: Value = function.get("isolated")?;
+    Ok(all.as_bool() == Some(true) || this.as_bool() == Some(true))
+}
 
-        let export_names = export_names(exports.clone()).unwrap();
+/// The function exported as `name`, if there is one.
+fn export_function<'js>(ctx: &Ctx<'js>, name: &str) -> rquickjs::Result<Option<Function<'js>>> {
+    let module: Object = ctx.globals().get("module")?;
+    let exports: Object = module.get("exports")?;
+    Ok(exports.get::<_, Value>(name)?.into_function())
+}
 
-        let function: Function = exports.get(export_names[idx as usize].as_str()).unwrap();
+/// Calls `function`, drains the job queue and unwraps the Promise it returns,
+/// if any. Errors are formatted for the host.
+fn call_settled<'js, A: IntoArgs<'js>>(
+    ctx: &Ctx<'js>,
+    name: &str,
+    function: &Function<'js>,
+    args: A,
+) -> Result<Value<'js>, String> {
+    let result = function.call::<_, Value>(args);
 
-        let function_invocation_result = function.call_arg(args);
+    // If the function call failed, catch the exception now before
+    // execute_pending_job() can consume or overwrite it.
+    let call_err = if result.is_err() {
+        Some(ctx.catch())
+    } else {
+        None
+    };
+
+    if let Err(err) = run_jobs(ctx, result.as_ref().ok().and_then(|r| r.as_promise())) {
+        return Err(err_into_string(ctx, err));
+    }
 
-        // If the function call failed, catch the exception now before
-        // execute_pending_job() can consume or overwrite it.
-        let call_err = if function_invocation_result.is_err() {
-            Some(ctx.catch())
-        } else {
-            None
-        };
+    let rejections = report_rejections(ctx, result.as_ref().ok().cloned(), |message| {
+        extism_pdk::error!("{}", message)
+    });
 
+    // Async functions hand back a Promise; with the job queue drained it has
+    // either settled or will never settle.
+    result
+        .and_then(|r| settle_promise(ctx, name, r))
+        .map_err(|err| match call_err {
+            Some(caught) if !caught.is_null() && !caught.is_undefined() => caught_to_string(caught),
+            _ => err_into_string(ctx, err),
+        })
+        .and_then(|value| rejections.map(|_| value))
+}
+
+/// Drains the job queue. While `waiting` is still pending with nothing left
+/// to run, the engine sleeps until the next `AbortSignal.timeout()` with an
+/// `abort` listener is due and fires it, standing in for the event loop
+/// QuickJS doesn't have.
+fn run_jobs(ctx: &Ctx, waiting: Option<&Promise>) -> rquickjs::Result<()> {
+    let fire: Function = ctx.globals().get("__fireAbortTimeouts")?;
+    loop {
         while ctx.execute_pending_job() {
             continue;
         }
+        let next: Option<f64> = fire.call(())?;
+        // a signal that fired may have queued reactions
+        if ctx.execute_pending_job() {
+            continue;
+        }
+        let pending = waiting.is_some_and(|p| p.state() == PromiseState::Pending);
+        match next {
+            Some(wait_ms) if pending && wait_ms.is_finite() => {
+                std::thread::sleep(std::time::Duration::from_secs_f64(
+                    wait_ms.max(0.0) / 1000.0,
+                ));
+                fire.call::<_, Option<f64>>((true,))?;
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Forgets the call's `AbortSignal.timeout()` signals, so one still pending
+/// can't make a later call wait for it.
+fn clear_abort_timeouts(ctx: &Ctx) {
+    if let Ok(clear) = ctx.globals().get::<_, Function>("__clearAbortTimeouts") {
+        if clear.call::<_, ()>(()).is_err() {
+            ctx.catch();
+        }
+    }
+}
 
-        match function_invocation_result {
-            Ok(r) => {
-                let res = conv(ctx.clone(), r);
-                Ok(res)
+/// Logs the promises that were rejected without a handler during a call,
+/// apart from `returned`, which the caller settles. They fail the call when
+/// the plug-in was built with `--fail-on-unhandled-rejection`.
+fn report_rejections<'js>(
+    ctx: &Ctx<'js>,
+    returned: Option<Value<'js>>,
+    log: impl Fn(&str),
+) -> Result<(), String> {
+    let report: Function = ctx
+        .globals()
+        .get("__reportRejections")
+        .map_err(|err| err_into_string(ctx, err))?;
+    let reasons: Vec<Value> = report
+        .call((returned,))
+        .map_err(|err| err_into_string(ctx, err))?;
+
+    let mut first = None;
+    for reason in reasons {
+        let message = format!("Unhandled promise rejection: {}", caught_to_string(reason));
+        log(&message);
+        first.get_or_insert(message);
+    }
+
+    let strict: Value = ctx
+        .globals()
+        .get("__failOnUnhandledRejection")
+        .map_err(|err| err_into_string(ctx, err))?;
+    match first {
+        Some(message) if strict.as_bool() == Some(true) => Err(message),
+        _ => Ok(()),
+    }
+}
+
+fn invoke<'a, T, F: for<'b> Fn(Value<'b>) -> Result<T, String>>(
+    idx: i32,
+    conv: F,
+) -> Result<T, anyhow::Error> {
+    // An export that throws part way through leaves its writer open
+    globals::discard_output_writer();
+    let result = invoke_export(idx, conv);
+    globals::discard_output_writer();
+    #[cfg(feature = "compression")]
+    compression::discard_codecs();
+    #[cfg(feature = "hashes")]
+    hash::discard_hashers();
+    result
+}
+
+fn invoke_export<'a, T, F: for<'b> Fn(Value<'b>) -> Result<T, String>>(
+    idx: i32,
+    conv: F,
+) -> Result<T, anyhow::Error> {
+    let call_args = CALL_ARGS.lock().unwrap().pop();
+    let context = js_context();
+
+    let prepared = resume().and_then(|_| {
+        context.with(|ctx| {
+            // `onInit` may define the exports, so it runs before the export is
+            // found, even for an isolated call. A failed start runs again on
+            // the next call
+            if !STARTED.load(std::sync::atomic::Ordering::Relaxed) {
+                start(&ctx)?;
+                STARTED.store(true, std::sync::atomic::Ordering::Relaxed);
             }
-            Err(err) => {
-                // Use the exception we caught earlier (before execute_pending_job
-                // could overwrite it).
-                let s = match call_err {
-                    Some(caught) if !caught.is_null() && !caught.is_undefined() => {
-                        caught_to_string(caught)
+            let (export_name, function) = resolve_export(&ctx, idx as usize)?;
+            let isolated = is_isolated(&ctx, &function)?;
+            Ok::<_, anyhow::Error>((export_name, isolated))
+        })
+    });
+    let (export_name, isolated) = match prepared {
+        Ok(prepared) => prepared,
+        Err(err) => {
+            set_error(&err.to_string());
+            return Err(err);
+        }
+    };
+    let export_name = &*export_name;
+
+    let call = |context: &Context| {
+        context.with(|ctx| {
+            let call_args = call_args.unwrap();
+            let args: Vec<Value> = call_args
+                .iter()
+                .map(|rust_arg| match rust_arg {
+                    ArgType::I32(v) => v.into_js(&ctx),
+                    ArgType::I64(v) => {
+                        rquickjs::BigInt::from_i64(ctx.clone(), *v).map(|v| v.into_value())
                     }
-                    _ => err_into_string(&ctx, err),
-                };
-                let mem = extism_pdk::Memory::from_bytes(&s).unwrap();
-                unsafe {
-                    extism_pdk::extism::error_set(mem.offset());
+                    ArgType::F32(v) => v.into_js(&ctx),
+                    ArgType::F64(v) => v.into_js(&ctx),
+                })
+                .collect::<rquickjs::Result<_>>()
+                .expect("Should be able to convert args to JS");
+
+            if isolated {
+                if let Err(err) = start(&ctx) {
+                    set_error(&err.to_string());
+                    return Err(err);
+                }
+            }
+
+            // An isolated call's context loaded its own exports
+            let function = if isolated {
+                export_function(&ctx, export_name)
+                    .map_err(|err| anyhow::Error::msg(err_into_string(&ctx, err)))
+                    .and_then(|function| {
+                        function.ok_or_else(|| {
+                            anyhow::anyhow!("Export `{}` is not a function", export_name)
+                        })
+                    })
+            } else {
+                resolve_export(&ctx, idx as usize).map(|(_, function)| function)
+            };
+            let function = match function {
+                Ok(function) => function,
+                Err(err) => {
+                    set_error(&err.to_string());
+                    return Err(err);
+                }
+            };
+
+            // onCall decides whether, and when, the export itself runs
+            let function_invocation_result = match export_function(&ctx, "onCall") {
+                Ok(Some(on_call)) => Function::new(ctx.clone(), move || {
+                    function.call::<_, Value>((Rest(args.clone()),))
+                })
+                .map_err(|err| err_into_string(&ctx, err))
+                .and_then(|next| call_settled(&ctx, export_name, &on_call, (export_name, next))),
+                Ok(None) => call_settled(&ctx, export_name, &function, (Rest(args),)),
+                Err(err) => Err(err_into_string(&ctx, err)),
+            };
+            clear_abort_timeouts(&ctx);
+
+            match function_invocation_result {
+                Ok(r) => conv(r).map_err(|e| {
+                    let s = format!("Invalid return value from export `{}`: {}", export_name, e);
+                    set_error(&s);
+                    anyhow::Error::msg(s)
+                }),
+                Err(s) => {
+                    set_error(&s);
+                    Err(anyhow::Error::msg(s))
                 }
-                Err(anyhow::Error::msg(s))
             }
+        })
+    };
+
+    if !isolated {
+        return call(&context);
+    }
+    let source = SOURCE.get().map(String::as_str).unwrap_or_default();
+    let load = |runtime: &Runtime| {
+        load_script(runtime, source, |message| extism_pdk::error!("{}", message))
+    };
+    match isolate::call(load, call) {
+        Ok(result) => result,
+        Err(err) => {
+            let err = anyhow::Error::msg(err.to_string());
+            set_error(&err.to_string());
+            Err(err)
         }
-    })
+    }
+}
+
+/// Finds the export the shim calls by index. The function found is kept and
+/// used until `module.exports` holds a different one under its name, so
+/// reassigning an export takes effect on the next call.
+fn resolve_export<'js>(ctx: &Ctx<'js>, idx: usize) -> anyhow::Result<(Arc<str>, Function<'js>)> {
+    let mut exports = EXPORTS.lock().unwrap();
+    let Some(export) = exports.get_mut(idx) else {
+        anyhow::bail!("No export found at index {}", idx);
+    };
+    let name = export.name.clone();
+
+    let module: Object = ctx.globals().get("module")?;
+    let value: Value = module.get::<_, Object>("exports")?.get(&*name)?;
+    if let Some(cached) = export.function.clone() {
+        let function = cached.restore(ctx)?;
+        if *function.as_value() == value {
+            return Ok((name, function));
+        }
+    }
+
+    let function = value
+        .into_function()
+        .ok_or_else(|| anyhow::anyhow!("Export `{}` is not a function", name))?;
+    export.function = Some(Persistent::save(ctx, function.clone()));
+    Ok((name, function))
+}
+
+fn settle_promise<'js>(
+    ctx: &Ctx<'js>,
+    export_name: &str,
+    value: Value<'js>,
+) -> rquickjs::Result<Value<'js>> {
+    let Some(promise) = value.as_promise() else {
+        return Ok(value);
+    };
+    match promise.result::<Value>() {
+        Some(result) => result,
+        None => Err(rquickjs::Exception::throw_message(
+            ctx,
+            &format!(
+                "Export `{}` returned a Promise that never settled",
+                export_name
+            ),
+        )),
+    }
 }
 
 #[no_mangle]
@@ -187,47 +532,190 @@ pub extern "C" fn __arg_f64(arg: f64) {
 
 #[no_mangle]
 pub extern "C" fn __invoke_i32(idx: i32) -> i32 {
-    invoke(idx, |_ctx, r| r.as_number().unwrap_or_default() as i32).unwrap_or(-1)
+    invoke(idx, |r| {
+        if r.is_undefined() {
+            // Plug-ins written before `I32 | void` existed return nothing
+            // from `I32` exports, so this still means 0 for now
+            if !WARNED_UNDEFINED_I32.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                extism_pdk::warn!(
+                    "An export declared `I32` returned undefined, which is treated as 0. This is deprecated: declare the result as `I32 | void` if the export returns nothing"
+                );
+            }
+            return Ok(0);
+        }
+        to_i32(&r)
+    })
+    .unwrap_or(-1)
+}
+
+/// Calls an export declared `I32 | void`, which may return nothing to mean
+/// success.
+#[no_mangle]
+pub extern "C" fn __invoke_i32_or_void(idx: i32) -> i32 {
+    invoke(idx, |r| if r.is_undefined() { Ok(0) } else { to_i32(&r) }).unwrap_or(-1)
 }
 
 #[no_mangle]
 pub extern "C" fn __invoke_i64(idx: i32) -> i64 {
-    invoke(idx, |_ctx, r| {
-        if let Some(number) = r.as_big_int() {
-            return number.clone().to_i64().unwrap_or_default();
-        } else if let Some(number) = r.as_number() {
-            return number as i64;
-        }
-        0
-    })
-    .unwrap_or(-1)
+    invoke(idx, |r| to_i64(&r)).unwrap_or(-1)
 }
 
 #[no_mangle]
 pub extern "C" fn __invoke_f64(idx: i32) -> f64 {
-    invoke(idx, |_ctx, r| r.as_float().unwrap_or_default()).unwrap_or(-1.0)
+    invoke(idx, |r| to_float("F64", &r)).unwrap_or(-1.0)
 }
 
 #[no_mangle]
 pub extern "C" fn __invoke_f32(idx: i32) -> f32 {
-    invoke(idx, |_ctx, r| r.as_number().unwrap_or_default() as f32).unwrap_or(-1.0)
+    invoke(idx, |r| to_float("F32", &r).map(|n| n as f32)).unwrap_or(-1.0)
 }
 
 #[no_mangle]
 pub extern "C" fn __invoke(idx: i32) {
-    invoke(idx, |_ctx, _r| ()).unwrap()
+    invoke(idx, |_r| Ok(())).unwrap()
+}
+
+#[no_mangle]
+pub extern "C" fn __result_type(type_code: u32) {
+    RESULT_TYPES.lock().unwrap().push(type_code);
+}
+
+/// Calls an export declared with several results. The shim registers the
+/// result types with `__result_type` beforehand and collects the converted
+/// values with the `__result_*` functions afterwards.
+#[no_mangle]
+pub extern "C" fn __invoke_multi(idx: i32) {
+    let types = std::mem::take(&mut *RESULT_TYPES.lock().unwrap());
+    let results = invoke(idx, |r| to_results(&r, &types)).unwrap_or_default();
+    let mut call_results = CALL_RESULTS.lock().unwrap();
+    call_results.clear();
+    call_results.extend(results.into_iter().rev());
+}
+
+#[no_mangle]
+pub extern "C" fn __result_i32() -> i32 {
+    match CALL_RESULTS.lock().unwrap().pop() {
+        Some(ArgType::I32(v)) => v,
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn __result_i64() -> i64 {
+    match CALL_RESULTS.lock().unwrap().pop() {
+        Some(ArgType::I64(v)) => v,
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn __result_f32() -> f32 {
+    match CALL_RESULTS.lock().unwrap().pop() {
+        Some(ArgType::F32(v)) => v,
+        _ => -1.0,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn __result_f64() -> f64 {
+    match CALL_RESULTS.lock().unwrap().pop() {
+        Some(ArgType::F64(v)) => v,
+        _ => -1.0,
+    }
+}
+
+/// Largest integer a JS number can hold without losing precision.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+fn to_i32(value: &Value) -> Result<i32, String> {
+    let number = value
+        .as_number()
+        .ok_or_else(|| type_mismatch("I32", value))?;
+    if number.fract() != 0.0 || number < i32::MIN as f64 || number > i32::MAX as f64 {
+        return Err(format!(
+            "expected I32, got {} which is out of range",
+            number
+        ));
+    }
+    Ok(number as i32)
+}
+
+fn to_i64(value: &Value) -> Result<i64, String> {
+    if value.is_big_int() {
+        let Coerced(digits) = value.get::<Coerced<String>>().map_err(|e| e.to_string())?;
+        return digits
+            .parse::<i64>()
+            .map_err(|_| format!("expected I64, got {}n which is out of range", digits));
+    }
+    let number = value
+        .as_number()
+        .ok_or_else(|| type_mismatch("I64", value))?;
+    if number.fract() != 0.0 || number.abs() > MAX_SAFE_INTEGER {
+        return Err(format!(
+            "expected I64, got {} which is not a safe integer (use a BigInt)",
+            number
+        ));
+    }
+    Ok(number as i64)
+}
+
+fn to_float(expected: &str, value: &Value) -> Result<f64, String> {
+    value
+        .as_number()
+        .ok_or_else(|| type_mismatch(expected, value))
+}
+
+fn to_results(value: &Value, types: &[u32]) -> Result<Vec<ArgType>, String> {
+    let array = value.as_array().ok_or_else(|| {
+        format!(
+            "expected an array of {} values, got {}",
+            types.len(),
+            js_type_name(value)
+        )
+    })?;
+    if array.len() != types.len() {
+        return Err(format!(
+            "expected an array of {} values, got {}",
+            types.len(),
+            array.len()
+        ));
+    }
+    types
+        .iter()
+        .enumerate()
+        .map(|(i, type_code)| {
+            let item: Value = array.get(i).map_err(|e| e.to_string())?;
+            let result = match *type_code {
+                globals::TYPE_I32 => to_i32(&item).map(ArgType::I32),
+                globals::TYPE_I64 => to_i64(&item).map(ArgType::I64),
+                globals::TYPE_F32 => to_float("F32", &item).map(|n| ArgType::F32(n as f32)),
+                globals::TYPE_F64 => to_float("F64", &item).map(ArgType::F64),
+                t => Err(format!("unsupported result type code {}", t)),
+            };
+            result.map_err(|e| format!("result {}: {}", i, e))
+        })
+        .collect()
+}
+
+fn type_mismatch(expected: &str, value: &Value) -> String {
+    format!("expected {}, got {}", expected, js_type_name(value))
 }
 
-fn export_names(exports: Object) -> anyhow::Result<Vec<String>> {
-    let mut keys_iter: ObjectKeysIter<String> = exports.keys();
-    let mut key = keys_iter.next();
-    let mut keys: Vec<String> = vec![];
-    while key.is_some() {
-        keys.push(key.unwrap()?.to_string());
-        key = keys_iter.next();
+/// Names a value the way `typeof` would, calling out `null` and arrays.
+fn js_type_name(value: &Value) -> &'static str {
+    match value.type_of() {
+        Type::Uninitialized | Type::Undefined => "undefined",
+        Type::Null => "null",
+        Type::Bool => "boolean",
+        Type::Int | Type::Float => "number",
+        Type::BigInt => "bigint",
+        Type::String => "string",
+        Type::Symbol => "symbol",
+        Type::Array => "array",
+        Type::Function | Type::Constructor => "function",
+        Type::Promise => "Promise",
+        _ => "object",
     }
-    keys.sort();
-    Ok(keys)
 }
 
 enum ArgType {
@@ -0,0 +1,157 @@
+declare global {
+  /**
+   * Aborts the `AbortSignal.timeout()` signals that are due and returns the
+   * milliseconds until the next one something listens for is, if any. With
+   * `next`, that one is aborted whether or not it is due.
+   * @internal
+   */
+  var __fireAbortTimeouts: (next?: boolean) => number | undefined;
+
+  /**
+   * Forgets the timeout signals made so far, called at the end of each call.
+   * They still abort when read after their deadline.
+   * @internal
+   */
+  var __clearAbortTimeouts: () => void;
+}
+
+// Timeout signals made during this call that haven't fired yet
+const timeouts = new Set<_AbortSignal>();
+
+class _AbortSignal extends EventTarget {
+  onabort: ((this: _AbortSignal, event: Event) => any) | null = null;
+  private _aborted: boolean = false;
+  private _reason: any = undefined;
+  // Timeouts fire while the engine drains the job queue, and are also checked
+  // whenever the signal is read.
+  /**
+   * @internal
+   */
+  _deadline?: number;
+  private _sources: _AbortSignal[] = [];
+  // Signals from `AbortSignal.any()` that abort along with this one
+  private _dependents: _AbortSignal[] = [];
+
+  get aborted(): boolean {
+    this._poll();
+    return this._aborted;
+  }
+
+  get reason(): any {
+    this._poll();
+    return this._reason;
+  }
+
+  throwIfAborted(): void {
+    if (this.aborted) {
+      throw this._reason;
+    }
+  }
+
+  static abort(reason?: any): _AbortSignal {
+    const signal = new _AbortSignal();
+    signal._abort(reason);
+    return signal;
+  }
+
+  static timeout(milliseconds: number): _AbortSignal {
+    const signal = new _AbortSignal();
+    signal._deadline = performance.now() + milliseconds;
+    timeouts.add(signal);
+    return signal;
+  }
+
+  static any(signals: _AbortSignal[]): _AbortSignal {
+    const signal = new _AbortSignal();
+    for (const source of signals) {
+      if (source.aborted) {
+        signal._abort(source.reason);
+        return signal;
+      }
+    }
+    signal._sources = signals.slice();
+    for (const source of signals) {
+      source._dependents.push(signal);
+    }
+    return signal;
+  }
+
+  /**
+   * Whether aborting this signal would run any code, so that it's worth
+   * waiting for.
+   * @internal
+   */
+  _watched(): boolean {
+    return (
+      this.onabort !== null ||
+      (this as any)._hasListeners("abort") ||
+      this._dependents.some((signal) => signal._watched())
+    );
+  }
+
+  private _poll(): void {
+    if (this._aborted) return;
+    if (this._deadline !== undefined && performance.now() >= this._deadline) {
+      this._abort(new DOMException("signal timed out", "TimeoutError"));
+      return;
+    }
+    // reading a source polls it too, which aborts this signal
+    this._sources.some((source) => source.aborted);
+  }
+
+  /**
+   * @internal
+   */
+  _abort(reason?: any): void {
+    if (this._aborted) return;
+    timeouts.delete(this);
+    this._aborted = true;
+    this._reason =
+      reason !== undefined
+        ? reason
+        : new DOMException("signal is aborted without reason", "AbortError");
+
+    const event = new Event("abort");
+    if (this.onabort) {
+      this.onabort.call(this, event);
+    }
+    this.dispatchEvent(event);
+
+    for (const signal of this._dependents) {
+      signal._abort(this._reason);
+    }
+    this._dependents = [];
+  }
+}
+
+class _AbortController {
+  readonly signal: _AbortSignal = new _AbortSignal();
+
+  abort(reason?: any): void {
+    this.signal._abort(reason);
+  }
+}
+
+globalThis.__fireAbortTimeouts = (next?: boolean) => {
+  let earliest: _AbortSignal | undefined;
+  for (const signal of Array.from(timeouts)) {
+    // reading `aborted` aborts the signal if it's due
+    if (signal.aborted || !signal._watched()) continue;
+    if (!earliest || signal._deadline! < earliest._deadline!) {
+      earliest = signal;
+    }
+  }
+  if (!earliest) return undefined;
+  if (next) {
+    earliest._abort(new DOMException("signal timed out", "TimeoutError"));
+    return globalThis.__fireAbortTimeouts();
+  }
+  return Math.max(0, earliest._deadline! - performance.now());
+};
+
+globalThis.__clearAbortTimeouts = () => timeouts.clear();
+
+globalThis.AbortSignal = _AbortSignal as any;
+globalThis.AbortController = _AbortController as any;
+
+export {};
@@ -0,0 +1,95 @@
+function partToBytes(part: BlobPart): Uint8Array {
+  if (typeof part === "string") {
+    return new TextEncoder().encode(part);
+  }
+  if (part instanceof _Blob) {
+    return part._bytes;
+  }
+  if (ArrayBuffer.isView(part)) {
+    return new Uint8Array(part.buffer, part.byteOffset, part.byteLength);
+  }
+  return new Uint8Array(part as ArrayBufferLike);
+}
+
+class _Blob {
+  readonly type: string;
+  /**
+   * @internal
+   */
+  _bytes: Uint8Array;
+
+  constructor(parts: BlobPart[] = [], options?: { type?: string }) {
+    const chunks = parts.map(partToBytes);
+    const size = chunks.reduce((total, chunk) => total + chunk.byteLength, 0);
+    this._bytes = new Uint8Array(size);
+    let offset = 0;
+    for (const chunk of chunks) {
+      this._bytes.set(chunk, offset);
+      offset += chunk.byteLength;
+    }
+
+    const type = options?.type ?? "";
+    // types with characters outside printable ASCII are ignored, per the spec
+    this.type = /^[\x20-\x7E]*$/.test(type) ? type.toLowerCase() : "";
+  }
+
+  get size(): number {
+    return this._bytes.byteLength;
+  }
+
+  slice(start?: number, end?: number, contentType?: string): _Blob {
+    const blob = new _Blob([], { type: contentType });
+    blob._bytes = this._bytes.slice(start, end);
+    return blob;
+  }
+
+  text(): Promise<string> {
+    return Promise.resolve(new TextDecoder().decode(this._bytes));
+  }
+
+  arrayBuffer(): Promise<ArrayBuffer> {
+    return Promise.resolve(this._bytes.slice().buffer);
+  }
+
+  bytes(): Promise<Uint8Array> {
+    return Promise.resolve(this._bytes.slice());
+  }
+
+  stream(): ReadableStream<Uint8Array> {
+    const bytes = this._bytes;
+    return new ReadableStream<Uint8Array>({
+      start(controller) {
+        if (bytes.byteLength > 0) controller.enqueue(bytes.slice());
+        controller.close();
+      },
+    });
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Blob";
+  }
+}
+
+class _File extends _Blob {
+  readonly name: string;
+  readonly lastModified: number;
+
+  constructor(
+    parts: BlobPart[],
+    name: string,
+    options?: { type?: string; lastModified?: number },
+  ) {
+    super(parts, options);
+    this.name = String(name);
+    this.lastModified = options?.lastModified ?? Date.now();
+  }
+
+  get [Symbol.toStringTag]() {
+    return "File";
+  }
+}
+
+globalThis.Blob = _Blob as any;
+globalThis.File = _File as any;
+
+export {};
@@ -0,0 +1,127 @@
+// Only defined when the engine is built with the `compression` feature
+declare var __compression:
+  | {
+      open(format: string, decompress: boolean): number;
+      write(id: number, data: ArrayBuffer): ArrayBuffer;
+      finish(id: number): ArrayBuffer;
+      close(id: number): void;
+    }
+  | undefined;
+
+declare global {
+  interface Host {
+    /**
+     * Compresses a whole buffer at once. Requires an engine built with the
+     * `compression` feature.
+     */
+    compress(data: string | AllowSharedBufferSource, format: CompressionFormat): Uint8Array;
+    /**
+     * Decompresses a whole buffer at once. Requires an engine built with the
+     * `compression` feature.
+     */
+    decompress(data: AllowSharedBufferSource, format: CompressionFormat): Uint8Array;
+  }
+}
+
+const FORMATS = ["gzip", "deflate", "deflate-raw"];
+
+function checkFormat(format: CompressionFormat): void {
+  if (typeof __compression === "undefined") {
+    throw new Error(
+      "Compression is not available: build the engine with the `compression` feature",
+    );
+  }
+  if (!FORMATS.includes(format)) {
+    throw new TypeError(`Unsupported compression format: ${format}`);
+  }
+}
+
+function openCodec(format: CompressionFormat, decompress: boolean): number {
+  checkFormat(format);
+  return __compression!.open(format, decompress);
+}
+
+function toArrayBuffer(data: string | AllowSharedBufferSource): ArrayBuffer {
+  if (typeof data === "string") {
+    data = new TextEncoder().encode(data);
+  }
+  if (ArrayBuffer.isView(data)) {
+    return data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength);
+  }
+  if (data instanceof ArrayBuffer) {
+    return data;
+  }
+  throw new TypeError("Expected a string, ArrayBuffer or ArrayBufferView");
+}
+
+function runCodec(data: string | AllowSharedBufferSource, format: CompressionFormat, decompress: boolean) {
+  const id = openCodec(format, decompress);
+  let head: ArrayBuffer;
+  try {
+    head = __compression!.write(id, toArrayBuffer(data));
+  } catch (e) {
+    __compression!.close(id);
+    throw e;
+  }
+  const tail = __compression!.finish(id);
+
+  const result = new Uint8Array(head.byteLength + tail.byteLength);
+  result.set(new Uint8Array(head));
+  result.set(new Uint8Array(tail), head.byteLength);
+  return result;
+}
+
+Host.compress = function (data: string | AllowSharedBufferSource, format: CompressionFormat) {
+  return runCodec(data, format, false);
+};
+
+Host.decompress = function (data: AllowSharedBufferSource, format: CompressionFormat) {
+  return runCodec(data, format, true);
+};
+
+function codecTransformer(
+  format: CompressionFormat,
+  decompress: boolean,
+): Transformer<AllowSharedBufferSource, Uint8Array> {
+  checkFormat(format);
+  // Opened by the first chunk, so a stream made ahead of time, e.g. at the top
+  // level, doesn't hold a codec. It's freed when the call that opened it ends.
+  let id: number | undefined;
+  const codec = () => {
+    if (id === undefined) id = openCodec(format, decompress);
+    return id;
+  };
+  return {
+    transform(chunk, controller) {
+      const output = __compression!.write(codec(), toArrayBuffer(chunk));
+      if (output.byteLength > 0) controller.enqueue(new Uint8Array(output));
+    },
+    flush(controller) {
+      const output = __compression!.finish(codec());
+      if (output.byteLength > 0) controller.enqueue(new Uint8Array(output));
+    },
+    cancel() {
+      if (id !== undefined) __compression!.close(id);
+    },
+  };
+}
+
+class _CompressionStream extends TransformStream<AllowSharedBufferSource, Uint8Array> {
+  constructor(format: CompressionFormat) {
+    super(codecTransformer(format, false));
+  }
+}
+
+class _DecompressionStream extends TransformStream<AllowSharedBufferSource, Uint8Array> {
+  constructor(format: CompressionFormat) {
+    super(codecTransformer(format, true));
+  }
+}
+
+// Left undefined without native support so scripts can feature-detect them
+if (typeof __compression !== "undefined") {
+  globalThis.CompressionStream = _CompressionStream as any;
+  globalThis.DecompressionStream = _DecompressionStream as any;
+}
+
+export {};
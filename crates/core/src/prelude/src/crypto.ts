@@ -1,5 +1,450 @@
 declare var __getRandomBytes: (n: number) => ArrayBuffer;
 declare var __shaDigest: (algorithm: string, data: ArrayBuffer) => ArrayBuffer;
+// Only defined when the engine is built with the `webcrypto` feature
+declare var __subtle:
+  | {
+      hmacSign(hash: string, key: ArrayBuffer, data: ArrayBuffer): ArrayBuffer;
+      hmacVerify(hash: string, key: ArrayBuffer, data: ArrayBuffer, signature: ArrayBuffer): boolean;
+      aesGcm(
+        decrypt: boolean,
+        key: ArrayBuffer,
+        iv: ArrayBuffer,
+        additionalData: ArrayBuffer,
+        tagLength: number,
+        data: ArrayBuffer,
+      ): ArrayBuffer;
+      aesCbc(decrypt: boolean, key: ArrayBuffer, iv: ArrayBuffer, data: ArrayBuffer): ArrayBuffer;
+      aesCtr(key: ArrayBuffer, counter: ArrayBuffer, length: number, data: ArrayBuffer): ArrayBuffer;
+      importKey(
+        algorithm: string,
+        curve: string,
+        format: string,
+        data: ArrayBuffer | Record<string, ArrayBuffer | undefined>,
+      ): { private: boolean; material: ArrayBuffer; modulusLength?: number; publicExponent?: ArrayBuffer };
+      asymmetricSign(
+        algorithm: string,
+        curve: string,
+        hash: string,
+        saltLength: number | undefined,
+        key: ArrayBuffer,
+        data: ArrayBuffer,
+      ): ArrayBuffer;
+      asymmetricVerify(
+        algorithm: string,
+        curve: string,
+        hash: string,
+        saltLength: number | undefined,
+        key: ArrayBuffer,
+        data: ArrayBuffer,
+        signature: ArrayBuffer,
+      ): boolean;
+      pbkdf2(hash: string, password: ArrayBuffer, salt: ArrayBuffer, iterations: number, length: number): ArrayBuffer;
+      hkdf(hash: string, key: ArrayBuffer, salt: ArrayBuffer, info: ArrayBuffer, length: number): ArrayBuffer;
+      ecdh(curve: string, privateKey: ArrayBuffer, publicKey: ArrayBuffer): ArrayBuffer;
+      generateKeyPair(curve: string): { publicKey: ArrayBuffer; privateKey: ArrayBuffer };
+    }
+  | undefined;
+
+const HASH_NAMES = ["SHA-1", "SHA-256", "SHA-384", "SHA-512"];
+// digest also takes the SHA-3 family; other hashes are under Host.createHash
+const DIGEST_NAMES = [...HASH_NAMES, "SHA3-256", "SHA3-384", "SHA3-512"];
+const AES_KEY_OPERATIONS = ["importKey", "exportKey", "generateKey", "encrypt", "decrypt"];
+const SIGNATURE_KEY_OPERATIONS = ["importKey", "exportKey", "sign", "verify"];
+const AGREEMENT_KEY_OPERATIONS = ["importKey", "exportKey", "generateKey", "deriveBits", "deriveKey"];
+const DERIVATION_KEY_OPERATIONS = ["importKey", "deriveBits", "deriveKey"];
+
+// The operations each algorithm supports, keyed by its canonical name
+const ALGORITHMS: Record<string, string[]> = {
+  HMAC: ["importKey", "exportKey", "generateKey", "sign", "verify"],
+  "AES-GCM": AES_KEY_OPERATIONS,
+  "AES-CBC": AES_KEY_OPERATIONS,
+  "AES-CTR": AES_KEY_OPERATIONS,
+  ECDSA: SIGNATURE_KEY_OPERATIONS,
+  Ed25519: SIGNATURE_KEY_OPERATIONS,
+  "RSASSA-PKCS1-v1_5": SIGNATURE_KEY_OPERATIONS,
+  "RSA-PSS": SIGNATURE_KEY_OPERATIONS,
+  ECDH: AGREEMENT_KEY_OPERATIONS,
+  X25519: AGREEMENT_KEY_OPERATIONS,
+  PBKDF2: DERIVATION_KEY_OPERATIONS,
+  HKDF: DERIVATION_KEY_OPERATIONS,
+};
+
+const NAMED_CURVES = ["P-256", "P-384"];
+
+// The JWK "kty" each asymmetric algorithm takes
+const JWK_KEY_TYPES: Record<string, string> = {
+  ECDSA: "EC",
+  Ed25519: "OKP",
+  "RSASSA-PKCS1-v1_5": "RSA",
+  "RSA-PSS": "RSA",
+  ECDH: "EC",
+  X25519: "OKP",
+};
+
+// Block size of each hash in bits, the default length of generated HMAC keys
+const HASH_BLOCK_BITS: Record<string, number> = {
+  "SHA-1": 512,
+  "SHA-256": 512,
+  "SHA-384": 1024,
+  "SHA-512": 1024,
+};
+
+class _CryptoKey {
+  readonly type: KeyType;
+  readonly extractable: boolean;
+  readonly algorithm: KeyAlgorithm;
+  readonly usages: KeyUsage[];
+  /**
+   * @internal
+   */
+  _material: ArrayBuffer;
+
+  /**
+   * @internal
+   */
+  constructor(
+    type: KeyType,
+    extractable: boolean,
+    algorithm: KeyAlgorithm,
+    usages: KeyUsage[],
+    material: ArrayBuffer,
+  ) {
+    this.type = type;
+    this.extractable = extractable;
+    this.algorithm = algorithm;
+    this.usages = usages;
+    this._material = material;
+  }
+
+  get [Symbol.toStringTag]() {
+    return "CryptoKey";
+  }
+}
+
+function toArrayBuffer(data: ArrayBuffer | ArrayBufferView): ArrayBuffer {
+  if (ArrayBuffer.isView(data)) {
+    return data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength);
+  }
+  if (data instanceof ArrayBuffer) {
+    return data;
+  }
+  throw new TypeError("Expected an ArrayBuffer or ArrayBufferView");
+}
+
+// Algorithm and hash names are matched case-insensitively, as in browsers
+function normalizeAlgorithm(
+  algorithm: AlgorithmIdentifier,
+  operation: string,
+): Algorithm & Record<string, any> {
+  if (typeof __subtle === "undefined") {
+    throw new DOMException(
+      `crypto.subtle.${operation} is not available: build the engine with the \`webcrypto\` feature`,
+      "NotSupportedError",
+    );
+  }
+  const params = typeof algorithm === "string" ? { name: algorithm } : { ...algorithm };
+  const name = Object.keys(ALGORITHMS).find(
+    (known) => known.toUpperCase() === String(params.name).toUpperCase(),
+  );
+  if (name === undefined || !ALGORITHMS[name].includes(operation)) {
+    throw new DOMException(
+      `Unrecognized algorithm for ${operation}: ${params.name}`,
+      "NotSupportedError",
+    );
+  }
+  return { ...params, name };
+}
+
+function normalizeHash(hash: HashAlgorithmIdentifier | undefined): string {
+  const name = String(typeof hash === "string" ? hash : hash?.name).toUpperCase();
+  if (!HASH_NAMES.includes(name)) {
+    throw new DOMException(`Unsupported hash: ${name}`, "NotSupportedError");
+  }
+  return name;
+}
+
+// Only public keys may be created without any usages
+function checkUsages(usages: KeyUsage[], allowed: KeyUsage[], type: KeyType = "secret"): KeyUsage[] {
+  const invalid = usages.find((usage) => !allowed.includes(usage));
+  if (invalid !== undefined) {
+    throw new DOMException(`Invalid key usage: ${invalid}`, "SyntaxError");
+  }
+  if (usages.length === 0 && type !== "public") {
+    throw new DOMException("Key usages must not be empty", "SyntaxError");
+  }
+  return Array.from(new Set(usages));
+}
+
+function checkKey(key: CryptoKey, name: string, usage: KeyUsage): _CryptoKey {
+  if (!(key instanceof _CryptoKey)) {
+    throw new TypeError("Expected a CryptoKey");
+  }
+  if (key.algorithm.name !== name) {
+    throw new DOMException(`Key is not a ${name} key`, "InvalidAccessError");
+  }
+  if (!key.usages.includes(usage)) {
+    throw new DOMException(`Key does not support the '${usage}' operation`, "InvalidAccessError");
+  }
+  return key;
+}
+
+function hmacKey(
+  hash: string,
+  material: ArrayBuffer,
+  length: number | undefined,
+  extractable: boolean,
+  usages: KeyUsage[],
+): _CryptoKey {
+  const bits = material.byteLength * 8;
+  if (bits === 0) {
+    throw new DOMException("HMAC keys must not be empty", "DataError");
+  }
+  // a length may drop bits from the last byte, but no more
+  if (length !== undefined && (length > bits || length <= bits - 8)) {
+    throw new DOMException("Key length does not match the key data", "DataError");
+  }
+  const algorithm: HmacKeyAlgorithm = { name: "HMAC", hash: { name: hash }, length: length ?? bits };
+  return new _CryptoKey("secret", extractable, algorithm, checkUsages(usages, ["sign", "verify"]), material);
+}
+
+function aesKey(
+  name: string,
+  material: ArrayBuffer,
+  extractable: boolean,
+  usages: KeyUsage[],
+): _CryptoKey {
+  if (![16, 24, 32].includes(material.byteLength)) {
+    throw new DOMException("AES keys must be 128, 192 or 256 bits", "DataError");
+  }
+  const algorithm: AesKeyAlgorithm = { name, length: material.byteLength * 8 };
+  return new _CryptoKey("secret", extractable, algorithm, checkUsages(usages, ["encrypt", "decrypt"]), material);
+}
+
+// Failures in the native code surface as OperationError, as in browsers,
+// or DataError when parsing key data
+function native<T>(operation: () => T, name: string = "OperationError"): T {
+  try {
+    return operation();
+  } catch (e) {
+    throw new DOMException(String(e), name);
+  }
+}
+
+function base64UrlToArrayBuffer(value: unknown, member: string): ArrayBuffer | undefined {
+  if (value === undefined) {
+    return undefined;
+  }
+  if (typeof value !== "string") {
+    throw new DOMException(`JWK member "${member}" must be a string`, "DataError");
+  }
+  const base64 = value.replace(/-/g, "+").replace(/_/g, "/");
+  return native(() => Host.base64ToArrayBuffer(base64.padEnd(Math.ceil(base64.length / 4) * 4, "=")), "DataError");
+}
+
+function asymmetricKey(
+  format: KeyFormat,
+  keyData: ArrayBuffer | ArrayBufferView | JsonWebKey,
+  params: Record<string, any>,
+  extractable: boolean,
+  usages: KeyUsage[],
+): _CryptoKey {
+  const name: string = params.name;
+  const curve = name === "ECDSA" || name === "ECDH" ? namedCurve(params) : "";
+  const hash = name.startsWith("RSA") ? normalizeHash(params.hash) : "";
+
+  let data: ArrayBuffer | Record<string, ArrayBuffer | undefined>;
+  if (format === "jwk") {
+    const jwk = keyData as JsonWebKey;
+    if (jwk.kty !== JWK_KEY_TYPES[name]) {
+      throw new DOMException(`Expected a JWK with kty "${JWK_KEY_TYPES[name]}"`, "DataError");
+    }
+    if (jwk.crv !== undefined && jwk.crv !== (curve || name)) {
+      throw new DOMException(`JWK curve ${jwk.crv} does not match the algorithm`, "DataError");
+    }
+    data = {};
+    for (const member of ["x", "y", "d", "n", "e", "p", "q"] as const) {
+      data[member] = base64UrlToArrayBuffer(jwk[member], member);
+    }
+  } else if (format === "raw" && name.startsWith("RSA")) {
+    throw new DOMException("RSA keys can't be imported in raw format", "NotSupportedError");
+  } else {
+    data = toArrayBuffer(keyData as ArrayBuffer | ArrayBufferView);
+  }
+
+  const imported = native(() => __subtle!.importKey(name, curve, format, data), "DataError");
+  const type: KeyType = imported.private ? "private" : "public";
+  const algorithm: KeyAlgorithm & Record<string, any> = { name };
+  if (curve) {
+    algorithm.namedCurve = curve;
+  }
+  if (hash) {
+    algorithm.modulusLength = imported.modulusLength;
+    algorithm.publicExponent = new Uint8Array(imported.publicExponent!);
+    algorithm.hash = { name: hash };
+  }
+  let allowed: KeyUsage[] = imported.private ? ["sign"] : ["verify"];
+  if (name === "ECDH" || name === "X25519") {
+    allowed = imported.private ? ["deriveBits", "deriveKey"] : [];
+  }
+  return new _CryptoKey(type, extractable, algorithm, checkUsages(usages, allowed, type), imported.material);
+}
+
+function namedCurve(params: Record<string, any>): string {
+  if (!NAMED_CURVES.includes(params.namedCurve)) {
+    throw new DOMException(`Unsupported named curve: ${params.namedCurve}`, "NotSupportedError");
+  }
+  return params.namedCurve;
+}
+
+// ECDH keys are told apart by their named curve, X25519 keys by their name
+function keyCurve(key: _CryptoKey): string {
+  return (key.algorithm as EcKeyAlgorithm).namedCurve ?? key.algorithm.name;
+}
+
+function generateKeyPair(
+  params: Record<string, any>,
+  extractable: boolean,
+  usages: KeyUsage[],
+): CryptoKeyPair {
+  const algorithm: KeyAlgorithm & Record<string, any> = { name: params.name };
+  if (params.name === "ECDH") {
+    algorithm.namedCurve = namedCurve(params);
+  }
+  const privateUsages = checkUsages(usages, ["deriveBits", "deriveKey"]);
+  const pair = native(() => __subtle!.generateKeyPair(algorithm.namedCurve ?? params.name));
+  return {
+    // public keys are always extractable, so they can be sent to the peer
+    publicKey: new _CryptoKey("public", true, algorithm, [], pair.publicKey),
+    privateKey: new _CryptoKey("private", extractable, { ...algorithm }, privateUsages, pair.privateKey),
+  };
+}
+
+// The secret of a password-based or agreement key. Only ECDH and X25519 can
+// leave out the length, which then gives the whole shared secret.
+function deriveBits(params: Record<string, any>, key: _CryptoKey, length: number | null): ArrayBuffer {
+  if (params.name === "PBKDF2" || params.name === "HKDF") {
+    if (length === null || !Number.isInteger(length) || length <= 0 || length % 8 !== 0 || length > 0xffffffff) {
+      throw new DOMException("Length must be a positive multiple of 8 bits", "OperationError");
+    }
+    const hash = normalizeHash(params.hash);
+    const salt = toArrayBuffer(params.salt);
+    if (params.name === "HKDF") {
+      const info = toArrayBuffer(params.info);
+      return native(() => __subtle!.hkdf(hash, key._material, salt, info, length / 8));
+    }
+    const iterations = Number(params.iterations);
+    if (!Number.isInteger(iterations) || iterations <= 0 || iterations > 0xffffffff) {
+      throw new DOMException("PBKDF2 needs a positive iteration count", "OperationError");
+    }
+    return native(() => __subtle!.pbkdf2(hash, key._material, salt, iterations, length / 8));
+  }
+
+  const peer = params.public;
+  if (!(peer instanceof _CryptoKey) || peer.type !== "public") {
+    throw new DOMException("Expected the peer's public key", "InvalidAccessError");
+  }
+  if (peer.algorithm.name !== key.algorithm.name || keyCurve(peer) !== keyCurve(key)) {
+    throw new DOMException("Public key is for a different algorithm", "InvalidAccessError");
+  }
+  const secret = native(() => __subtle!.ecdh(keyCurve(key), key._material, peer._material));
+  if (length === null) {
+    return secret;
+  }
+  if (length > secret.byteLength * 8) {
+    throw new DOMException("Length is longer than the shared secret", "OperationError");
+  }
+  const bits = new Uint8Array(secret.slice(0, Math.ceil(length / 8)));
+  if (length % 8 !== 0) {
+    bits[bits.length - 1] &= 0xff << (8 - (length % 8));
+  }
+  return bits.buffer;
+}
+
+// The length of key deriveKey derives, from the parameters of the key
+function derivedKeyLength(params: Record<string, any>): number {
+  if (params.name === "HMAC") {
+    const length: number = params.length ?? HASH_BLOCK_BITS[normalizeHash(params.hash)];
+    if (!(length > 0)) {
+      throw new DOMException("Key length must be positive", "OperationError");
+    }
+    return length;
+  }
+  if (params.name.startsWith("AES-")) {
+    if (![128, 192, 256].includes(params.length)) {
+      throw new DOMException("AES key length must be 128, 192 or 256 bits", "OperationError");
+    }
+    return params.length;
+  }
+  throw new DOMException(`Can't derive ${params.name} keys`, "NotSupportedError");
+}
+
+// The one format each kind of key is kept in, which exportKey can return as is
+function exportFormat(key: _CryptoKey): KeyFormat | null {
+  if (key.type === "secret") return "raw";
+  if (key.algorithm.name.startsWith("RSA")) {
+    return key.type === "public" ? "spki" : "pkcs8";
+  }
+  return key.type === "public" ? "raw" : null;
+}
+
+function asymmetricSignature(
+  params: Record<string, any>,
+  key: _CryptoKey,
+): [curve: string, hash: string, saltLength: number | undefined] {
+  const algorithm = key.algorithm as KeyAlgorithm & Record<string, any>;
+  switch (params.name) {
+    case "ECDSA":
+      return [algorithm.namedCurve, normalizeHash(params.hash), undefined];
+    case "RSA-PSS": {
+      const saltLength = Number(params.saltLength);
+      if (!(saltLength >= 0)) {
+        throw new TypeError("RSA-PSS needs a saltLength");
+      }
+      return ["", algorithm.hash.name, saltLength];
+    }
+    case "RSASSA-PKCS1-v1_5":
+      return ["", algorithm.hash.name, undefined];
+    default:
+      return ["", "", undefined];
+  }
+}
+
+function aesCrypt(
+  params: Record<string, any>,
+  key: _CryptoKey,
+  data: ArrayBuffer | ArrayBufferView,
+  decrypt: boolean,
+): ArrayBuffer {
+  const input = toArrayBuffer(data);
+  switch (params.name) {
+    case "AES-GCM": {
+      const iv = toArrayBuffer(params.iv);
+      const additionalData = toArrayBuffer(params.additionalData ?? new ArrayBuffer(0));
+      const tagLength = params.tagLength ?? 128;
+      return native(() =>
+        __subtle!.aesGcm(decrypt, key._material, iv, additionalData, tagLength, input),
+      );
+    }
+    case "AES-CBC": {
+      const iv = toArrayBuffer(params.iv);
+      return native(() => __subtle!.aesCbc(decrypt, key._material, iv, input));
+    }
+    default: {
+      const counter = toArrayBuffer(params.counter);
+      const length = Number(params.length);
+      return native(() => __subtle!.aesCtr(key._material, counter, length, input));
+    }
+  }
+}
+
+// WebCrypto reports every failure, even bad arguments, as a rejected promise
+function settle<T>(operation: () => T): Promise<T> {
+  try {
+    return Promise.resolve(operation());
+  } catch (e) {
+    return Promise.reject(e);
+  }
+}
 
 const _crypto = {
   getRandomValues<T extends ArrayBufferView>(array: T): T {
@@ -41,8 +486,10 @@ const _crypto = {
       algorithm: string | { name: string },
       data: ArrayBuffer | ArrayBufferView,
     ): Promise<ArrayBuffer> {
-      const algoName =
-        typeof algorithm === "string" ? algorithm : algorithm.name;
+      const algoName = String(typeof algorithm === "string" ? algorithm : algorithm.name).toUpperCase();
+      if (!DIGEST_NAMES.includes(algoName)) {
+        throw new DOMException(`Unrecognized algorithm for digest: ${algoName}`, "NotSupportedError");
+      }
 
       let buffer: ArrayBuffer;
       if (ArrayBuffer.isView(data)) {
@@ -57,6 +504,178 @@ const _crypto = {
       const result = __shaDigest(algoName, buffer);
       return Promise.resolve(result);
     },
+
+    importKey(
+      format: KeyFormat,
+      keyData: ArrayBuffer | ArrayBufferView | JsonWebKey,
+      algorithm: AlgorithmIdentifier | HmacImportParams | EcKeyImportParams | RsaHashedImportParams,
+      extractable: boolean,
+      keyUsages: KeyUsage[],
+    ): Promise<CryptoKey> {
+      return settle(() => {
+        const params = normalizeAlgorithm(algorithm, "importKey");
+        if (params.name in JWK_KEY_TYPES) {
+          return asymmetricKey(format, keyData, params, extractable, keyUsages);
+        }
+        if (format !== "raw") {
+          throw new DOMException(`Unsupported key format: ${format}`, "NotSupportedError");
+        }
+        // copied so later changes to keyData don't change the key
+        const material = toArrayBuffer(keyData as ArrayBuffer | ArrayBufferView).slice(0);
+        if (params.name === "HMAC") {
+          return hmacKey(normalizeHash(params.hash), material, params.length, extractable, keyUsages);
+        }
+        if (params.name === "PBKDF2" || params.name === "HKDF") {
+          if (extractable) {
+            throw new DOMException(`${params.name} keys can't be extractable`, "SyntaxError");
+          }
+          const usages = checkUsages(keyUsages, ["deriveBits", "deriveKey"]);
+          return new _CryptoKey("secret", false, { name: params.name }, usages, material);
+        }
+        return aesKey(params.name, material, extractable, keyUsages);
+      });
+    },
+
+    exportKey(format: KeyFormat, key: CryptoKey): Promise<ArrayBuffer> {
+      return settle(() => {
+        if (!(key instanceof _CryptoKey)) {
+          throw new TypeError("Expected a CryptoKey");
+        }
+        if (!key.extractable) {
+          throw new DOMException("Key is not extractable", "InvalidAccessError");
+        }
+        if (format !== exportFormat(key)) {
+          throw new DOMException(`Unsupported key format: ${format}`, "NotSupportedError");
+        }
+        return key._material.slice(0);
+      });
+    },
+
+    generateKey(
+      algorithm: AlgorithmIdentifier | HmacKeyGenParams | AesKeyGenParams | EcKeyGenParams,
+      extractable: boolean,
+      keyUsages: KeyUsage[],
+    ): Promise<CryptoKey | CryptoKeyPair> {
+      return settle((): CryptoKey | CryptoKeyPair => {
+        const params = normalizeAlgorithm(algorithm, "generateKey");
+        if (params.name === "ECDH" || params.name === "X25519") {
+          return generateKeyPair(params, extractable, keyUsages);
+        }
+        if (params.name === "HMAC") {
+          const hash = normalizeHash(params.hash);
+          const length: number = params.length ?? HASH_BLOCK_BITS[hash];
+          if (!(length > 0)) {
+            throw new DOMException("Key length must be positive", "OperationError");
+          }
+          const material = __getRandomBytes(Math.ceil(length / 8));
+          return hmacKey(hash, material, length, extractable, keyUsages);
+        }
+        if (![128, 192, 256].includes(params.length)) {
+          throw new DOMException("AES key length must be 128, 192 or 256 bits", "OperationError");
+        }
+        return aesKey(params.name, __getRandomBytes(params.length / 8), extractable, keyUsages);
+      });
+    },
+
+    deriveBits(
+      algorithm: Pbkdf2Params | HkdfParams | EcdhKeyDeriveParams,
+      baseKey: CryptoKey,
+      length: number | null = null,
+    ): Promise<ArrayBuffer> {
+      return settle(() => {
+        const params = normalizeAlgorithm(algorithm, "deriveBits");
+        return deriveBits(params, checkKey(baseKey, params.name, "deriveBits"), length);
+      });
+    },
+
+    deriveKey(
+      algorithm: Pbkdf2Params | HkdfParams | EcdhKeyDeriveParams,
+      baseKey: CryptoKey,
+      derivedKeyType: HmacImportParams | AesDerivedKeyParams,
+      extractable: boolean,
+      keyUsages: KeyUsage[],
+    ): Promise<CryptoKey> {
+      return settle(() => {
+        const params = normalizeAlgorithm(algorithm, "deriveKey");
+        const key = checkKey(baseKey, params.name, "deriveKey");
+        const target = normalizeAlgorithm(derivedKeyType, "importKey");
+        const length = derivedKeyLength(target);
+        const material = deriveBits(params, key, length);
+        if (target.name === "HMAC") {
+          return hmacKey(normalizeHash(target.hash), material, length, extractable, keyUsages);
+        }
+        return aesKey(target.name, material, extractable, keyUsages);
+      });
+    },
+
+    encrypt(
+      algorithm: AlgorithmIdentifier | AesGcmParams | AesCbcParams | AesCtrParams,
+      key: CryptoKey,
+      data: ArrayBuffer | ArrayBufferView,
+    ): Promise<ArrayBuffer> {
+      return settle(() => {
+        const params = normalizeAlgorithm(algorithm, "encrypt");
+        return aesCrypt(params, checkKey(key, params.name, "encrypt"), data, false);
+      });
+    },
+
+    decrypt(
+      algorithm: AlgorithmIdentifier | AesGcmParams | AesCbcParams | AesCtrParams,
+      key: CryptoKey,
+      data: ArrayBuffer | ArrayBufferView,
+    ): Promise<ArrayBuffer> {
+      return settle(() => {
+        const params = normalizeAlgorithm(algorithm, "decrypt");
+        return aesCrypt(params, checkKey(key, params.name, "decrypt"), data, true);
+      });
+    },
+
+    sign(
+      algorithm: AlgorithmIdentifier | EcdsaParams | RsaPssParams,
+      key: CryptoKey,
+      data: ArrayBuffer | ArrayBufferView,
+    ): Promise<ArrayBuffer> {
+      return settle(() => {
+        const params = normalizeAlgorithm(algorithm, "sign");
+        const k = checkKey(key, params.name, "sign");
+        if (params.name === "HMAC") {
+          const hash = (k.algorithm as HmacKeyAlgorithm).hash.name;
+          return __subtle!.hmacSign(hash, k._material, toArrayBuffer(data));
+        }
+        const [curve, hash, saltLength] = asymmetricSignature(params, k);
+        return native(() =>
+          __subtle!.asymmetricSign(params.name, curve, hash, saltLength, k._material, toArrayBuffer(data)),
+        );
+      });
+    },
+
+    verify(
+      algorithm: AlgorithmIdentifier | EcdsaParams | RsaPssParams,
+      key: CryptoKey,
+      signature: ArrayBuffer | ArrayBufferView,
+      data: ArrayBuffer | ArrayBufferView,
+    ): Promise<boolean> {
+      return settle(() => {
+        const params = normalizeAlgorithm(algorithm, "verify");
+        const k = checkKey(key, params.name, "verify");
+        if (params.name === "HMAC") {
+          const hash = (k.algorithm as HmacKeyAlgorithm).hash.name;
+          return __subtle!.hmacVerify(hash, k._material, toArrayBuffer(data), toArrayBuffer(signature));
+        }
+        const [curve, hash, saltLength] = asymmetricSignature(params, k);
+        return native(() =>
+          __subtle!.asymmetricVerify(
+            params.name,
+            curve,
+            hash,
+            saltLength,
+            k._material,
+            toArrayBuffer(data),
+            toArrayBuffer(signature),
+          ),
+        );
+      });
+    },
   },
 
   randomUUID(): string {
@@ -86,5 +705,6 @@ const _crypto = {
 };
 
 globalThis.crypto = _crypto as any;
+globalThis.CryptoKey = _CryptoKey as any;
 
 export {};
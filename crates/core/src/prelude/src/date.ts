@@ -3,17 +3,220 @@ declare global {
    * @internal
    */
   function __getTime(): string;
+
+  /**
+   * @internal
+   */
+  function __getTimeMs(): number;
+
+  /**
+   * @internal
+   */
+  var __tz: {
+    canonicalize(timeZone: string): string;
+    offset(timeZone: string, epochMilliseconds: number): number;
+    possibleOffsets(timeZone: string, wallMilliseconds: number): number[];
+    abbreviation(timeZone: string, epochMilliseconds: number): string | undefined;
+  };
+
+  /**
+   * The plugin's local time zone, set with the `TZ` config key
+   * @internal
+   */
+  var __localTimeZone: () => string;
+
+  /**
+   * @internal
+   */
+  var __canonicalizeTimeZone: (timeZone: string) => string;
+}
+
+const DAY_MS = 86400000;
+
+let localTimeZone: string | undefined;
+
+globalThis.__canonicalizeTimeZone = (timeZone: string) => {
+  try {
+    return __tz.canonicalize(String(timeZone));
+  } catch (e) {
+    throw new RangeError(`Invalid time zone: ${timeZone}`);
+  }
+};
+
+globalThis.__localTimeZone = () => {
+  if (localTimeZone === undefined) {
+    let configured: string | null;
+    try {
+      configured = Config.get("TZ");
+    } catch (e) {
+      // config isn't readable while the plugin is being initialized
+      return "UTC";
+    }
+    localTimeZone = configured ? __canonicalizeTimeZone(configured) : "UTC";
+  }
+  return localTimeZone;
+};
+
+// Read TZ as soon as config is available, so a bad zone fails the first call
+__onResume(() => {
+  localTimeZone = undefined;
+  __localTimeZone();
+});
+
+// UTC needs none of the conversions below, and is the default
+function isUtc(timeZone: string): boolean {
+  return timeZone === "UTC";
+}
+
+function offsetMs(timeZone: string, time: number): number {
+  return __tz.offset(timeZone, time) * 1000;
 }
 
+/**
+ * The instant a local wall-clock time refers to. Times the clocks skipped
+ * over are moved forward by the length of the gap and repeated times resolve
+ * to the earlier instant, as browsers do.
+ */
+function fromWallClock(timeZone: string, wall: number): number {
+  if (isNaN(wall)) {
+    return NaN;
+  }
+  const offsets = __tz.possibleOffsets(timeZone, wall);
+  if (offsets.length > 0) {
+    return wall - Math.max(...offsets) * 1000;
+  }
+  return wall - offsetMs(timeZone, wall - DAY_MS);
+}
+
+// A date whose UTC fields hold the local wall-clock time
+function wallClock(date: Date): Date {
+  const time = date.getTime();
+  if (isNaN(time)) {
+    return new NativeDate(NaN);
+  }
+  return new NativeDate(time + offsetMs(__localTimeZone(), time));
+}
+
+// ISO date-time strings without an offset are local time; date-only ones are UTC
+const LOCAL_ISO_DATE_TIME = /^(?:[+-]\d{6}|\d{4})-\d{2}-\d{2}T\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?$/;
+
+function parse(text: string): number {
+  const timeZone = __localTimeZone();
+  if (isUtc(timeZone) || !LOCAL_ISO_DATE_TIME.test(text)) {
+    return nativeParse(text);
+  }
+  return fromWallClock(timeZone, nativeParse(`${text}Z`));
+}
+
+const NativeDate = Date;
+const nativeParse = Date.parse;
+const proto = Date.prototype as any;
+
+Date.parse = (text: string) => parse(String(text));
+// QuickJS reads the system clock directly, which deterministic mode replaces
+Date.now = () => __getTimeMs();
+
 globalThis.Date = new Proxy(Date, {
   apply() {
     return __getTime();
   },
-  construct(target, args) {
-    if (args.length === 0) return new target(__getTime());
+  construct(target, args, newTarget) {
+    if (args.length === 0) return Reflect.construct(target, [__getTime()], newTarget);
 
-    return Reflect.construct(target, args);
+    const timeZone = __localTimeZone();
+    if (isUtc(timeZone)) {
+      return Reflect.construct(target, args, newTarget);
+    }
+    if (args.length === 1) {
+      const value = args[0];
+      const time = typeof value === "string" ? parse(value) : value;
+      return Reflect.construct(target, [time], newTarget);
+    }
+    const wall = (NativeDate.UTC as (...args: number[]) => number)(...args);
+    return Reflect.construct(target, [fromWallClock(timeZone, wall)], newTarget);
   },
 });
 
+// Local getters read the UTC fields of the shifted wall-clock date
+for (const field of ["FullYear", "Month", "Date", "Day", "Hours", "Minutes", "Seconds", "Milliseconds"]) {
+  const getLocal = proto[`get${field}`];
+  const getUtc = proto[`getUTC${field}`];
+  proto[`get${field}`] = function (this: Date) {
+    if (isUtc(__localTimeZone())) return getLocal.call(this);
+    return getUtc.call(wallClock(this));
+  };
+}
+
+// Local setters update the wall-clock date and convert it back
+for (const field of ["FullYear", "Month", "Date", "Hours", "Minutes", "Seconds", "Milliseconds"]) {
+  const setLocal = proto[`set${field}`];
+  const setUtc = proto[`setUTC${field}`];
+  proto[`set${field}`] = function (this: Date, ...args: number[]) {
+    const timeZone = __localTimeZone();
+    if (isUtc(timeZone)) return setLocal.apply(this, args);
+
+    // setFullYear is the only setter that works on an invalid date
+    const wall = field === "FullYear" && isNaN(this.getTime()) ? new NativeDate(0) : wallClock(this);
+    setUtc.apply(wall, args);
+    return this.setTime(fromWallClock(timeZone, wall.getTime()));
+  };
+}
+
+proto.getTimezoneOffset = function (this: Date) {
+  const time = this.getTime();
+  if (isNaN(time)) return NaN;
+  return -offsetMs(__localTimeZone(), time) / 60000;
+};
+
+const DAYS = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+function pad(value: number, length = 2): string {
+  return String(value).padStart(length, "0");
+}
+
+function dateString(wall: Date): string {
+  const year = wall.getUTCFullYear();
+  const yearString = year < 0 ? `-${pad(-year, 6)}` : pad(year, 4);
+  return `${DAYS[wall.getUTCDay()]} ${MONTHS[wall.getUTCMonth()]} ${pad(wall.getUTCDate())} ${yearString}`;
+}
+
+function timeString(date: Date, wall: Date): string {
+  const timeZone = __localTimeZone();
+  const offset = -date.getTimezoneOffset();
+  const sign = offset < 0 ? "-" : "+";
+  const gmt = `GMT${sign}${pad(Math.floor(Math.abs(offset) / 60))}${pad(Math.abs(offset) % 60)}`;
+  const abbreviation = __tz.abbreviation(timeZone, date.getTime());
+  const time = `${pad(wall.getUTCHours())}:${pad(wall.getUTCMinutes())}:${pad(wall.getUTCSeconds())}`;
+  return abbreviation ? `${time} ${gmt} (${abbreviation})` : `${time} ${gmt}`;
+}
+
+const nativeToString = proto.toString;
+const nativeToDateString = proto.toDateString;
+const nativeToTimeString = proto.toTimeString;
+
+proto.toString = function (this: Date) {
+  if (isUtc(__localTimeZone()) || isNaN(this.getTime())) return nativeToString.call(this);
+  const wall = wallClock(this);
+  return `${dateString(wall)} ${timeString(this, wall)}`;
+};
+proto.toDateString = function (this: Date) {
+  if (isUtc(__localTimeZone()) || isNaN(this.getTime())) return nativeToDateString.call(this);
+  return dateString(wallClock(this));
+};
+proto.toTimeString = function (this: Date) {
+  if (isUtc(__localTimeZone()) || isNaN(this.getTime())) return nativeToTimeString.call(this);
+  return timeString(this, wallClock(this));
+};
+
+// QuickJS formats these in UTC, so they're handed the wall-clock date. The
+// `intl` feature replaces them with time zone aware versions.
+for (const method of ["toLocaleString", "toLocaleDateString", "toLocaleTimeString"]) {
+  const format = proto[method];
+  proto[method] = function (this: Date, ...args: unknown[]) {
+    if (isUtc(__localTimeZone())) return format.apply(this, args);
+    return format.apply(wallClock(this), args);
+  };
+}
+
 export {};
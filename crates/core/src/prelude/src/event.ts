@@ -78,6 +78,13 @@ class _EventTarget {
     );
   }
 
+  /**
+   * @internal
+   */
+  _hasListeners(type: string): boolean {
+    return (this._listeners[type]?.length ?? 0) > 0;
+  }
+
   dispatchEvent(event: _Event): boolean {
     const listeners = this._listeners[event.type];
     if (!listeners || listeners.length === 0) return !event.defaultPrevented;
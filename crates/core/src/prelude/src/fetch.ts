@@ -19,34 +19,33 @@ const STATUS_TEXT: Record<number, string> = {
   504: "Gateway Timeout",
 };
 
+function normalizeHeaders(init: HeadersInit | undefined): [string, string][] {
+  if (!init) return [];
+  if (init instanceof _Headers) return init.entries();
+  if (Array.isArray(init)) return init.map(([name, value]): [string, string] => [name, value]);
+  return Object.keys(init).map((key): [string, string] => [
+    key,
+    (init as Record<string, string>)[key],
+  ]);
+}
+
 class _Headers {
   private _map: Record<string, string> = {};
+  // Set-Cookie values can't be combined with commas, so they are kept apart
+  private _cookies: string[] = [];
 
-  constructor(
-    init?:
-      | Record<string, string>
-      | [string, string][]
-      | _Headers,
-  ) {
-    if (!init) return;
-
-    if (init instanceof _Headers) {
-      Object.keys(init._map).forEach((key) => {
-        this._map[key] = init._map[key];
-      });
-    } else if (Array.isArray(init)) {
-      for (let i = 0; i < init.length; i++) {
-        this._map[init[i][0].toLowerCase()] = init[i][1];
-      }
-    } else {
-      Object.keys(init).forEach((key) => {
-        this._map[key.toLowerCase()] = init[key];
-      });
+  constructor(init?: HeadersInit) {
+    for (const [name, value] of normalizeHeaders(init)) {
+      this.append(name, value);
     }
   }
 
   append(name: string, value: string): void {
     const key = name.toLowerCase();
+    value = String(value);
+    if (key === "set-cookie") {
+      this._cookies.push(value);
+    }
     if (key in this._map) {
       this._map[key] = this._map[key] + ", " + value;
     } else {
@@ -55,7 +54,11 @@ class _Headers {
   }
 
   delete(name: string): void {
-    delete this._map[name.toLowerCase()];
+    const key = name.toLowerCase();
+    if (key === "set-cookie") {
+      this._cookies = [];
+    }
+    delete this._map[key];
   }
 
   get(name: string): string | null {
@@ -63,159 +66,499 @@ class _Headers {
     return val !== undefined ? val : null;
   }
 
+  getSetCookie(): string[] {
+    return this._cookies.slice();
+  }
+
   has(name: string): boolean {
     return name.toLowerCase() in this._map;
   }
 
   set(name: string, value: string): void {
-    this._map[name.toLowerCase()] = value;
+    const key = name.toLowerCase();
+    value = String(value);
+    if (key === "set-cookie") {
+      this._cookies = [value];
+    }
+    this._map[key] = value;
   }
 
   forEach(
     callback: (value: string, key: string, parent: _Headers) => void,
   ): void {
-    Object.keys(this._map).forEach((key) => {
-      callback(this._map[key], key, this);
+    this.entries().forEach(([key, value]) => {
+      callback(value, key, this);
     });
   }
 
+  /**
+   * Sorted by name, with each Set-Cookie header as its own entry.
+   */
   entries(): [string, string][] {
-    return Object.keys(this._map).map((k) => [k, this._map[k]] as [string, string]);
+    const entries: [string, string][] = [];
+    Object.keys(this._map)
+      .sort()
+      .forEach((key) => {
+        if (key === "set-cookie" && this._cookies.length > 0) {
+          this._cookies.forEach((cookie) => entries.push([key, cookie]));
+        } else {
+          entries.push([key, this._map[key]]);
+        }
+      });
+    return entries;
   }
 
   keys(): string[] {
-    return Object.keys(this._map);
+    return this.entries().map(([key]) => key);
   }
 
   values(): string[] {
-    return Object.keys(this._map).map((k) => this._map[k]);
+    return this.entries().map(([, value]) => value);
+  }
+
+  [Symbol.iterator](): IterableIterator<[string, string]> {
+    return this.entries()[Symbol.iterator]();
   }
 }
 
-class _Response {
-  readonly status: number;
-  readonly statusText: string;
-  readonly ok: boolean;
+function escapeMultipartName(name: string): string {
+  return name.replace(/\n/g, "%0A").replace(/\r/g, "%0D").replace(/"/g, "%22");
+}
+
+function encodeMultipart(form: FormData): { bytes: Uint8Array; contentType: string } {
+  const random = crypto.getRandomValues(new Uint8Array(12));
+  const boundary =
+    "----ExtismFormBoundary" +
+    Array.from(random, (b) => b.toString(16).padStart(2, "0")).join("");
+
+  const parts: BlobPart[] = [];
+  form.forEach((value, name) => {
+    let header = `--${boundary}\r\nContent-Disposition: form-data; name="${escapeMultipartName(name)}"`;
+    if (typeof value === "string") {
+      parts.push(header + "\r\n\r\n", value.replace(/\r?\n|\r/g, "\r\n"), "\r\n");
+    } else {
+      header += `; filename="${escapeMultipartName(value.name)}"`;
+      header += `\r\nContent-Type: ${value.type || "application/octet-stream"}`;
+      parts.push(header + "\r\n\r\n", value, "\r\n");
+    }
+  });
+  parts.push(`--${boundary}--\r\n`);
+
+  return {
+    bytes: (new Blob(parts) as any)._bytes,
+    contentType: `multipart/form-data; boundary=${boundary}`,
+  };
+}
+
+function indexOfBytes(haystack: Uint8Array, needle: Uint8Array, from: number): number {
+  outer: for (let i = from; i <= haystack.length - needle.length; i++) {
+    for (let j = 0; j < needle.length; j++) {
+      if (haystack[i + j] !== needle[j]) continue outer;
+    }
+    return i;
+  }
+  return -1;
+}
+
+function parseMultipart(bytes: Uint8Array, boundary: string): FormData {
+  const form = new FormData();
+  const encoder = new TextEncoder();
+  const decoder = new TextDecoder();
+  const delimiter = encoder.encode("--" + boundary);
+  const headerEnd = encoder.encode("\r\n\r\n");
+
+  let pos = indexOfBytes(bytes, delimiter, 0);
+  while (pos !== -1) {
+    let start = pos + delimiter.length;
+    // "--" after the delimiter closes the body
+    if (bytes[start] === 0x2d && bytes[start + 1] === 0x2d) break;
+    start += 2;
+
+    const next = indexOfBytes(bytes, delimiter, start);
+    if (next === -1) break;
+    // the CRLF before the next delimiter belongs to the delimiter
+    const part = bytes.subarray(start, next - 2);
+    const split = indexOfBytes(part, headerEnd, 0);
+    if (split === -1) {
+      throw new TypeError("Malformed multipart/form-data body");
+    }
+
+    let name: string | undefined;
+    let filename: string | undefined;
+    let type = "";
+    for (const line of decoder.decode(part.subarray(0, split)).split("\r\n")) {
+      const [key, ...rest] = line.split(":");
+      const value = rest.join(":").trim();
+      if (key.toLowerCase() === "content-disposition") {
+        name = /;\s*name="([^"]*)"/i.exec(value)?.[1];
+        filename = /;\s*filename="([^"]*)"/i.exec(value)?.[1];
+      } else if (key.toLowerCase() === "content-type") {
+        type = value;
+      }
+    }
+    if (name === undefined) {
+      throw new TypeError("Multipart part is missing a name");
+    }
+
+    const content = part.subarray(split + headerEnd.length);
+    if (filename !== undefined) {
+      form.append(name, new File([content.slice()], filename, { type }));
+    } else {
+      form.append(name, decoder.decode(content));
+    }
+    pos = next;
+  }
+  return form;
+}
+
+/**
+ * Converts a body to bytes along with the Content-Type it implies, if any.
+ * Streams are kept as they are and only read when the body is consumed.
+ */
+function extractBody(body: BodyInit | null | undefined): {
+  bytes: Uint8Array | null;
+  stream: ReadableStream<Uint8Array> | null;
+  contentType: string | null;
+} {
+  if (body === null || body === undefined) {
+    return { bytes: null, stream: null, contentType: null };
+  }
+  if (body instanceof ReadableStream) {
+    if (body.locked || (body as any)._disturbed) {
+      throw new TypeError("The body stream is locked or has already been read");
+    }
+    return { bytes: null, stream: body, contentType: null };
+  }
+  if (body instanceof URLSearchParams) {
+    return {
+      bytes: new TextEncoder().encode(body.toString()),
+      stream: null,
+      contentType: "application/x-www-form-urlencoded;charset=UTF-8",
+    };
+  }
+  if (body instanceof FormData) {
+    return { ...encodeMultipart(body), stream: null };
+  }
+  if (body instanceof Blob) {
+    return { bytes: (body as any)._bytes, stream: null, contentType: body.type || null };
+  }
+  if (ArrayBuffer.isView(body)) {
+    return {
+      bytes: new Uint8Array(body.buffer, body.byteOffset, body.byteLength).slice(),
+      stream: null,
+      contentType: null,
+    };
+  }
+  if (body instanceof ArrayBuffer) {
+    return { bytes: new Uint8Array(body.slice(0)), stream: null, contentType: null };
+  }
+  return {
+    bytes: new TextEncoder().encode(String(body)),
+    stream: null,
+    contentType: "text/plain;charset=UTF-8",
+  };
+}
+
+function readAll(stream: ReadableStream<Uint8Array>): Promise<Uint8Array> {
+  const reader = stream.getReader();
+  const chunks: Uint8Array[] = [];
+  const next = (): Promise<Uint8Array> =>
+    reader.read().then(({ done, value }) => {
+      if (!done) {
+        if (!(value instanceof Uint8Array)) {
+          throw new TypeError("Body stream chunks must be Uint8Arrays");
+        }
+        chunks.push(value);
+        return next();
+      }
+      return (new Blob(chunks) as any)._bytes;
+    });
+  return next();
+}
+
+class _Body {
   readonly headers: _Headers;
-  readonly url: string;
-  private _body: string;
+  /**
+   * @internal
+   */
+  _body: Uint8Array | null;
+  /**
+   * @internal
+   */
+  _stream: ReadableStream<Uint8Array> | null;
   private _bodyUsed: boolean = false;
 
-  constructor(
-    body: string | null,
-    init?: { status?: number; statusText?: string; headers?: _Headers },
-  ) {
-    this._body = body || "";
-    this.status = init?.status ?? 200;
-    this.statusText = init?.statusText ?? STATUS_TEXT[this.status] ?? "";
-    this.ok = this.status >= 200 && this.status < 300;
-    this.headers = init?.headers ?? new _Headers();
-    this.url = "";
+  constructor(body: BodyInit | null | undefined, headers: _Headers) {
+    const extracted = extractBody(body);
+    this._body = extracted.bytes;
+    this._stream = extracted.stream;
+    this.headers = headers;
+    if (extracted.contentType !== null && !headers.has("content-type")) {
+      headers.set("content-type", extracted.contentType);
+    }
+  }
+
+  /**
+   * The body as a stream, created on first access for byte bodies.
+   */
+  get body(): ReadableStream<Uint8Array> | null {
+    if (this._stream === null && this._body !== null) {
+      const bytes = this._bodyUsed ? new Uint8Array(0) : this._body;
+      this._stream = new ReadableStream<Uint8Array>({
+        start(controller) {
+          if (bytes.byteLength > 0) controller.enqueue(bytes);
+          controller.close();
+        },
+      });
+      this._body = null;
+    }
+    return this._stream;
   }
 
   get bodyUsed(): boolean {
-    return this._bodyUsed;
+    return this._bodyUsed || (this._stream !== null && (this._stream as any)._disturbed);
   }
 
-  text(): Promise<string> {
-    if (this._bodyUsed) {
-      return Promise.reject(new Error("Body has already been consumed"));
+  // Byte bodies resolve right away; only stream bodies need to be read
+  private _consume<T>(convert: (body: Uint8Array) => T): Promise<T> {
+    if (this.bodyUsed) {
+      return Promise.reject(new TypeError("Body has already been consumed"));
+    }
+    if (this._stream !== null) {
+      if (this._stream.locked) {
+        return Promise.reject(new TypeError("The body stream is locked"));
+      }
+      this._bodyUsed = true;
+      return readAll(this._stream).then(convert);
+    }
+    if (this._body !== null) {
+      this._bodyUsed = true;
+    }
+    try {
+      return Promise.resolve(convert(this._body ?? new Uint8Array(0)));
+    } catch (e) {
+      return Promise.reject(e);
     }
-    this._bodyUsed = true;
-    return Promise.resolve(this._body);
+  }
+
+  text(): Promise<string> {
+    return this._consume((body) => new TextDecoder().decode(body));
   }
 
   json(): Promise<any> {
-    if (this._bodyUsed) {
-      return Promise.reject(new Error("Body has already been consumed"));
-    }
-    this._bodyUsed = true;
-    return Promise.resolve(JSON.parse(this._body));
+    return this._consume((body) => JSON.parse(new TextDecoder().decode(body)));
   }
 
   arrayBuffer(): Promise<ArrayBuffer> {
-    if (this._bodyUsed) {
-      return Promise.reject(new Error("Body has already been consumed"));
+    return this._consume((body) => body.slice().buffer);
+  }
+
+  bytes(): Promise<Uint8Array> {
+    return this._consume((body) => body.slice());
+  }
+
+  blob(): Promise<Blob> {
+    return this._consume(
+      (body) => new Blob([body], { type: this.headers.get("content-type") ?? "" }),
+    );
+  }
+
+  formData(): Promise<FormData> {
+    const contentType = this.headers.get("content-type") ?? "";
+    return this._consume((body) => {
+      if (/^application\/x-www-form-urlencoded/i.test(contentType)) {
+        const form = new FormData();
+        new URLSearchParams(new TextDecoder().decode(body)).forEach((value, key) => {
+          form.append(key, value);
+        });
+        return form;
+      }
+      const boundary = /^multipart\/form-data;.*boundary="?([^";]+)"?/i.exec(contentType);
+      if (boundary) {
+        return parseMultipart(body, boundary[1]);
+      }
+      throw new TypeError(`Cannot parse a body of type "${contentType}" as FormData`);
+    });
+  }
+
+  /**
+   * @internal
+   */
+  _cloneBody(): Uint8Array | ReadableStream<Uint8Array> | null {
+    if (this.bodyUsed) {
+      throw new TypeError("Cannot clone a body that has already been consumed");
     }
-    this._bodyUsed = true;
-    const encoder = new TextEncoder();
-    return Promise.resolve(encoder.encode(this._body).buffer);
+    if (this._stream !== null) {
+      const [mine, theirs] = this._stream.tee();
+      this._stream = mine;
+      return theirs;
+    }
+    return this._body;
   }
+}
 
-  clone(): _Response {
-    if (this._bodyUsed) {
-      throw new Error("Cannot clone a Response whose body has already been consumed");
+const NULL_BODY_STATUSES = [101, 204, 205, 304];
+const REDIRECT_STATUSES = [301, 302, 303, 307, 308];
+
+class _Response extends _Body {
+  readonly status: number;
+  readonly statusText: string;
+  readonly ok: boolean;
+  readonly url: string;
+  readonly type: ResponseType = "default";
+  readonly redirected: boolean = false;
+
+  constructor(body?: BodyInit | null, init?: ResponseInit) {
+    super(body, new _Headers(init?.headers));
+    this.status = init?.status ?? 200;
+    if (this.status < 200 || this.status > 599) {
+      throw new RangeError(`Invalid response status code ${this.status}`);
     }
-    const cloned = new _Response(this._body, {
-      status: this.status,
+    if ((this._body !== null || this._stream !== null) && NULL_BODY_STATUSES.includes(this.status)) {
+      throw new TypeError(`Response with status ${this.status} cannot have a body`);
+    }
+    this.statusText = init?.statusText ?? STATUS_TEXT[this.status] ?? "";
+    this.ok = this.status >= 200 && this.status < 300;
+    this.url = "";
+  }
+
+  static error(): _Response {
+    const response = new _Response(null);
+    Object.assign(response, { type: "error", status: 0, statusText: "", ok: false });
+    return response;
+  }
+
+  static redirect(url: string | URL, status: number = 302): _Response {
+    if (!REDIRECT_STATUSES.includes(status)) {
+      throw new RangeError(`Invalid redirect status code ${status}`);
+    }
+    return new _Response(null, {
+      status,
+      headers: { location: new URL(String(url)).toString() },
+    });
+  }
+
+  static json(data: any, init?: ResponseInit): _Response {
+    const response = new _Response(JSON.stringify(data), init);
+    response.headers.set("content-type", "application/json");
+    return response;
+  }
+
+  clone(): _Response {
+    const cloned = new _Response(this._cloneBody(), {
+      status: this.status || 200,
       statusText: this.statusText,
       headers: new _Headers(this.headers),
     });
+    Object.assign(cloned, {
+      status: this.status,
+      ok: this.ok,
+      url: this.url,
+      type: this.type,
+      redirected: this.redirected,
+    });
     return cloned;
   }
 }
 
-globalThis.fetch = function fetch(
-  input: string | URL,
-  init?: {
-    method?: string;
-    headers?: _Headers | Record<string, string> | [string, string][];
-    body?: string;
-  },
-): Promise<_Response> {
-  var url: string;
-  if (typeof input === "string") {
-    url = input;
-  } else {
-    url = input.toString();
-  }
+class _Request extends _Body {
+  readonly url: string;
+  readonly method: string;
+  readonly redirect: RequestRedirect;
+  readonly credentials: RequestCredentials = "same-origin";
+  readonly mode: RequestMode = "cors";
+  readonly cache: RequestCache = "default";
+  readonly referrer: string = "about:client";
+  readonly integrity: string = "";
+  readonly keepalive: boolean = false;
+  readonly signal: AbortSignal;
 
-  var method = (init?.method ?? "GET").toUpperCase();
-  var reqHeaders: Record<string, string | number | boolean> = {};
+  constructor(input: string | URL | _Request, init: RequestInit = {}) {
+    const source = input instanceof _Request ? input : null;
+    const method = (init.method ?? source?.method ?? "GET").toUpperCase();
+    const headers = new _Headers(init.headers ?? source?.headers);
 
-  if (init?.headers) {
-    if (init.headers instanceof _Headers) {
-      init.headers.forEach((value, key) => {
-        reqHeaders[key] = value;
-      });
-    } else if (Array.isArray(init.headers)) {
-      for (let i = 0; i < init.headers.length; i++) {
-        reqHeaders[init.headers[i][0]] = init.headers[i][1];
-      }
-    } else {
-      reqHeaders = init.headers as Record<string, string>;
+    let body: BodyInit | null | undefined = init.body;
+    if (body === undefined && source) {
+      body = source._cloneBody();
     }
+    if (body !== undefined && body !== null && (method === "GET" || method === "HEAD")) {
+      throw new TypeError("Request with GET/HEAD method cannot have body");
+    }
+
+    super(body, headers);
+
+    const url = source ? source.url : String(input);
+    try {
+      new URL(url);
+    } catch {
+      throw new TypeError(`Invalid URL: ${url}`);
+    }
+    this.url = url;
+    this.method = method;
+    this.redirect = init.redirect ?? source?.redirect ?? "follow";
+    this.signal = init.signal ?? source?.signal ?? new AbortController().signal;
   }
 
-  var body = init?.body;
+  clone(): _Request {
+    return new _Request(this);
+  }
+}
+
+function send(request: _Request, body: Uint8Array | null): _Response {
+  var reqHeaders: Record<string, string | number | boolean> = {};
+  request.headers.forEach((value, key) => {
+    reqHeaders[key] = value;
+  });
 
   var httpReq: HttpRequest = {
-    url: url,
-    method: method as HttpRequest["method"],
+    url: request.url,
+    method: request.method as HttpRequest["method"],
     headers: reqHeaders,
   };
 
-  try {
-    var httpRes =
-      body !== undefined ? Http.request(httpReq, body) : Http.request(httpReq);
+  var httpRes =
+    body !== null ? Http.request(httpReq, body) : Http.request(httpReq);
 
-    var responseHeaders = new _Headers(httpRes.headers || {});
+  // The request can't be interrupted while it runs, but a signal that
+  // aborted or timed out meanwhile still rejects the fetch.
+  request.signal.throwIfAborted();
 
-    var response = new _Response(httpRes.body, {
+  var responseHeaders = new _Headers(httpRes.headers || {});
+
+  var response = new _Response(
+    NULL_BODY_STATUSES.includes(httpRes.status) ? null : httpRes.bodyBytes,
+    {
       status: httpRes.status,
       statusText: STATUS_TEXT[httpRes.status] ?? "",
       headers: responseHeaders,
-    });
-    // Store the url on the response (readonly in spec, but we set it internally)
-    (response as any).url = url;
+    },
+  );
+  // Store the url on the response (readonly in spec, but we set it internally)
+  (response as any).url = request.url;
+  return response;
+}
+
+globalThis.fetch = function fetch(
+  input: string | URL | _Request,
+  init?: RequestInit,
+): Promise<_Response> {
+  try {
+    var request = new _Request(input, init);
+    request.signal.throwIfAborted();
 
-    return Promise.resolve(response);
+    // A streamed request body has to be read in full before it can be sent
+    if (request._stream !== null) {
+      return request.bytes().then((body) => send(request, body));
+    }
+    return Promise.resolve(send(request, request._body));
   } catch (err) {
     return Promise.reject(err);
   }
 };
 
-globalThis.Headers = _Headers;
-globalThis.Response = _Response;
+globalThis.Headers = _Headers as any;
+globalThis.Request = _Request as any;
+globalThis.Response = _Response as any;
 
 export {};
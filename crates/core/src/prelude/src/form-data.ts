@@ -0,0 +1,76 @@
+function toEntryValue(value: string | Blob, filename?: string): FormDataEntryValue {
+  if (!(value instanceof Blob)) {
+    return String(value);
+  }
+  if (value instanceof File && filename === undefined) {
+    return value;
+  }
+  return new File([value], filename ?? "blob", { type: value.type });
+}
+
+class _FormData {
+  private _entries: [string, FormDataEntryValue][] = [];
+
+  append(name: string, value: string | Blob, filename?: string): void {
+    this._entries.push([String(name), toEntryValue(value, filename)]);
+  }
+
+  set(name: string, value: string | Blob, filename?: string): void {
+    name = String(name);
+    const entry: [string, FormDataEntryValue] = [name, toEntryValue(value, filename)];
+    const index = this._entries.findIndex(([key]) => key === name);
+    if (index === -1) {
+      this._entries.push(entry);
+      return;
+    }
+    this._entries = this._entries.filter(([key], i) => i <= index || key !== name);
+    this._entries[index] = entry;
+  }
+
+  get(name: string): FormDataEntryValue | null {
+    const entry = this._entries.find(([key]) => key === name);
+    return entry ? entry[1] : null;
+  }
+
+  getAll(name: string): FormDataEntryValue[] {
+    return this._entries.filter(([key]) => key === name).map(([, value]) => value);
+  }
+
+  has(name: string): boolean {
+    return this._entries.some(([key]) => key === name);
+  }
+
+  delete(name: string): void {
+    this._entries = this._entries.filter(([key]) => key !== name);
+  }
+
+  forEach(
+    callback: (value: FormDataEntryValue, key: string, parent: _FormData) => void,
+  ): void {
+    this._entries.forEach(([key, value]) => callback(value, key, this));
+  }
+
+  entries(): IterableIterator<[string, FormDataEntryValue]> {
+    return this._entries.slice()[Symbol.iterator]();
+  }
+
+  keys(): IterableIterator<string> {
+    return this._entries.map(([key]) => key)[Symbol.iterator]();
+  }
+
+  values(): IterableIterator<FormDataEntryValue> {
+    return this._entries.map(([, value]) => value)[Symbol.iterator]();
+  }
+
+  [Symbol.iterator](): IterableIterator<[string, FormDataEntryValue]> {
+    return this.entries();
+  }
+
+  get [Symbol.toStringTag]() {
+    return "FormData";
+  }
+}
+
+globalThis.FormData = _FormData as any;
+
+export {};
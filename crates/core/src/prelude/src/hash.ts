@@ -0,0 +1,131 @@
+declare var __shaDigest: (algorithm: string, data: ArrayBuffer) => ArrayBuffer;
+// Only defined when the engine is built with the `hashes` feature
+declare var __hash:
+  | {
+      open(algorithm: string): number;
+      update(id: number, data: ArrayBuffer): void;
+      finish(id: number): ArrayBuffer;
+    }
+  | undefined;
+
+declare global {
+  interface Host {
+    /**
+     * Hashes a whole input at once. Algorithm names are matched
+     * case-insensitively. Algorithms other than SHA-1 and SHA-2 require an
+     * engine built with the `hashes` feature.
+     */
+    hash(algorithm: HashAlgorithm, data: string | AllowSharedBufferSource): Uint8Array;
+    /**
+     * Starts an incremental hash, so large inputs can be hashed chunk by
+     * chunk. The native state is released by `digest()`, or when the call
+     * that created the hash ends. Requires an engine built with the `hashes`
+     * feature.
+     */
+    createHash(algorithm: HashAlgorithm): Hash;
+  }
+
+  /**
+   * `BLAKE2b` is BLAKE2b-512. `CRC32`, `XXH64` and `XXH3` (64-bit, seed 0)
+   * are checksums, not cryptographic hashes, and are returned big-endian.
+   */
+  type HashAlgorithm =
+    | "SHA-1"
+    | "SHA-256"
+    | "SHA-384"
+    | "SHA-512"
+    | "SHA3-256"
+    | "SHA3-384"
+    | "SHA3-512"
+    | "BLAKE2b"
+    | "BLAKE3"
+    | "MD5"
+    | "CRC32"
+    | "XXH64"
+    | "XXH3";
+
+  /**
+   * An incremental hash from `Host.createHash`. It only lives until the end
+   * of the export call that created it: digest it before returning, as a
+   * hash kept for a later call throws when it's used.
+   */
+  interface Hash {
+    update(data: string | AllowSharedBufferSource): Hash;
+    digest(): Uint8Array;
+  }
+}
+
+const ALGORITHMS: HashAlgorithm[] = [
+  "SHA-1",
+  "SHA-256",
+  "SHA-384",
+  "SHA-512",
+  "SHA3-256",
+  "SHA3-384",
+  "SHA3-512",
+  "BLAKE2b",
+  "BLAKE3",
+  "MD5",
+  "CRC32",
+  "XXH64",
+  "XXH3",
+];
+
+function toArrayBuffer(data: string | AllowSharedBufferSource): ArrayBuffer {
+  if (typeof data === "string") {
+    data = new TextEncoder().encode(data);
+  }
+  if (ArrayBuffer.isView(data)) {
+    return data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength);
+  }
+  if (data instanceof ArrayBuffer) {
+    return data;
+  }
+  throw new TypeError("Expected a string, ArrayBuffer or ArrayBufferView");
+}
+
+function canonicalName(algorithm: string): HashAlgorithm {
+  const name = ALGORITHMS.find((known) => known.toUpperCase() === String(algorithm).toUpperCase());
+  if (name === undefined) {
+    throw new TypeError(`Unsupported hash algorithm: ${algorithm}`);
+  }
+  return name;
+}
+
+class _Hash {
+  private _id: number | null;
+
+  constructor(algorithm: HashAlgorithm) {
+    if (typeof __hash === "undefined") {
+      throw new Error("Host.createHash is not available: build the engine with the `hashes` feature");
+    }
+    this._id = __hash.open(canonicalName(algorithm));
+  }
+
+  update(data: string | AllowSharedBufferSource): _Hash {
+    if (this._id === null) {
+      throw new Error("Hash has already been digested");
+    }
+    __hash!.update(this._id, toArrayBuffer(data));
+    return this;
+  }
+
+  digest(): Uint8Array {
+    if (this._id === null) {
+      throw new Error("Hash has already been digested");
+    }
+    const id = this._id;
+    this._id = null;
+    return new Uint8Array(__hash!.finish(id));
+  }
+}
+
+Host.hash = function (algorithm: HashAlgorithm, data: string | AllowSharedBufferSource) {
+  return new Uint8Array(__shaDigest(canonicalName(algorithm), toArrayBuffer(data)));
+};
+
+Host.createHash = function (algorithm: HashAlgorithm) {
+  return new _Hash(algorithm);
+};
+
+export {};
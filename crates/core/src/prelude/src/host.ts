@@ -5,14 +5,82 @@ declare global {
      */
     __hostFunctions: Array<{ name: string; results: number }>;
 
-    invokeFunc(id: number, ...args: unknown[]): number;
+    /**
+     * @internal
+     */
+    __exportNames: string[];
+
+    /**
+     * Host functions declared with a tuple result, e.g. `[I64, I32]`, return an array.
+     */
+    invokeFunc(id: number, ...args: unknown[]): number | number[];
     inputBytes(): ArrayBufferLike;
     inputString(): string;
     outputBytes(output: ArrayBufferLike): boolean;
     outputString(output: string): boolean;
+    /**
+     * Reads the plugin input in ranges instead of loading all of it up front.
+     * The input lives in host memory, so every read copies the bytes it
+     * returns into a new buffer.
+     */
+    inputReader(): InputReader;
+    /**
+     * Builds the plugin output incrementally in host memory. The output is set
+     * when the writer is closed.
+     */
+    outputWriter(sizeHint?: number): OutputWriter;
+    /**
+     * A WritableStream over `outputWriter`, so results can be piped straight
+     * to the plugin output. Aborting the stream discards what was written.
+     */
+    outputStream(sizeHint?: number): WritableStream<string | ArrayBufferLike | ArrayBufferView>;
     getFunctions(): import("extism:host").user;
     arrayBufferToBase64(input: ArrayBuffer): string;
     base64ToArrayBuffer(input: string): ArrayBuffer;
+
+    /**
+     * @internal
+     */
+    _inputLength(): number;
+    /**
+     * @internal
+     */
+    _inputRead(offset: number, buffer: ArrayBufferLike, byteOffset: number, byteLength: number): number;
+    /**
+     * @internal
+     */
+    _outputOpen(sizeHint?: number): void;
+    /**
+     * @internal
+     */
+    _outputWrite(data: string | ArrayBufferLike, byteOffset?: number, byteLength?: number): void;
+    /**
+     * @internal
+     */
+    _outputClose(): number;
+    /**
+     * @internal
+     */
+    _outputDiscard(): void;
+  }
+
+  interface InputReader {
+    readonly length: number;
+    /** Copies `length` bytes starting at `offset`; shorter at the end of the input. */
+    read(offset: number, length: number): Uint8Array;
+    /** Fills `target` from `offset`, returning the number of bytes copied. */
+    readInto(target: ArrayBufferView, offset: number): number;
+    /** Copies the input in consecutive chunks, one at a time. */
+    chunks(chunkSize?: number): IterableIterator<Uint8Array>;
+    /** The whole input, loaded on first use. */
+    bytes(): Uint8Array;
+    text(): string;
+  }
+
+  interface OutputWriter {
+    write(chunk: string | ArrayBufferLike | ArrayBufferView): void;
+    /** Sets everything written as the plugin output and returns its length. */
+    close(): number;
   }
 
   var Host: Host;
@@ -31,4 +99,101 @@ Host.getFunctions = function () {
   }, {});
 };
 
+const DEFAULT_CHUNK_SIZE = 64 * 1024;
+
+// Only the length is read up front. Reads copy out of host memory, since JS
+// can't reach it directly, so this bounds what's held at once rather than what
+// is copied.
+class InputReader implements globalThis.InputReader {
+  readonly length = Host._inputLength();
+  private _bytes?: Uint8Array;
+
+  read(offset: number, length: number) {
+    const target = new Uint8Array(Math.max(0, Math.min(length, this.length - offset)));
+    this.readInto(target, offset);
+    return target;
+  }
+
+  readInto(target: ArrayBufferView, offset: number) {
+    if (this._bytes) {
+      const source = this._bytes.subarray(offset, offset + target.byteLength);
+      new Uint8Array(target.buffer, target.byteOffset, source.length).set(source);
+      return source.length;
+    }
+    return Host._inputRead(offset, target.buffer, target.byteOffset, target.byteLength);
+  }
+
+  *chunks(chunkSize = DEFAULT_CHUNK_SIZE) {
+    for (let offset = 0; offset < this.length; offset += chunkSize) {
+      yield this.read(offset, chunkSize);
+    }
+  }
+
+  bytes() {
+    if (!this._bytes) {
+      this._bytes = this.read(0, this.length);
+    }
+    return this._bytes;
+  }
+
+  text() {
+    return new TextDecoder().decode(this.bytes());
+  }
+}
+
+class OutputWriter implements globalThis.OutputWriter {
+  private _closed: boolean = false;
+
+  constructor(sizeHint?: number) {
+    Host._outputOpen(sizeHint);
+  }
+
+  write(chunk: string | ArrayBufferLike | ArrayBufferView) {
+    if (this._closed) {
+      throw new Error("The output writer is closed");
+    }
+    if (typeof chunk === "string") {
+      Host._outputWrite(chunk);
+    } else if (ArrayBuffer.isView(chunk)) {
+      Host._outputWrite(chunk.buffer, chunk.byteOffset, chunk.byteLength);
+    } else {
+      Host._outputWrite(chunk, 0, chunk.byteLength);
+    }
+  }
+
+  close() {
+    if (this._closed) {
+      throw new Error("The output writer is closed");
+    }
+    this._closed = true;
+    return Host._outputClose();
+  }
+}
+
+Host.inputReader = function () {
+  return new InputReader();
+};
+
+Host.outputWriter = function (sizeHint?: number) {
+  return new OutputWriter(sizeHint);
+};
+
+Host.outputStream = function (sizeHint?: number) {
+  let writer: OutputWriter;
+  return new WritableStream<string | ArrayBufferLike | ArrayBufferView>({
+    start() {
+      writer = new OutputWriter(sizeHint);
+    },
+    write(chunk) {
+      writer.write(chunk);
+    },
+    close() {
+      writer.close();
+    },
+    abort() {
+      Host._outputDiscard();
+    },
+  });
+};
+
 export { };
\ No newline at end of file
@@ -16,7 +16,11 @@ declare global {
   }
 
   interface HttpResponse {
-    body: string;
+    /**
+     * the response body decoded as UTF-8, decoded on first access
+     */
+    readonly body: string;
+    bodyBytes: ArrayBuffer;
     status: number;
     /**
      * the host needs to enable allow_http_response_headers for this to be present
@@ -25,28 +29,40 @@ declare global {
   }
 
   var Http: {
-    request(req: HttpRequest, body?: string | ArrayBufferLike): HttpResponse;
+    request(req: HttpRequest, body?: string | ArrayBufferLike | ArrayBufferView): HttpResponse;
   };
 }
 
 Http.request = new Proxy(Http.request, {
   apply(target, thisArg, [req, body]) {
-    // convert body to string if it's an arraybuffer
-    if (typeof body === "object" && "byteLength" in body) {
-      body = new Uint8Array(body).toString();
+    // byte bodies are sent as-is, views only send the bytes they cover
+    if (ArrayBuffer.isView(body)) {
+      body = body.buffer.slice(body.byteOffset, body.byteOffset + body.byteLength);
     }
 
     if (req.method === undefined) {
       req.method = "GET";
     }
 
-    return Reflect.apply(
+    const res = Reflect.apply(
       target,
       thisArg,
       // TODO: We need to completely avoid passing a second argument due to a bug in the runtime,
       // which converts `undefined` to `"undefined"`. This is also the case for req.method.
       body !== undefined ? [req, body] : [req],
     );
+
+    let text: string | undefined;
+    Object.defineProperty(res, "body", {
+      enumerable: true,
+      get() {
+        if (text === undefined) {
+          text = new TextDecoder().decode(res.bodyBytes);
+        }
+        return text;
+      },
+    });
+    return res;
   },
 });
 
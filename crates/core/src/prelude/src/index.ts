@@ -8,8 +8,10 @@ import "core-js/actual/url/to-json";
 import "core-js/actual/url-search-params";
 import "urlpattern-polyfill";
 
+import "./resume";
 import "./config";
 import "./date";
+import "./temporal";
 import "./text-decoder";
 import "./text-encoder";
 import "./host";
@@ -22,8 +24,16 @@ import "./dom-exception";
 import "./atob-btoa";
 import "./structured-clone";
 import "./performance";
+import "./streams";
+import "./compression";
+import "./blob";
+import "./form-data";
 import "./fetch";
 import "./crypto";
+import "./hash";
+import "./intl";
 import "./event";
+import "./rejection";
+import "./abort";
 import "./buffer";
 import "./compat";
\ No newline at end of file
@@ -0,0 +1,534 @@
+// Only defined when the engine is built with the `intl` feature
+declare var __intl:
+  | {
+      locales: string[];
+      canonicalize(tag: string): string;
+      dateTimeFormat(locale: string, dateStyle: string, timeStyle: string): string;
+      numberFormat(locale: string, style: string, currency: string, useGrouping: boolean): string;
+      collator(locale: string, sensitivity: string, numeric: boolean, ignorePunctuation: boolean): string;
+      pluralRules(locale: string, type: string): string;
+      formatDate(
+        key: string,
+        year: number,
+        month: number,
+        day: number,
+        hour: number,
+        minute: number,
+        second: number,
+      ): string;
+      formatNumber(
+        key: string,
+        value: number | string,
+        minimumIntegerDigits: number,
+        minimumFractionDigits: number,
+        maximumFractionDigits: number,
+      ): string;
+      compare(key: string, a: string, b: string): number;
+      selectPlural(
+        key: string,
+        value: number,
+        minimumFractionDigits: number,
+        maximumFractionDigits: number,
+      ): string;
+    }
+  | undefined;
+
+type LocalesArgument = string | readonly string[] | undefined;
+
+// Errors from the native code are bad options or locales
+function native<T>(operation: () => T): T {
+  try {
+    return operation();
+  } catch (e) {
+    throw new RangeError(String(e));
+  }
+}
+
+function canonicalizeLocaleList(locales: LocalesArgument): string[] {
+  if (locales === undefined) {
+    return [];
+  }
+  const canonical: string[] = [];
+  for (const tag of typeof locales === "string" ? [locales] : Array.from(locales)) {
+    if (typeof tag !== "string") {
+      throw new TypeError("Language tags must be strings");
+    }
+    const locale = native(() => __intl!.canonicalize(tag));
+    if (!canonical.includes(locale)) {
+      canonical.push(locale);
+    }
+  }
+  return canonical;
+}
+
+// A locale is supported when its language was built in; regional variants
+// fall back to the language's data
+function isSupported(locale: string): boolean {
+  const language = locale.split("-")[0];
+  return __intl!.locales.some((available) => available === locale || available.split("-")[0] === language);
+}
+
+function resolveLocale(locales: LocalesArgument): string {
+  const locale = canonicalizeLocaleList(locales).find(isSupported);
+  return locale ?? __intl!.locales[0] ?? "und";
+}
+
+function supportedLocalesOf(locales: LocalesArgument): string[] {
+  return canonicalizeLocaleList(locales).filter(isSupported);
+}
+
+function getOption<T extends string>(
+  options: Record<string, any>,
+  name: string,
+  allowed: readonly T[],
+  fallback: T,
+): T;
+function getOption<T extends string>(
+  options: Record<string, any>,
+  name: string,
+  allowed: readonly T[],
+  fallback?: T,
+): T | undefined;
+function getOption<T extends string>(
+  options: Record<string, any>,
+  name: string,
+  allowed: readonly T[],
+  fallback?: T,
+): T | undefined {
+  const value = options[name];
+  if (value === undefined) {
+    return fallback;
+  }
+  if (!allowed.includes(String(value) as T)) {
+    throw new RangeError(`Invalid value for ${name}: ${value}`);
+  }
+  return String(value) as T;
+}
+
+function getNumberOption(
+  options: Record<string, any>,
+  name: string,
+  minimum: number,
+  maximum: number,
+  fallback: number,
+): number {
+  const value = options[name];
+  if (value === undefined) {
+    return fallback;
+  }
+  const number = Number(value);
+  if (!(number >= minimum && number <= maximum)) {
+    throw new RangeError(`${name} must be between ${minimum} and ${maximum}`);
+  }
+  return Math.floor(number);
+}
+
+// Calling the constructors without `new` works as it does in browsers
+function callable<T extends new (...args: any[]) => any>(constructor: T): T {
+  return new Proxy(constructor, {
+    apply(target, _thisArg, args) {
+      return Reflect.construct(target, args);
+    },
+  });
+}
+
+const STYLES = ["full", "long", "medium", "short"] as const;
+const DATE_COMPONENTS = ["weekday", "era", "year", "month", "day"];
+const TIME_COMPONENTS = ["hour", "minute", "second", "fractionalSecondDigits", "timeZoneName"];
+
+/**
+ * Dates are formatted with CLDR's date and time lengths. Component options
+ * such as `{ month: "long" }` pick the closest length instead of building a
+ * custom pattern.
+ */
+function dateTimeStyles(
+  options: Intl.DateTimeFormatOptions,
+  required: "date" | "time" | "any",
+  defaults: "date" | "time" | "all",
+): [dateStyle: string, timeStyle: string] {
+  const o = options as Record<string, any>;
+  const dateStyle = getOption(o, "dateStyle", STYLES);
+  const timeStyle = getOption(o, "timeStyle", STYLES);
+  const hasDate = DATE_COMPONENTS.some((name) => o[name] !== undefined);
+  const hasTime = TIME_COMPONENTS.some((name) => o[name] !== undefined);
+
+  if (dateStyle !== undefined || timeStyle !== undefined) {
+    if (hasDate || hasTime) {
+      throw new TypeError("dateStyle and timeStyle can't be combined with other date and time options");
+    }
+    const missing = required === "date" ? dateStyle : required === "time" ? timeStyle : "";
+    if (missing === undefined) {
+      throw new TypeError(`Expected a ${required}Style`);
+    }
+    return [dateStyle ?? "", timeStyle ?? ""];
+  }
+
+  let date = "";
+  if (o.weekday !== undefined || o.era !== undefined) {
+    date = "full";
+  } else if (o.month === "long") {
+    date = "long";
+  } else if (o.month === "short" || o.month === "narrow") {
+    date = "medium";
+  } else if (hasDate) {
+    date = "short";
+  }
+
+  let time = "";
+  if (o.timeZoneName !== undefined) {
+    time = "long";
+  } else if (o.second !== undefined || o.fractionalSecondDigits !== undefined) {
+    time = "medium";
+  } else if (hasTime) {
+    time = "short";
+  }
+
+  if (!hasDate && !hasTime) {
+    if (defaults !== "time") date = "short";
+    if (defaults !== "date") time = "medium";
+  }
+  return [date, time];
+}
+
+class _DateTimeFormat {
+  private _locale: string;
+  private _dateStyle: string;
+  private _timeStyle: string;
+  private _hourCycle: string | undefined;
+  private _timeZone: string;
+  private _key: string;
+  private _format: ((date?: Date | number) => string) | undefined;
+
+  constructor(locales?: LocalesArgument, options: Intl.DateTimeFormatOptions = {}) {
+    this._initialize(locales, options, "any", "date");
+  }
+
+  /**
+   * @internal
+   */
+  _initialize(
+    locales: LocalesArgument,
+    options: Intl.DateTimeFormatOptions,
+    required: "date" | "time" | "any",
+    defaults: "date" | "time" | "all",
+  ) {
+    this._locale = resolveLocale(locales);
+    const o = options as Record<string, any>;
+    this._timeZone =
+      o.timeZone === undefined ? __localTimeZone() : __canonicalizeTimeZone(o.timeZone);
+    [this._dateStyle, this._timeStyle] = dateTimeStyles(options, required, defaults);
+
+    // the hour cycle is passed on as a Unicode extension of the locale
+    this._hourCycle = getOption(o, "hourCycle", ["h11", "h12", "h23", "h24"]);
+    if (o.hour12 !== undefined) {
+      this._hourCycle = o.hour12 ? "h12" : "h23";
+    }
+    const locale = this._hourCycle ? `${this._locale}-u-hc-${this._hourCycle}` : this._locale;
+    this._key = native(() => __intl!.dateTimeFormat(locale, this._dateStyle, this._timeStyle));
+  }
+
+  get format(): (date?: Date | number) => string {
+    this._format ??= (date?: Date | number) => {
+      const time = date === undefined ? Date.now() : Number(date);
+      if (isNaN(time)) {
+        throw new RangeError("Invalid time value");
+      }
+      // the UTC fields of `d` are the wall-clock time in the time zone
+      const d = new Date(time + __tz.offset(this._timeZone, time) * 1000);
+      return native(() =>
+        __intl!.formatDate(
+          this._key,
+          d.getUTCFullYear(),
+          d.getUTCMonth() + 1,
+          d.getUTCDate(),
+          d.getUTCHours(),
+          d.getUTCMinutes(),
+          d.getUTCSeconds(),
+        ),
+      );
+    };
+    return this._format;
+  }
+
+  resolvedOptions(): Intl.ResolvedDateTimeFormatOptions {
+    const options: Record<string, any> = {
+      locale: this._locale,
+      calendar: "gregory",
+      numberingSystem: "latn",
+      timeZone: this._timeZone,
+    };
+    if (this._hourCycle) {
+      options.hourCycle = this._hourCycle;
+      options.hour12 = this._hourCycle === "h11" || this._hourCycle === "h12";
+    }
+    if (this._dateStyle) options.dateStyle = this._dateStyle;
+    if (this._timeStyle) options.timeStyle = this._timeStyle;
+    return options as Intl.ResolvedDateTimeFormatOptions;
+  }
+
+  static supportedLocalesOf(locales: LocalesArgument): string[] {
+    return supportedLocalesOf(locales);
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Intl.DateTimeFormat";
+  }
+}
+
+function dateTimeFormat(
+  locales: LocalesArgument,
+  options: Intl.DateTimeFormatOptions | undefined,
+  required: "date" | "time" | "any",
+  defaults: "date" | "time" | "all",
+): _DateTimeFormat {
+  const format = Object.create(_DateTimeFormat.prototype) as _DateTimeFormat;
+  format._initialize(locales, options ?? {}, required, defaults);
+  return format;
+}
+
+// ISO 4217 currencies without the usual two minor digits
+const CURRENCY_DIGITS: Record<string, number> = {
+  BHD: 3, BIF: 0, CLP: 0, DJF: 0, GNF: 0, IQD: 3, ISK: 0, JOD: 3, JPY: 0, KMF: 0, KRW: 0,
+  KWD: 3, LYD: 3, OMR: 3, PYG: 0, RWF: 0, TND: 3, UGX: 0, UYI: 0, VND: 0, VUV: 0, XAF: 0,
+  XOF: 0, XPF: 0,
+};
+
+class _NumberFormat {
+  private _locale: string;
+  private _style: "decimal" | "currency" | "percent";
+  private _currency: string | undefined;
+  private _useGrouping: boolean;
+  private _minimumIntegerDigits: number;
+  private _minimumFractionDigits: number;
+  private _maximumFractionDigits: number;
+  private _key: string;
+  private _format: ((value: number | bigint | string) => string) | undefined;
+
+  constructor(locales?: LocalesArgument, options: Intl.NumberFormatOptions = {}) {
+    const o = options as Record<string, any>;
+    this._locale = resolveLocale(locales);
+    this._style = getOption(o, "style", ["decimal", "currency", "percent"] as const, "decimal");
+    getOption(o, "notation", ["standard"] as const);
+    getOption(o, "currencyDisplay", ["symbol"] as const);
+
+    let defaultMinimum = 0;
+    let defaultMaximum = this._style === "decimal" ? 3 : 0;
+    if (this._style === "currency") {
+      if (o.currency === undefined) {
+        throw new TypeError("Currency code is required with currency style");
+      }
+      this._currency = String(o.currency).toUpperCase();
+      if (!/^[A-Z]{3}$/.test(this._currency)) {
+        throw new RangeError(`Invalid currency code: ${o.currency}`);
+      }
+      defaultMinimum = defaultMaximum = CURRENCY_DIGITS[this._currency] ?? 2;
+    }
+
+    this._minimumIntegerDigits = getNumberOption(o, "minimumIntegerDigits", 1, 21, 1);
+    this._minimumFractionDigits = getNumberOption(o, "minimumFractionDigits", 0, 20, defaultMinimum);
+    this._maximumFractionDigits = getNumberOption(
+      o,
+      "maximumFractionDigits",
+      0,
+      20,
+      Math.max(this._minimumFractionDigits, defaultMaximum),
+    );
+    if (this._minimumFractionDigits > this._maximumFractionDigits) {
+      throw new RangeError("maximumFractionDigits is less than minimumFractionDigits");
+    }
+    this._useGrouping = o.useGrouping === undefined ? true : Boolean(o.useGrouping);
+
+    this._key = native(() =>
+      __intl!.numberFormat(this._locale, this._style, this._currency ?? "", this._useGrouping),
+    );
+  }
+
+  get format(): (value: number | bigint | string) => string {
+    this._format ??= (value: number | bigint | string) => {
+      const number = typeof value === "bigint" ? value : Number(value);
+      if (typeof number === "number" && !isFinite(number)) {
+        if (isNaN(number)) return "NaN";
+        return number < 0 ? "-∞" : "∞";
+      }
+      const input = typeof number === "bigint" ? number.toString() : number;
+      return native(() =>
+        __intl!.formatNumber(
+          this._key,
+          input,
+          this._minimumIntegerDigits,
+          this._minimumFractionDigits,
+          this._maximumFractionDigits,
+        ),
+      );
+    };
+    return this._format;
+  }
+
+  resolvedOptions(): Intl.ResolvedNumberFormatOptions {
+    const options: Record<string, any> = {
+      locale: this._locale,
+      numberingSystem: "latn",
+      style: this._style,
+      minimumIntegerDigits: this._minimumIntegerDigits,
+      minimumFractionDigits: this._minimumFractionDigits,
+      maximumFractionDigits: this._maximumFractionDigits,
+      useGrouping: this._useGrouping,
+      notation: "standard",
+    };
+    if (this._currency) {
+      options.currency = this._currency;
+      options.currencyDisplay = "symbol";
+    }
+    return options as Intl.ResolvedNumberFormatOptions;
+  }
+
+  static supportedLocalesOf(locales: LocalesArgument): string[] {
+    return supportedLocalesOf(locales);
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Intl.NumberFormat";
+  }
+}
+
+class _Collator {
+  private _locale: string;
+  private _usage: string;
+  private _sensitivity: string;
+  private _ignorePunctuation: boolean;
+  private _numeric: boolean;
+  private _key: string;
+  private _compare: ((a: string, b: string) => number) | undefined;
+
+  constructor(locales?: LocalesArgument, options: Intl.CollatorOptions = {}) {
+    const o = options as Record<string, any>;
+    this._locale = resolveLocale(locales);
+    this._usage = getOption(o, "usage", ["sort", "search"] as const, "sort");
+    const sensitivities = ["base", "accent", "case", "variant"] as const;
+    this._sensitivity = getOption(o, "sensitivity", sensitivities, "variant");
+    this._ignorePunctuation = Boolean(o.ignorePunctuation);
+    // `-u-kn` in the locale turns numeric collation on unless the option says otherwise
+    this._numeric =
+      o.numeric === undefined
+        ? /-u-(?:.*-)?kn(?:-true)?(?:-|$)/.test(this._locale)
+        : Boolean(o.numeric);
+    this._key = native(() =>
+      __intl!.collator(this._locale, this._sensitivity, this._numeric, this._ignorePunctuation),
+    );
+  }
+
+  get compare(): (a: string, b: string) => number {
+    this._compare ??= (a: string, b: string) => __intl!.compare(this._key, String(a), String(b));
+    return this._compare;
+  }
+
+  resolvedOptions(): Intl.ResolvedCollatorOptions {
+    return {
+      locale: this._locale,
+      usage: this._usage,
+      sensitivity: this._sensitivity,
+      ignorePunctuation: this._ignorePunctuation,
+      collation: "default",
+      numeric: this._numeric,
+      caseFirst: "false",
+    };
+  }
+
+  static supportedLocalesOf(locales: LocalesArgument): string[] {
+    return supportedLocalesOf(locales);
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Intl.Collator";
+  }
+}
+
+class _PluralRules {
+  private _locale: string;
+  private _type: "cardinal" | "ordinal";
+  private _minimumFractionDigits: number;
+  private _maximumFractionDigits: number;
+  private _key: string;
+
+  constructor(locales?: LocalesArgument, options: Intl.PluralRulesOptions = {}) {
+    const o = options as Record<string, any>;
+    this._locale = resolveLocale(locales);
+    this._type = getOption(o, "type", ["cardinal", "ordinal"] as const, "cardinal");
+    this._minimumFractionDigits = getNumberOption(o, "minimumFractionDigits", 0, 20, 0);
+    this._maximumFractionDigits = getNumberOption(
+      o,
+      "maximumFractionDigits",
+      0,
+      20,
+      Math.max(this._minimumFractionDigits, 3),
+    );
+    this._key = native(() => __intl!.pluralRules(this._locale, this._type));
+  }
+
+  select(value: number): Intl.LDMLPluralRule {
+    const number = Number(value);
+    if (!isFinite(number)) {
+      return "other";
+    }
+    return native(() =>
+      __intl!.selectPlural(this._key, number, this._minimumFractionDigits, this._maximumFractionDigits),
+    ) as Intl.LDMLPluralRule;
+  }
+
+  resolvedOptions(): Record<string, unknown> {
+    return {
+      locale: this._locale,
+      type: this._type,
+      minimumIntegerDigits: 1,
+      minimumFractionDigits: this._minimumFractionDigits,
+      maximumFractionDigits: this._maximumFractionDigits,
+    };
+  }
+
+  static supportedLocalesOf(locales: LocalesArgument): string[] {
+    return supportedLocalesOf(locales);
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Intl.PluralRules";
+  }
+}
+
+// Without the `intl` feature `Intl` stays undefined, so scripts can feature-detect it
+if (typeof __intl !== "undefined") {
+  globalThis.Intl = {
+    DateTimeFormat: callable(_DateTimeFormat),
+    NumberFormat: callable(_NumberFormat),
+    Collator: callable(_Collator),
+    PluralRules: _PluralRules,
+    getCanonicalLocales: canonicalizeLocaleList,
+  } as any;
+
+  type DateOptions = Intl.DateTimeFormatOptions;
+  type NumberOptions = Intl.NumberFormatOptions;
+
+  Date.prototype.toLocaleString = function (locales?: LocalesArgument, options?: DateOptions) {
+    return dateTimeFormat(locales, options, "any", "all").format(this);
+  };
+  Date.prototype.toLocaleDateString = function (locales?: LocalesArgument, options?: DateOptions) {
+    return dateTimeFormat(locales, options, "date", "date").format(this);
+  };
+  Date.prototype.toLocaleTimeString = function (locales?: LocalesArgument, options?: DateOptions) {
+    return dateTimeFormat(locales, options, "time", "time").format(this);
+  };
+  Number.prototype.toLocaleString = function (locales?: LocalesArgument, options?: NumberOptions) {
+    return new _NumberFormat(locales, options).format(Number(this));
+  };
+  BigInt.prototype.toLocaleString = function (locales?: LocalesArgument, options?: NumberOptions) {
+    return new _NumberFormat(locales, options).format(BigInt(this));
+  };
+  String.prototype.localeCompare = function (
+    that: string,
+    locales?: LocalesArgument,
+    options?: Intl.CollatorOptions,
+  ) {
+    return new _Collator(locales, options).compare(String(this), String(that));
+  };
+}
+
+export {};
@@ -4,6 +4,11 @@ declare global {
    */
   function __getTimeMs(): number;
 
+  /**
+   * @internal
+   */
+  function __getTimeOrigin(): number;
+
   interface Performance {
     now(): number;
     readonly timeOrigin: number;
@@ -12,12 +17,12 @@ declare global {
   var performance: Performance;
 }
 
-const timeOrigin = __getTimeMs();
-
 globalThis.performance = {
-  timeOrigin,
+  get timeOrigin(): number {
+    return __getTimeOrigin();
+  },
   now(): number {
-    return __getTimeMs() - timeOrigin;
+    return __getTimeMs() - __getTimeOrigin();
   },
 };
 
@@ -0,0 +1,78 @@
+declare global {
+  /**
+   * Called by the engine's promise rejection tracker
+   * @internal
+   */
+  var __trackRejection: (promise: Promise<unknown>, reason: unknown, handled: boolean) => void;
+
+  /**
+   * Fires `unhandledrejection` for the rejections still unhandled after a call
+   * and returns the reasons no listener prevented. `except` is the Promise the
+   * export returned, which the engine handles itself.
+   * @internal
+   */
+  var __reportRejections: (except?: unknown) => unknown[];
+
+  var onunhandledrejection: ((event: PromiseRejectionEvent) => void) | null;
+  var addEventListener: EventTarget["addEventListener"];
+  var removeEventListener: EventTarget["removeEventListener"];
+  var dispatchEvent: EventTarget["dispatchEvent"];
+}
+
+class _PromiseRejectionEvent extends Event implements PromiseRejectionEvent {
+  readonly promise: Promise<unknown>;
+  readonly reason: unknown;
+
+  constructor(
+    type: string,
+    init: { promise: Promise<unknown>; reason?: unknown; bubbles?: boolean; cancelable?: boolean },
+  ) {
+    super(type, init);
+    this.promise = init.promise;
+    this.reason = init.reason;
+  }
+}
+
+// globalThis is an event target, as `window` is in browsers
+const target = new EventTarget();
+globalThis.addEventListener = target.addEventListener.bind(target);
+globalThis.removeEventListener = target.removeEventListener.bind(target);
+globalThis.dispatchEvent = target.dispatchEvent.bind(target);
+globalThis.onunhandledrejection = null;
+
+// Rejected promises nothing has handled yet. A handler added later in the
+// same call, before the engine reports, still counts.
+const pending = new Map<Promise<unknown>, unknown>();
+
+globalThis.__trackRejection = (promise, reason, handled) => {
+  if (handled) {
+    pending.delete(promise);
+  } else {
+    pending.set(promise, reason);
+  }
+};
+
+globalThis.__reportRejections = (except?: unknown) => {
+  const unhandled: unknown[] = [];
+  const rejections = Array.from(pending);
+  pending.clear();
+  for (const [promise, reason] of rejections) {
+    if (promise === except) continue;
+    const event = new _PromiseRejectionEvent("unhandledrejection", {
+      promise,
+      reason,
+      cancelable: true,
+    });
+    if (typeof globalThis.onunhandledrejection === "function") {
+      globalThis.onunhandledrejection(event);
+    }
+    if (dispatchEvent(event)) {
+      unhandled.push(reason);
+    }
+  }
+  return unhandled;
+};
+
+globalThis.PromiseRejectionEvent = _PromiseRejectionEvent as any;
+
+export {};
@@ -0,0 +1,30 @@
+declare global {
+  /**
+   * Registers a function to run when the engine resumes from its Wizer
+   * snapshot, before the first call. Everything computed while the engine is
+   * wizened is frozen into the binary, so state that should differ between
+   * instances is refreshed here.
+   * @internal
+   */
+  var __onResume: (hook: () => void) => void;
+
+  /**
+   * Runs the resume hooks. Called by the engine.
+   * @internal
+   */
+  var __resume: () => void;
+}
+
+const hooks: Array<() => void> = [];
+
+globalThis.__onResume = (hook: () => void) => {
+  hooks.push(hook);
+};
+
+globalThis.__resume = () => {
+  for (const hook of hooks) {
+    hook();
+  }
+};
+
+export {};
@@ -0,0 +1,965 @@
+interface Deferred<T> {
+  promise: Promise<T>;
+  resolve: (value: T) => void;
+  reject: (reason: any) => void;
+  settled: boolean;
+}
+
+function deferred<T = void>(): Deferred<T> {
+  const d = { settled: false } as Deferred<T>;
+  d.promise = new Promise<T>((resolve, reject) => {
+    d.resolve = (value) => {
+      d.settled = true;
+      resolve(value);
+    };
+    d.reject = (reason) => {
+      d.settled = true;
+      reject(reason);
+    };
+  });
+  return d;
+}
+
+// Rejections that nobody has to observe, like a reader's `closed` promise
+function handled<T>(d: Deferred<T>): Deferred<T> {
+  d.promise.catch(() => {});
+  return d;
+}
+
+function promiseCall<T>(fn: () => T | PromiseLike<T>): Promise<T> {
+  try {
+    return Promise.resolve(fn());
+  } catch (e) {
+    return Promise.reject(e);
+  }
+}
+
+function extractHighWaterMark(strategy: QueuingStrategy<any>, defaultHWM: number): number {
+  const hwm = strategy.highWaterMark === undefined ? defaultHWM : Number(strategy.highWaterMark);
+  if (Number.isNaN(hwm) || hwm < 0) {
+    throw new RangeError("Invalid highWaterMark");
+  }
+  return hwm;
+}
+
+function extractSize<T>(strategy: QueuingStrategy<T>): (chunk: T) => number {
+  const size = strategy.size;
+  return size ? (chunk) => size(chunk) : () => 1;
+}
+
+class _CountQueuingStrategy {
+  readonly highWaterMark: number;
+
+  constructor(init: { highWaterMark: number }) {
+    this.highWaterMark = init.highWaterMark;
+  }
+
+  get size(): (chunk: any) => number {
+    return () => 1;
+  }
+}
+
+class _ByteLengthQueuingStrategy {
+  readonly highWaterMark: number;
+
+  constructor(init: { highWaterMark: number }) {
+    this.highWaterMark = init.highWaterMark;
+  }
+
+  get size(): (chunk: ArrayBufferView) => number {
+    return (chunk) => chunk.byteLength;
+  }
+}
+
+interface QueueEntry<T> {
+  value: T;
+  size: number;
+}
+
+class _ReadableStreamDefaultController<R> {
+  /**
+   * @internal
+   */
+  _stream: _ReadableStream<R>;
+
+  constructor(stream: _ReadableStream<R>) {
+    this._stream = stream;
+  }
+
+  // Byte streams are served as default streams, so there is never a BYOB request
+  get byobRequest(): null {
+    return null;
+  }
+
+  get desiredSize(): number | null {
+    return this._stream._desiredSize();
+  }
+
+  enqueue(chunk: R): void {
+    const stream = this._stream;
+    if (stream._closeRequested || stream._state !== "readable") {
+      throw new TypeError("Cannot enqueue into a stream that is closed or errored");
+    }
+    stream._enqueue(chunk);
+  }
+
+  close(): void {
+    const stream = this._stream;
+    if (stream._closeRequested || stream._state !== "readable") {
+      throw new TypeError("The stream is already closed or errored");
+    }
+    stream._close();
+  }
+
+  error(reason?: any): void {
+    this._stream._error(reason);
+  }
+}
+
+class _ReadableStreamDefaultReader<R> {
+  /**
+   * @internal
+   */
+  _stream: _ReadableStream<R> | null;
+  /**
+   * @internal
+   */
+  _readRequests: Deferred<ReadableStreamReadResult<R>>[] = [];
+  /**
+   * @internal
+   */
+  _closed: Deferred<undefined> = handled(deferred<undefined>());
+
+  constructor(stream: _ReadableStream<R>) {
+    if (stream._reader) {
+      throw new TypeError("ReadableStream is already locked to a reader");
+    }
+    this._stream = stream;
+    stream._reader = this;
+    if (stream._state === "closed") {
+      this._closed.resolve(undefined);
+    } else if (stream._state === "errored") {
+      this._closed.reject(stream._storedError);
+    }
+  }
+
+  get closed(): Promise<undefined> {
+    return this._closed.promise;
+  }
+
+  read(): Promise<ReadableStreamReadResult<R>> {
+    const stream = this._stream;
+    if (!stream) {
+      return Promise.reject(new TypeError("The reader has been released"));
+    }
+    stream._disturbed = true;
+    if (stream._state === "closed") {
+      return Promise.resolve({ done: true, value: undefined });
+    }
+    if (stream._state === "errored") {
+      return Promise.reject(stream._storedError);
+    }
+    if (stream._queue.length > 0) {
+      const value = stream._dequeue();
+      return Promise.resolve({ done: false, value });
+    }
+    const request = deferred<ReadableStreamReadResult<R>>();
+    this._readRequests.push(request);
+    stream._callPullIfNeeded();
+    return request.promise;
+  }
+
+  cancel(reason?: any): Promise<void> {
+    if (!this._stream) {
+      return Promise.reject(new TypeError("The reader has been released"));
+    }
+    return this._stream._cancel(reason);
+  }
+
+  releaseLock(): void {
+    const stream = this._stream;
+    if (!stream) return;
+    const error = new TypeError("The reader has been released");
+    this._readRequests.splice(0).forEach((request) => request.reject(error));
+    if (this._closed.settled) {
+      this._closed = handled(deferred<undefined>());
+    }
+    this._closed.reject(error);
+    stream._reader = null;
+    this._stream = null;
+  }
+}
+
+class _ReadableStream<R = any> {
+  /**
+   * @internal
+   */
+  _state: "readable" | "closed" | "errored" = "readable";
+  /**
+   * @internal
+   */
+  _storedError: any = undefined;
+  /**
+   * @internal
+   */
+  _reader: _ReadableStreamDefaultReader<R> | null = null;
+  /**
+   * @internal
+   */
+  _disturbed: boolean = false;
+  /**
+   * @internal
+   */
+  _queue: QueueEntry<R>[] = [];
+  /**
+   * @internal
+   */
+  _closeRequested: boolean = false;
+  private _queueTotalSize: number = 0;
+  private _started: boolean = false;
+  private _pulling: boolean = false;
+  private _pullAgain: boolean = false;
+  private _source: UnderlyingDefaultSource<R>;
+  private _controller: _ReadableStreamDefaultController<R>;
+  private _highWaterMark: number;
+  private _size: (chunk: R) => number;
+
+  constructor(
+    source: UnderlyingDefaultSource<R> | UnderlyingByteSource = {},
+    strategy: QueuingStrategy<R> = {},
+  ) {
+    const isBytes = (source as UnderlyingByteSource).type === "bytes";
+    if (!isBytes && (source as any).type !== undefined) {
+      throw new RangeError(`Invalid type: ${(source as any).type}`);
+    }
+    this._source = source as UnderlyingDefaultSource<R>;
+    this._highWaterMark = extractHighWaterMark(strategy, isBytes ? 0 : 1);
+    this._size = isBytes ? (chunk: any) => chunk.byteLength : extractSize(strategy);
+    this._controller = new _ReadableStreamDefaultController(this);
+
+    const controller = this._controller as any;
+    promiseCall(() => this._source.start?.(controller)).then(
+      () => {
+        this._started = true;
+        this._callPullIfNeeded();
+      },
+      (e) => this._error(e),
+    );
+  }
+
+  get locked(): boolean {
+    return this._reader !== null;
+  }
+
+  getReader(options?: { mode?: "byob" }): _ReadableStreamDefaultReader<R> {
+    if (options?.mode === "byob") {
+      throw new TypeError("BYOB readers are not supported");
+    }
+    return new _ReadableStreamDefaultReader(this);
+  }
+
+  cancel(reason?: any): Promise<void> {
+    if (this.locked) {
+      return Promise.reject(new TypeError("Cannot cancel a locked stream"));
+    }
+    return this._cancel(reason);
+  }
+
+  tee(): [_ReadableStream<R>, _ReadableStream<R>] {
+    const reader = this.getReader();
+    let canceled = 0;
+    const reasons: any[] = [];
+    const cancelled = deferred<void>();
+    const branches: _ReadableStream<R>[] = [];
+
+    const pull = (): Promise<void> =>
+      reader.read().then(({ done, value }) => {
+        branches.forEach((branch) => {
+          if (branch._state !== "readable" || branch._closeRequested) return;
+          if (done) branch._close();
+          else branch._enqueue(value as R);
+        });
+      });
+
+    const cancel = (index: number) => (reason: any) => {
+      reasons[index] = reason;
+      if (++canceled === 2) {
+        reader.cancel(reasons).then(() => cancelled.resolve());
+      }
+      return cancelled.promise;
+    };
+
+    for (let i = 0; i < 2; i++) {
+      branches.push(new _ReadableStream<R>({ pull, cancel: cancel(i) }));
+    }
+    reader.closed.catch((e) => branches.forEach((branch) => branch._error(e)));
+    return [branches[0], branches[1]];
+  }
+
+  async pipeTo(destination: _WritableStream<R>, options: StreamPipeOptions = {}): Promise<void> {
+    const reader = this.getReader();
+    const writer = destination.getWriter();
+    const signal = options.signal;
+
+    try {
+      while (true) {
+        if (signal?.aborted) {
+          throw signal.reason;
+        }
+        await writer.ready;
+        const { done, value } = await reader.read().catch((e) => {
+          if (!options.preventAbort) writer.abort(e);
+          throw e;
+        });
+        if (done) {
+          if (!options.preventClose) await writer.close();
+          return;
+        }
+        await writer.write(value as R).catch((e) => {
+          if (!options.preventCancel) reader.cancel(e);
+          throw e;
+        });
+      }
+    } catch (e) {
+      const aborted = signal?.aborted && e === signal.reason;
+      if ((aborted || destination._state === "errored") && !options.preventCancel) {
+        await reader.cancel(e).catch(() => {});
+      }
+      if (aborted && !options.preventAbort) {
+        await writer.abort(e).catch(() => {});
+      }
+      throw e;
+    } finally {
+      reader.releaseLock();
+      writer.releaseLock();
+    }
+  }
+
+  pipeThrough<T>(
+    transform: { writable: _WritableStream<R>; readable: _ReadableStream<T> },
+    options?: StreamPipeOptions,
+  ): _ReadableStream<T> {
+    this.pipeTo(transform.writable, options).catch(() => {});
+    return transform.readable;
+  }
+
+  async *values(options?: { preventCancel?: boolean }): AsyncGenerator<R, void, undefined> {
+    const reader = this.getReader();
+    let finished = false;
+    try {
+      while (true) {
+        const { done, value } = await reader.read();
+        if (done) {
+          finished = true;
+          return;
+        }
+        yield value as R;
+      }
+    } catch (e) {
+      finished = true;
+      throw e;
+    } finally {
+      if (!finished && !options?.preventCancel) {
+        await reader.cancel().catch(() => {});
+      }
+      reader.releaseLock();
+    }
+  }
+
+  [Symbol.asyncIterator](options?: { preventCancel?: boolean }): AsyncGenerator<R, void, undefined> {
+    return this.values(options);
+  }
+
+  static from<R>(source: Iterable<R> | AsyncIterable<R>): _ReadableStream<R> {
+    const iterator =
+      typeof (source as any)[Symbol.asyncIterator] === "function"
+        ? (source as AsyncIterable<R>)[Symbol.asyncIterator]()
+        : (source as Iterable<R>)[Symbol.iterator]();
+
+    return new _ReadableStream<R>(
+      {
+        pull(controller) {
+          return Promise.resolve(iterator.next()).then((result) => {
+            if (result.done) controller.close();
+            else controller.enqueue(result.value);
+          });
+        },
+        cancel(reason) {
+          return Promise.resolve(iterator.return?.(reason)).then(() => {});
+        },
+      },
+      { highWaterMark: 0 },
+    );
+  }
+
+  /**
+   * @internal
+   */
+  _desiredSize(): number | null {
+    if (this._state === "errored") return null;
+    if (this._state === "closed") return 0;
+    return this._highWaterMark - this._queueTotalSize;
+  }
+
+  /**
+   * @internal
+   */
+  _enqueue(chunk: R): void {
+    const request = this._reader?._readRequests.shift();
+    if (request) {
+      request.resolve({ done: false, value: chunk });
+    } else {
+      let size: number;
+      try {
+        size = this._size(chunk);
+      } catch (e) {
+        this._error(e);
+        throw e;
+      }
+      this._queue.push({ value: chunk, size });
+      this._queueTotalSize += size;
+    }
+    this._callPullIfNeeded();
+  }
+
+  /**
+   * @internal
+   */
+  _dequeue(): R {
+    const entry = this._queue.shift()!;
+    this._queueTotalSize = Math.max(0, this._queueTotalSize - entry.size);
+    if (this._closeRequested && this._queue.length === 0) {
+      this._finishClose();
+    } else {
+      this._callPullIfNeeded();
+    }
+    return entry.value;
+  }
+
+  /**
+   * @internal
+   */
+  _close(): void {
+    this._closeRequested = true;
+    if (this._queue.length === 0) {
+      this._finishClose();
+    }
+  }
+
+  private _finishClose(): void {
+    if (this._state !== "readable") return;
+    this._state = "closed";
+    const reader = this._reader;
+    if (reader) {
+      reader._readRequests.splice(0).forEach((request) => request.resolve({ done: true, value: undefined }));
+      reader._closed.resolve(undefined);
+    }
+  }
+
+  /**
+   * @internal
+   */
+  _error(reason: any): void {
+    if (this._state !== "readable") return;
+    this._state = "errored";
+    this._storedError = reason;
+    this._queue = [];
+    this._queueTotalSize = 0;
+    const reader = this._reader;
+    if (reader) {
+      reader._readRequests.splice(0).forEach((request) => request.reject(reason));
+      reader._closed.reject(reason);
+    }
+  }
+
+  /**
+   * @internal
+   */
+  _cancel(reason: any): Promise<void> {
+    this._disturbed = true;
+    if (this._state === "closed") return Promise.resolve();
+    if (this._state === "errored") return Promise.reject(this._storedError);
+    this._queue = [];
+    this._queueTotalSize = 0;
+    this._finishClose();
+    return promiseCall(() => this._source.cancel?.(reason)).then(() => {});
+  }
+
+  /**
+   * @internal
+   */
+  _callPullIfNeeded(): void {
+    if (!this._shouldPull()) return;
+    if (this._pulling) {
+      this._pullAgain = true;
+      return;
+    }
+    this._pulling = true;
+    promiseCall(() => this._source.pull?.(this._controller as any)).then(
+      () => {
+        this._pulling = false;
+        if (this._pullAgain) {
+          this._pullAgain = false;
+          this._callPullIfNeeded();
+        }
+      },
+      (e) => this._error(e),
+    );
+  }
+
+  private _shouldPull(): boolean {
+    if (!this._started || this._closeRequested || this._state !== "readable") return false;
+    if (this._reader && this._reader._readRequests.length > 0) return true;
+    return this._desiredSize()! > 0;
+  }
+}
+
+interface WriteRecord<W> {
+  chunk?: W;
+  close?: boolean;
+  size: number;
+  done: Deferred<void>;
+}
+
+class _WritableStreamDefaultController {
+  /**
+   * @internal
+   */
+  _stream: _WritableStream<any>;
+  /**
+   * @internal
+   */
+  _abortController: AbortController = new AbortController();
+
+  constructor(stream: _WritableStream<any>) {
+    this._stream = stream;
+  }
+
+  get signal(): AbortSignal {
+    return this._abortController.signal;
+  }
+
+  error(reason?: any): void {
+    this._stream._error(reason);
+  }
+}
+
+class _WritableStreamDefaultWriter<W> {
+  /**
+   * @internal
+   */
+  _stream: _WritableStream<W> | null;
+  /**
+   * @internal
+   */
+  _ready: Deferred<undefined> = handled(deferred<undefined>());
+  /**
+   * @internal
+   */
+  _closed: Deferred<undefined> = handled(deferred<undefined>());
+
+  constructor(stream: _WritableStream<W>) {
+    if (stream._writer) {
+      throw new TypeError("WritableStream is already locked to a writer");
+    }
+    this._stream = stream;
+    stream._writer = this;
+    if (stream._state === "errored") {
+      this._ready.reject(stream._storedError);
+      this._closed.reject(stream._storedError);
+    } else {
+      if (stream._state === "closed") this._closed.resolve(undefined);
+      if (!stream._backpressure()) this._ready.resolve(undefined);
+    }
+  }
+
+  get closed(): Promise<undefined> {
+    return this._closed.promise;
+  }
+
+  get ready(): Promise<undefined> {
+    return this._ready.promise;
+  }
+
+  get desiredSize(): number | null {
+    if (!this._stream) throw new TypeError("The writer has been released");
+    return this._stream._desiredSize();
+  }
+
+  write(chunk: W): Promise<void> {
+    if (!this._stream) return Promise.reject(new TypeError("The writer has been released"));
+    return this._stream._write(chunk);
+  }
+
+  close(): Promise<void> {
+    if (!this._stream) return Promise.reject(new TypeError("The writer has been released"));
+    return this._stream._close();
+  }
+
+  abort(reason?: any): Promise<void> {
+    if (!this._stream) return Promise.reject(new TypeError("The writer has been released"));
+    return this._stream._abort(reason);
+  }
+
+  releaseLock(): void {
+    const stream = this._stream;
+    if (!stream) return;
+    const error = new TypeError("The writer has been released");
+    if (this._ready.settled) this._ready = handled(deferred<undefined>());
+    this._ready.reject(error);
+    if (this._closed.settled) this._closed = handled(deferred<undefined>());
+    this._closed.reject(error);
+    stream._writer = null;
+    this._stream = null;
+  }
+
+  /**
+   * @internal
+   */
+  _updateReady(backpressure: boolean): void {
+    if (backpressure && this._ready.settled) {
+      this._ready = handled(deferred<undefined>());
+    } else if (!backpressure && !this._ready.settled) {
+      this._ready.resolve(undefined);
+    }
+  }
+}
+
+class _WritableStream<W = any> {
+  /**
+   * @internal
+   */
+  _state: "writable" | "closed" | "errored" = "writable";
+  /**
+   * @internal
+   */
+  _storedError: any = undefined;
+  /**
+   * @internal
+   */
+  _writer: _WritableStreamDefaultWriter<W> | null = null;
+  private _queue: WriteRecord<W>[] = [];
+  private _queueTotalSize: number = 0;
+  private _closeRequested: boolean = false;
+  private _started: boolean = false;
+  private _inFlight: boolean = false;
+  private _sink: UnderlyingSink<W>;
+  private _controller: _WritableStreamDefaultController;
+  private _highWaterMark: number;
+  private _size: (chunk: W) => number;
+
+  constructor(sink: UnderlyingSink<W> = {}, strategy: QueuingStrategy<W> = {}) {
+    this._sink = sink;
+    this._highWaterMark = extractHighWaterMark(strategy, 1);
+    this._size = extractSize(strategy);
+    this._controller = new _WritableStreamDefaultController(this);
+
+    promiseCall(() => this._sink.start?.(this._controller as any)).then(
+      () => {
+        this._started = true;
+        this._advance();
+      },
+      (e) => this._error(e),
+    );
+  }
+
+  get locked(): boolean {
+    return this._writer !== null;
+  }
+
+  getWriter(): _WritableStreamDefaultWriter<W> {
+    return new _WritableStreamDefaultWriter(this);
+  }
+
+  close(): Promise<void> {
+    if (this.locked) return Promise.reject(new TypeError("Cannot close a locked stream"));
+    return this._close();
+  }
+
+  abort(reason?: any): Promise<void> {
+    if (this.locked) return Promise.reject(new TypeError("Cannot abort a locked stream"));
+    return this._abort(reason);
+  }
+
+  /**
+   * @internal
+   */
+  _desiredSize(): number | null {
+    if (this._state === "errored") return null;
+    if (this._state === "closed") return 0;
+    return this._highWaterMark - this._queueTotalSize;
+  }
+
+  /**
+   * @internal
+   */
+  _backpressure(): boolean {
+    return this._state === "writable" && !this._closeRequested && this._desiredSize()! <= 0;
+  }
+
+  /**
+   * @internal
+   */
+  _write(chunk: W): Promise<void> {
+    if (this._state === "errored") return Promise.reject(this._storedError);
+    if (this._state === "closed" || this._closeRequested) {
+      return Promise.reject(new TypeError("Cannot write to a closing or closed stream"));
+    }
+    let size: number;
+    try {
+      size = this._size(chunk);
+    } catch (e) {
+      this._error(e);
+      return Promise.reject(e);
+    }
+    const done = deferred<void>();
+    this._queue.push({ chunk, size, done });
+    this._queueTotalSize += size;
+    this._writer?._updateReady(this._backpressure());
+    this._advance();
+    return done.promise;
+  }
+
+  /**
+   * @internal
+   */
+  _close(): Promise<void> {
+    if (this._state !== "writable" || this._closeRequested) {
+      return Promise.reject(new TypeError("The stream is already closing or closed"));
+    }
+    this._closeRequested = true;
+    const done = deferred<void>();
+    this._queue.push({ close: true, size: 0, done });
+    this._writer?._updateReady(false);
+    this._advance();
+    return done.promise;
+  }
+
+  /**
+   * @internal
+   */
+  _abort(reason: any): Promise<void> {
+    if (this._state !== "writable") return Promise.resolve();
+    this._controller._abortController.abort(reason);
+    this._error(reason);
+    return promiseCall(() => this._sink.abort?.(reason)).then(() => {});
+  }
+
+  /**
+   * @internal
+   */
+  _error(reason: any): void {
+    if (this._state !== "writable") return;
+    this._state = "errored";
+    this._storedError = reason;
+    // The write in flight settles on its own when the sink is done with it
+    const pending = this._inFlight ? this._queue.slice(1) : this._queue;
+    pending.forEach((record) => record.done.reject(reason));
+    this._queue = this._inFlight ? this._queue.slice(0, 1) : [];
+    this._queueTotalSize = 0;
+    const writer = this._writer;
+    if (writer) {
+      if (writer._ready.settled) writer._ready = handled(deferred<undefined>());
+      writer._ready.reject(reason);
+      writer._closed.reject(reason);
+    }
+  }
+
+  private _advance(): void {
+    if (!this._started || this._inFlight || this._state !== "writable") return;
+    const record = this._queue[0];
+    if (!record) return;
+    this._inFlight = true;
+
+    const finish = () => {
+      this._queue.shift();
+      this._inFlight = false;
+    };
+
+    if (record.close) {
+      promiseCall(() => this._sink.close?.()).then(
+        () => {
+          finish();
+          this._state = "closed";
+          record.done.resolve();
+          this._writer?._closed.resolve(undefined);
+        },
+        (e) => {
+          finish();
+          record.done.reject(e);
+          this._error(e);
+        },
+      );
+      return;
+    }
+
+    promiseCall(() => this._sink.write?.(record.chunk as W, this._controller as any)).then(
+      () => {
+        finish();
+        this._queueTotalSize = Math.max(0, this._queueTotalSize - record.size);
+        record.done.resolve();
+        this._writer?._updateReady(this._backpressure());
+        this._advance();
+      },
+      (e) => {
+        finish();
+        record.done.reject(e);
+        this._error(e);
+      },
+    );
+  }
+}
+
+class _TransformStream<I = any, O = any> {
+  readonly readable: _ReadableStream<O>;
+  readonly writable: _WritableStream<I>;
+
+  constructor(
+    transformer: Transformer<I, O> = {},
+    writableStrategy: QueuingStrategy<I> = {},
+    readableStrategy: QueuingStrategy<O> = {},
+  ) {
+    let readableController!: _ReadableStreamDefaultController<O>;
+    // Set while the readable side is full; writes wait for the next pull
+    let pullWaiter: Deferred<void> | null = null;
+
+    const controller = {
+      get desiredSize() {
+        return readableController.desiredSize;
+      },
+      enqueue(chunk: O) {
+        readableController.enqueue(chunk);
+      },
+      error(reason?: any) {
+        readableController.error(reason);
+        writable._error(reason);
+      },
+      terminate() {
+        if (readable._state === "readable" && !readable._closeRequested) {
+          readableController.close();
+        }
+        writable._error(new TypeError("The TransformStream was terminated"));
+      },
+    };
+
+    const readable = new _ReadableStream<O>(
+      {
+        start(c) {
+          readableController = c as any;
+        },
+        pull() {
+          pullWaiter?.resolve();
+          pullWaiter = null;
+        },
+        cancel(reason) {
+          writable._error(reason);
+          return transformer.cancel?.(reason);
+        },
+      },
+      { highWaterMark: 0, ...readableStrategy },
+    );
+
+    const writable = new _WritableStream<I>(
+      {
+        start() {
+          return transformer.start?.(controller as any);
+        },
+        write(chunk) {
+          let wait: Promise<void> | undefined;
+          if (readableController.desiredSize! <= 0) {
+            pullWaiter = pullWaiter ?? deferred<void>();
+            wait = pullWaiter.promise;
+          }
+          return Promise.resolve(wait).then(() =>
+            transformer.transform
+              ? transformer.transform(chunk, controller as any)
+              : controller.enqueue(chunk as any),
+          );
+        },
+        close() {
+          return promiseCall(() => transformer.flush?.(controller as any)).then(() => {
+            if (readable._state === "readable" && !readable._closeRequested) {
+              readableController.close();
+            }
+          });
+        },
+        abort(reason) {
+          readableController.error(reason);
+          return transformer.cancel?.(reason);
+        },
+      },
+      writableStrategy,
+    );
+
+    this.readable = readable;
+    this.writable = writable;
+  }
+}
+
+class _TextDecoderStream extends _TransformStream<AllowSharedBufferSource, string> {
+  readonly encoding: string;
+  readonly fatal: boolean;
+  readonly ignoreBOM: boolean;
+
+  constructor(label: string = "utf-8", options: TextDecoderOptions = {}) {
+    // Multibyte sequences split across chunks wait in the decoder
+    const decoder = new TextDecoder(label, options);
+
+    super({
+      transform(chunk, controller) {
+        const text = decoder.decode(chunk, { stream: true });
+        if (text) controller.enqueue(text);
+      },
+      flush(controller) {
+        const text = decoder.decode();
+        if (text) controller.enqueue(text);
+      },
+    });
+
+    this.encoding = decoder.encoding;
+    this.fatal = decoder.fatal;
+    this.ignoreBOM = decoder.ignoreBOM;
+  }
+}
+
+class _TextEncoderStream extends _TransformStream<string, Uint8Array> {
+  readonly encoding: string = "utf-8";
+
+  constructor() {
+    const encoder = new TextEncoder();
+    // A high surrogate at the end of a chunk waits for its pair
+    let pendingSurrogate = "";
+
+    super({
+      transform(chunk, controller) {
+        let text = pendingSurrogate + String(chunk);
+        pendingSurrogate = "";
+        const last = text.charCodeAt(text.length - 1);
+        if (last >= 0xd800 && last <= 0xdbff) {
+          pendingSurrogate = text.slice(-1);
+          text = text.slice(0, -1);
+        }
+        if (text) controller.enqueue(encoder.encode(text));
+      },
+      flush(controller) {
+        if (pendingSurrogate) {
+          controller.enqueue(new Uint8Array([0xef, 0xbf, 0xbd]));
+        }
+      },
+    });
+  }
+}
+
+globalThis.ReadableStream = _ReadableStream as any;
+globalThis.ReadableStreamDefaultReader = _ReadableStreamDefaultReader as any;
+globalThis.ReadableStreamDefaultController = _ReadableStreamDefaultController as any;
+globalThis.WritableStream = _WritableStream as any;
+globalThis.WritableStreamDefaultWriter = _WritableStreamDefaultWriter as any;
+globalThis.WritableStreamDefaultController = _WritableStreamDefaultController as any;
+globalThis.TransformStream = _TransformStream as any;
+globalThis.CountQueuingStrategy = _CountQueuingStrategy as any;
+globalThis.ByteLengthQueuingStrategy = _ByteLengthQueuingStrategy as any;
+globalThis.TextDecoderStream = _TextDecoderStream as any;
+globalThis.TextEncoderStream = _TextEncoderStream as any;
+
+export {};
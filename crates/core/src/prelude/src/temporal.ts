@@ -0,0 +1,1418 @@
+/**
+ * A subset of Temporal (https://tc39.es/proposal-temporal/) for the ISO 8601
+ * calendar, using the tz database compiled into the engine. Rounding options
+ * and other calendars are not supported.
+ */
+
+interface IsoDate {
+  year: number;
+  month: number;
+  day: number;
+}
+
+interface IsoTime {
+  hour: number;
+  minute: number;
+  second: number;
+  millisecond: number;
+  microsecond: number;
+  nanosecond: number;
+}
+
+type Overflow = "constrain" | "reject";
+type Disambiguation = "compatible" | "earlier" | "later" | "reject";
+type DurationLike = _Duration | string | Record<string, unknown>;
+
+const DAY_NS = 86400000000000n;
+const MS_NS = 1000000n;
+const MAX_EPOCH_NS = 8640000000000000000000n;
+
+const DATE_UNITS = ["year", "month", "week", "day"];
+const TIME_UNITS = ["hour", "minute", "second", "millisecond", "microsecond", "nanosecond"];
+const UNIT_NS: [string, bigint][] = [
+  ["day", DAY_NS],
+  ["hour", 3600000000000n],
+  ["minute", 60000000000n],
+  ["second", 1000000000n],
+  ["millisecond", MS_NS],
+  ["microsecond", 1000n],
+  ["nanosecond", 1n],
+];
+const DURATION_FIELDS = [
+  "years",
+  "months",
+  "weeks",
+  "days",
+  "hours",
+  "minutes",
+  "seconds",
+  "milliseconds",
+  "microseconds",
+  "nanoseconds",
+] as const;
+
+function floorDiv(a: bigint, b: bigint): bigint {
+  const quotient = a / b;
+  return a % b !== 0n && (a < 0n) !== (b < 0n) ? quotient - 1n : quotient;
+}
+
+function floorMod(a: bigint, b: bigint): bigint {
+  return a - floorDiv(a, b) * b;
+}
+
+function sign(value: bigint | number): number {
+  return value > 0 ? 1 : value < 0 ? -1 : 0;
+}
+
+function toInteger(value: unknown, name: string): number {
+  const number = Number(value);
+  if (!isFinite(number)) {
+    throw new RangeError(`${name} must be a finite number`);
+  }
+  return Math.trunc(number);
+}
+
+function options(value: unknown): Record<string, any> {
+  if (value === undefined) return {};
+  if (typeof value !== "object" || value === null) {
+    throw new TypeError("Options must be an object");
+  }
+  return value as Record<string, any>;
+}
+
+function getOption<T extends string>(o: Record<string, any>, name: string, allowed: readonly T[], fallback: T): T {
+  const value = o[name];
+  if (value === undefined) return fallback;
+  if (!allowed.includes(String(value) as T)) {
+    throw new RangeError(`Invalid value for ${name}: ${value}`);
+  }
+  return String(value) as T;
+}
+
+function overflowOption(value: unknown): Overflow {
+  return getOption(options(value), "overflow", ["constrain", "reject"] as const, "constrain");
+}
+
+function disambiguationOption(o: Record<string, any>): Disambiguation {
+  return getOption(o, "disambiguation", ["compatible", "earlier", "later", "reject"] as const, "compatible");
+}
+
+/** Reads `largestUnit`, accepting plurals such as "hours". */
+function largestUnitOption(value: unknown, allowed: string[], fallback: string): string {
+  const o = options(value);
+  if (o.smallestUnit !== undefined && String(o.smallestUnit).replace(/s$/, "") !== "nanosecond") {
+    throw new RangeError("Rounding to a smallestUnit is not supported");
+  }
+  if (o.largestUnit === undefined || o.largestUnit === "auto") return fallback;
+  const unit = String(o.largestUnit).replace(/s$/, "");
+  if (!allowed.includes(unit)) {
+    throw new RangeError(`Invalid largestUnit: ${o.largestUnit}`);
+  }
+  return unit;
+}
+
+function timeZoneId(value: unknown): string {
+  if (typeof value !== "string") {
+    throw new TypeError("Time zone must be a string");
+  }
+  return __canonicalizeTimeZone(value);
+}
+
+// ISO 8601 dates, counted in days from the Unix epoch
+
+function isLeapYear(year: number): boolean {
+  return (year % 4 === 0 && year % 100 !== 0) || year % 400 === 0;
+}
+
+function daysInMonth(year: number, month: number): number {
+  return [31, isLeapYear(year) ? 29 : 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31][month - 1];
+}
+
+function epochDays({ year, month, day }: IsoDate): number {
+  const y = month <= 2 ? year - 1 : year;
+  const era = Math.floor(y / 400);
+  const yearOfEra = y - era * 400;
+  const dayOfYear = Math.floor((153 * ((month + 9) % 12) + 2) / 5) + day - 1;
+  const dayOfEra = yearOfEra * 365 + Math.floor(yearOfEra / 4) - Math.floor(yearOfEra / 100) + dayOfYear;
+  return era * 146097 + dayOfEra - 719468;
+}
+
+function fromEpochDays(days: number): IsoDate {
+  const z = days + 719468;
+  const era = Math.floor(z / 146097);
+  const dayOfEra = z - era * 146097;
+  const yearOfEra = Math.floor(
+    (dayOfEra - Math.floor(dayOfEra / 1460) + Math.floor(dayOfEra / 36524) - Math.floor(dayOfEra / 146096)) / 365,
+  );
+  const dayOfYear = dayOfEra - (365 * yearOfEra + Math.floor(yearOfEra / 4) - Math.floor(yearOfEra / 100));
+  const mp = Math.floor((5 * dayOfYear + 2) / 153);
+  const month = mp < 10 ? mp + 3 : mp - 9;
+  return {
+    year: yearOfEra + era * 400 + (month <= 2 ? 1 : 0),
+    month,
+    day: dayOfYear - Math.floor((153 * mp + 2) / 5) + 1,
+  };
+}
+
+function compareDate(a: IsoDate, b: IsoDate): number {
+  return sign(a.year - b.year) || sign(a.month - b.month) || sign(a.day - b.day);
+}
+
+function regulateDate(year: number, month: number, day: number, overflow: Overflow): IsoDate {
+  if (overflow === "reject") {
+    if (month < 1 || month > 12 || day < 1 || day > daysInMonth(year, month)) {
+      throw new RangeError(`Invalid date: ${year}-${month}-${day}`);
+    }
+    return { year, month, day };
+  }
+  month = Math.min(Math.max(month, 1), 12);
+  return { year, month, day: Math.min(Math.max(day, 1), daysInMonth(year, month)) };
+}
+
+function addDate(date: IsoDate, years: number, months: number, days: number, overflow: Overflow): IsoDate {
+  const monthIndex = date.month - 1 + months;
+  const year = date.year + years + Math.floor(monthIndex / 12);
+  const month = (((monthIndex % 12) + 12) % 12) + 1;
+  const regulated = regulateDate(year, month, date.day, overflow);
+  return days === 0 ? regulated : fromEpochDays(epochDays(regulated) + days);
+}
+
+/** The years, months, weeks and days from `a` to `b`. */
+function differenceDate(a: IsoDate, b: IsoDate, largestUnit: string): [number, number, number, number] {
+  const direction = compareDate(b, a);
+  if (direction === 0) return [0, 0, 0, 0];
+
+  if (largestUnit === "year" || largestUnit === "month") {
+    let months = (b.year - a.year) * 12 + (b.month - a.month);
+    let middle = addDate(a, 0, months, 0, "constrain");
+    if (compareDate(middle, b) === direction) {
+      months -= direction;
+      middle = addDate(a, 0, months, 0, "constrain");
+    }
+    const days = epochDays(b) - epochDays(middle);
+    const years = largestUnit === "year" ? Math.trunc(months / 12) : 0;
+    return [years, months - years * 12, 0, days];
+  }
+
+  const days = epochDays(b) - epochDays(a);
+  const weeks = largestUnit === "week" ? Math.trunc(days / 7) : 0;
+  return [0, 0, weeks, days - weeks * 7];
+}
+
+// Times of day, counted in nanoseconds since midnight
+
+function timeNs(time: IsoTime): number {
+  return (
+    ((time.hour * 60 + time.minute) * 60 + time.second) * 1e9 +
+    time.millisecond * 1e6 +
+    time.microsecond * 1e3 +
+    time.nanosecond
+  );
+}
+
+function fromTimeNs(ns: number): IsoTime {
+  return {
+    hour: Math.floor(ns / 3.6e12),
+    minute: Math.floor(ns / 6e10) % 60,
+    second: Math.floor(ns / 1e9) % 60,
+    millisecond: Math.floor(ns / 1e6) % 1000,
+    microsecond: Math.floor(ns / 1e3) % 1000,
+    nanosecond: ns % 1000,
+  };
+}
+
+const TIME_LIMITS: [keyof IsoTime, number][] = [
+  ["hour", 23],
+  ["minute", 59],
+  ["second", 59],
+  ["millisecond", 999],
+  ["microsecond", 999],
+  ["nanosecond", 999],
+];
+
+function regulateTime(time: IsoTime, overflow: Overflow): IsoTime {
+  const regulated = { ...time };
+  for (const [field, maximum] of TIME_LIMITS) {
+    const value = time[field];
+    if (value < 0 || value > maximum) {
+      if (overflow === "reject") {
+        throw new RangeError(`${field} must be between 0 and ${maximum}`);
+      }
+      regulated[field] = Math.min(Math.max(value, 0), maximum);
+    }
+  }
+  return regulated;
+}
+
+const MIDNIGHT: IsoTime = { hour: 0, minute: 0, second: 0, millisecond: 0, microsecond: 0, nanosecond: 0 };
+
+/**
+ * Splits `ns` into days (when `largestUnit` is "day") and time units, each
+ * with the sign of `ns`.
+ */
+function balanceTime(ns: bigint, largestUnit: string): number[] {
+  const fields: number[] = [];
+  let started = false;
+  for (const [unit, size] of UNIT_NS) {
+    started ||= unit === largestUnit;
+    const quotient = started ? ns / size : 0n;
+    ns -= quotient * size;
+    fields.push(Number(quotient));
+  }
+  return fields;
+}
+
+// Time zones
+
+function offsetSeconds(timeZone: string, epochNs: bigint): number {
+  return __tz.offset(timeZone, Number(floorDiv(epochNs, MS_NS)));
+}
+
+/** The instant a wall-clock time in `timeZone` refers to. */
+function epochNsFromWallClock(timeZone: string, wallNs: bigint, disambiguation: Disambiguation): bigint {
+  const wallMs = Number(floorDiv(wallNs, MS_NS));
+  const candidates = __tz
+    .possibleOffsets(timeZone, wallMs)
+    .map((offset) => wallNs - BigInt(offset) * 1000000000n)
+    .sort((a, b) => sign(a - b));
+
+  if (candidates.length === 1) return candidates[0];
+  if (disambiguation === "reject") {
+    throw new RangeError(`The wall-clock time is ${candidates.length ? "ambiguous" : "skipped"} in ${timeZone}`);
+  }
+  if (candidates.length === 2) {
+    return disambiguation === "later" ? candidates[1] : candidates[0];
+  }
+  // in a gap, move by the gap's length using the offset from before or after it
+  const day = Number(DAY_NS / MS_NS);
+  const offset = __tz.offset(timeZone, disambiguation === "earlier" ? wallMs + day : wallMs - day);
+  return wallNs - BigInt(offset) * 1000000000n;
+}
+
+function wallClock(epochNs: bigint, timeZone: string): [IsoDate, IsoTime, number] {
+  const offset = offsetSeconds(timeZone, epochNs);
+  const wallNs = epochNs + BigInt(offset) * 1000000000n;
+  return [fromEpochDays(Number(floorDiv(wallNs, DAY_NS))), fromTimeNs(Number(floorMod(wallNs, DAY_NS))), offset];
+}
+
+function wallNs(date: IsoDate, time: IsoTime): bigint {
+  return BigInt(epochDays(date)) * DAY_NS + BigInt(timeNs(time));
+}
+
+function checkEpochNs(epochNs: bigint): bigint {
+  if (epochNs > MAX_EPOCH_NS || epochNs < -MAX_EPOCH_NS) {
+    throw new RangeError("Instant is out of range");
+  }
+  return epochNs;
+}
+
+// ISO 8601 strings
+
+const DATE_TIME_PATTERN =
+  /^([+-]\d{6}|\d{4})-?(\d{2})-?(\d{2})(?:[T ](\d{2})(?::?(\d{2})(?::?(\d{2})(?:[.,](\d{1,9}))?)?)?)?(Z|[+-]\d{2}(?::?\d{2}(?::?\d{2})?)?)?(?:\[!?([^\]=]+)\])?(?:\[!?u-ca=([^\]]+)\])?$/i;
+const TIME_PATTERN = /^T?(\d{2})(?::?(\d{2})(?::?(\d{2})(?:[.,](\d{1,9}))?)?)?$/i;
+const OFFSET_PATTERN = /^([+-])(\d{2})(?::?(\d{2})(?::?(\d{2}))?)?$/;
+const DURATION_PATTERN =
+  /^([+-])?P(?:(\d+)Y)?(?:(\d+)M)?(?:(\d+)W)?(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+)(?:[.,](\d{1,9}))?S)?)?$/i;
+
+interface ParsedDateTime {
+  date: IsoDate;
+  time: IsoTime | undefined;
+  z: boolean;
+  offset: string | undefined;
+  timeZone: string | undefined;
+}
+
+function parseTime(hour: string, minute = "0", second = "0", fraction = ""): IsoTime {
+  const digits = fraction.padEnd(9, "0");
+  return regulateTime(
+    {
+      hour: Number(hour),
+      minute: Number(minute),
+      // leap seconds are read as the last second of the minute
+      second: Math.min(Number(second), 59),
+      millisecond: Number(digits.slice(0, 3)),
+      microsecond: Number(digits.slice(3, 6)),
+      nanosecond: Number(digits.slice(6, 9)),
+    },
+    "reject",
+  );
+}
+
+function parseDateTime(text: string): ParsedDateTime {
+  const match = DATE_TIME_PATTERN.exec(text);
+  if (!match || match[1] === "-000000") {
+    throw new RangeError(`Invalid ISO 8601 string: ${text}`);
+  }
+  const [, year, month, day, hour, minute, second, fraction, offset, timeZone, calendar] = match;
+  if (calendar !== undefined && calendar.toLowerCase() !== "iso8601") {
+    throw new RangeError(`Unsupported calendar: ${calendar}`);
+  }
+  return {
+    date: regulateDate(Number(year), Number(month), Number(day), "reject"),
+    time: hour === undefined ? undefined : parseTime(hour, minute, second, fraction),
+    z: offset?.toUpperCase() === "Z",
+    offset: offset?.toUpperCase() === "Z" ? undefined : offset,
+    timeZone,
+  };
+}
+
+/** Parses "+05:30" into seconds east of UTC. */
+function parseOffset(text: string): number {
+  const match = OFFSET_PATTERN.exec(text);
+  if (!match) {
+    throw new RangeError(`Invalid UTC offset: ${text}`);
+  }
+  const [, sign, hours, minutes = "0", seconds = "0"] = match;
+  const offset = Number(hours) * 3600 + Number(minutes) * 60 + Number(seconds);
+  return sign === "-" ? -offset : offset;
+}
+
+function parsePlainDateTime(text: string): ParsedDateTime {
+  const parsed = parseDateTime(String(text));
+  if (parsed.z) {
+    throw new RangeError(`A UTC designator isn't allowed in a plain date or time: ${text}`);
+  }
+  return parsed;
+}
+
+// Formatting
+
+function pad(value: number, length = 2): string {
+  return String(value).padStart(length, "0");
+}
+
+function formatDate({ year, month, day }: IsoDate): string {
+  const yearString = year >= 0 && year <= 9999 ? pad(year, 4) : `${year < 0 ? "-" : "+"}${pad(Math.abs(year), 6)}`;
+  return `${yearString}-${pad(month)}-${pad(day)}`;
+}
+
+function formatFraction(ns: number | bigint): string {
+  return ns === 0 || ns === 0n ? "" : `.${String(ns).padStart(9, "0").replace(/0+$/, "")}`;
+}
+
+function formatTime(time: IsoTime): string {
+  const subsecond = time.millisecond * 1e6 + time.microsecond * 1e3 + time.nanosecond;
+  return `${pad(time.hour)}:${pad(time.minute)}:${pad(time.second)}${formatFraction(subsecond)}`;
+}
+
+function formatOffset(offset: number): string {
+  const absolute = Math.abs(offset);
+  const seconds = absolute % 60;
+  const formatted = `${offset < 0 ? "-" : "+"}${pad(Math.floor(absolute / 3600))}:${pad(Math.floor(absolute / 60) % 60)}`;
+  return seconds ? `${formatted}:${pad(seconds)}` : formatted;
+}
+
+// Property bags
+
+function dateFields(item: Record<string, any>, defaults?: IsoDate): [number, number, number] {
+  let month = item.month === undefined ? undefined : toInteger(item.month, "month");
+  if (item.monthCode !== undefined) {
+    const match = /^M(\d{2})$/.exec(String(item.monthCode));
+    const code = match ? Number(match[1]) : NaN;
+    if (!(code >= 1 && code <= 12) || (month !== undefined && month !== code)) {
+      throw new RangeError(`Invalid monthCode: ${item.monthCode}`);
+    }
+    month = code;
+  }
+  const year = item.year === undefined ? defaults?.year : toInteger(item.year, "year");
+  month ??= defaults?.month;
+  const day = item.day === undefined ? defaults?.day : toInteger(item.day, "day");
+  if (year === undefined || month === undefined || day === undefined) {
+    throw new TypeError("year, month and day are required");
+  }
+  return [year, month, day];
+}
+
+function timeFields(item: Record<string, any>, defaults: IsoTime = MIDNIGHT): IsoTime {
+  const time = { ...defaults };
+  for (const [field] of TIME_LIMITS) {
+    if (item[field] !== undefined) {
+      time[field] = toInteger(item[field], field);
+    }
+  }
+  return time;
+}
+
+function hasTimeFields(item: Record<string, any>): boolean {
+  return TIME_LIMITS.some(([field]) => item[field] !== undefined);
+}
+
+function propertyBag(item: unknown): Record<string, any> {
+  if (typeof item !== "object" || item === null) {
+    throw new TypeError("Expected an object or an ISO 8601 string");
+  }
+  return item as Record<string, any>;
+}
+
+function noValueOf(): never {
+  throw new TypeError("Temporal values can't be converted to primitives, use compare() or equals()");
+}
+
+class _Duration {
+  private _fields: number[];
+
+  constructor(
+    years = 0,
+    months = 0,
+    weeks = 0,
+    days = 0,
+    hours = 0,
+    minutes = 0,
+    seconds = 0,
+    milliseconds = 0,
+    microseconds = 0,
+    nanoseconds = 0,
+  ) {
+    const fields = [years, months, weeks, days, hours, minutes, seconds, milliseconds, microseconds, nanoseconds];
+    this._fields = fields.map((value, i) => {
+      if (!Number.isInteger(Number(value))) {
+        throw new RangeError(`${DURATION_FIELDS[i]} must be an integer`);
+      }
+      return Number(value) || 0;
+    });
+    const signs = new Set(this._fields.map(sign).filter((s) => s !== 0));
+    if (signs.size > 1) {
+      throw new RangeError("Duration fields must all have the same sign");
+    }
+  }
+
+  static from(item: DurationLike): _Duration {
+    if (item instanceof _Duration) {
+      return new _Duration(...(item._fields as [number]));
+    }
+    if (typeof item === "string") {
+      const match = DURATION_PATTERN.exec(item);
+      if (!match || !/\d/.test(item) || /T$/i.test(item)) {
+        throw new RangeError(`Invalid ISO 8601 duration: ${item}`);
+      }
+      const [, sign, ...parts] = match;
+      const fraction = (parts[7] ?? "").padEnd(9, "0");
+      const fields = [
+        ...parts.slice(0, 7).map((part) => Number(part ?? 0)),
+        Number(fraction.slice(0, 3)),
+        Number(fraction.slice(3, 6)),
+        Number(fraction.slice(6, 9)),
+      ];
+      return new _Duration(...(fields.map((field) => (sign === "-" ? -field : field)) as [number]));
+    }
+    const o = propertyBag(item);
+    if (!DURATION_FIELDS.some((field) => o[field] !== undefined)) {
+      throw new TypeError("A duration needs at least one field");
+    }
+    return new _Duration(...(DURATION_FIELDS.map((field) => toInteger(o[field] ?? 0, field)) as [number]));
+  }
+
+  static compare(one: DurationLike, two: DurationLike): number {
+    const a = _Duration.from(one);
+    const b = _Duration.from(two);
+    return sign(a._totalNs() - b._totalNs());
+  }
+
+  get years() {
+    return this._fields[0];
+  }
+  get months() {
+    return this._fields[1];
+  }
+  get weeks() {
+    return this._fields[2];
+  }
+  get days() {
+    return this._fields[3];
+  }
+  get hours() {
+    return this._fields[4];
+  }
+  get minutes() {
+    return this._fields[5];
+  }
+  get seconds() {
+    return this._fields[6];
+  }
+  get milliseconds() {
+    return this._fields[7];
+  }
+  get microseconds() {
+    return this._fields[8];
+  }
+  get nanoseconds() {
+    return this._fields[9];
+  }
+
+  get sign(): number {
+    return this._fields.map(sign).find((s) => s !== 0) ?? 0;
+  }
+
+  get blank(): boolean {
+    return this.sign === 0;
+  }
+
+  /**
+   * The hours and smaller units in nanoseconds
+   * @internal
+   */
+  _timeNs(): bigint {
+    let ns = 0n;
+    for (let i = 4; i < 10; i++) {
+      ns += BigInt(this._fields[i]) * UNIT_NS[i - 3][1];
+    }
+    return ns;
+  }
+
+  /**
+   * Days and smaller units in nanoseconds. Years, months and weeks need a
+   * date to be measured from, which isn't supported.
+   * @internal
+   */
+  _totalNs(): bigint {
+    if (this.years || this.months || this.weeks) {
+      throw new RangeError("Durations with years, months or weeks can't be totalled without a relativeTo date");
+    }
+    return BigInt(this.days) * DAY_NS + this._timeNs();
+  }
+
+  /** @internal */
+  _largestUnit(): string {
+    const index = this._fields.findIndex((value) => value !== 0);
+    return index < 0 ? "nanosecond" : DURATION_FIELDS[index].replace(/s$/, "");
+  }
+
+  with(fields: Record<string, unknown>): _Duration {
+    const o = propertyBag(fields);
+    return new _Duration(
+      ...(DURATION_FIELDS.map((field, i) =>
+        o[field] === undefined ? this._fields[i] : toInteger(o[field], field),
+      ) as [number]),
+    );
+  }
+
+  negated(): _Duration {
+    return new _Duration(...(this._fields.map((value) => -value) as [number]));
+  }
+
+  abs(): _Duration {
+    return new _Duration(...(this._fields.map(Math.abs) as [number]));
+  }
+
+  add(other: DurationLike): _Duration {
+    const duration = _Duration.from(other);
+    const largest = [this._largestUnit(), duration._largestUnit()].sort(
+      (a, b) => UNIT_NS.findIndex(([u]) => u === a) - UNIT_NS.findIndex(([u]) => u === b),
+    )[0];
+    return new _Duration(0, 0, 0, ...(balanceTime(this._totalNs() + duration._totalNs(), largest) as [number]));
+  }
+
+  subtract(other: DurationLike): _Duration {
+    return this.add(_Duration.from(other).negated());
+  }
+
+  /** The duration in `unit`, which must be days or smaller. */
+  total(unit: string | { unit: string }): number {
+    const name = String(typeof unit === "object" ? unit.unit : unit).replace(/s$/, "");
+    const size = UNIT_NS.find(([u]) => u === name)?.[1];
+    if (size === undefined) {
+      throw new RangeError(`Unsupported unit: ${name}`);
+    }
+    const ns = this._totalNs();
+    return Number(ns / size) + Number(ns % size) / Number(size);
+  }
+
+  toString(): string {
+    const [years, months, weeks, days, hours, minutes] = this._fields.map(Math.abs);
+    let seconds = 0n;
+    for (let i = 6; i < 10; i++) {
+      seconds += BigInt(Math.abs(this._fields[i])) * UNIT_NS[i - 3][1];
+    }
+    const date =
+      (years ? `${years}Y` : "") + (months ? `${months}M` : "") + (weeks ? `${weeks}W` : "") + (days ? `${days}D` : "");
+    let time = (hours ? `${hours}H` : "") + (minutes ? `${minutes}M` : "");
+    if (seconds || (!date && !time)) {
+      time += `${seconds / 1000000000n}${formatFraction(seconds % 1000000000n)}S`;
+    }
+    return `${this.sign < 0 ? "-" : ""}P${date}${time ? `T${time}` : ""}`;
+  }
+
+  toJSON(): string {
+    return this.toString();
+  }
+
+  toLocaleString(): string {
+    return this.toString();
+  }
+
+  valueOf(): never {
+    return noValueOf();
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Temporal.Duration";
+  }
+}
+
+function durationFromParts(dateParts: number[], timeParts: number[]): _Duration {
+  const [years, months, weeks, days] = dateParts;
+  const [extraDays, ...time] = timeParts;
+  return new _Duration(years, months, weeks, days + extraDays, ...(time as [number]));
+}
+
+class _PlainTime {
+  private _time: IsoTime;
+
+  constructor(hour = 0, minute = 0, second = 0, millisecond = 0, microsecond = 0, nanosecond = 0) {
+    const values = [hour, minute, second, millisecond, microsecond, nanosecond];
+    const time = { ...MIDNIGHT };
+    TIME_LIMITS.forEach(([field], i) => (time[field] = toInteger(values[i], field)));
+    this._time = regulateTime(time, "reject");
+  }
+
+  /** @internal */
+  static _create(time: IsoTime): _PlainTime {
+    const plain = Object.create(_PlainTime.prototype) as _PlainTime;
+    plain._time = time;
+    return plain;
+  }
+
+  static from(item: _PlainTime | string | Record<string, unknown>, opts?: object): _PlainTime {
+    const overflow = overflowOption(opts);
+    if (item instanceof _PlainTime) return _PlainTime._create(item._time);
+    if (typeof item === "string") {
+      const match = TIME_PATTERN.exec(item);
+      if (match) return _PlainTime._create(parseTime(match[1], match[2], match[3], match[4]));
+      const { time } = parsePlainDateTime(item);
+      if (!time) throw new RangeError(`Invalid ISO 8601 time: ${item}`);
+      return _PlainTime._create(time);
+    }
+    const o = propertyBag(item);
+    if (!hasTimeFields(o)) {
+      throw new TypeError("A time needs at least one time field");
+    }
+    return _PlainTime._create(regulateTime(timeFields(o), overflow));
+  }
+
+  static compare(one: _PlainTime | string, two: _PlainTime | string): number {
+    return sign(timeNs(_PlainTime.from(one)._time) - timeNs(_PlainTime.from(two)._time));
+  }
+
+  /** @internal */
+  get _isoTime(): IsoTime {
+    return this._time;
+  }
+
+  get hour() {
+    return this._time.hour;
+  }
+  get minute() {
+    return this._time.minute;
+  }
+  get second() {
+    return this._time.second;
+  }
+  get millisecond() {
+    return this._time.millisecond;
+  }
+  get microsecond() {
+    return this._time.microsecond;
+  }
+  get nanosecond() {
+    return this._time.nanosecond;
+  }
+
+  with(fields: Record<string, unknown>, opts?: object): _PlainTime {
+    return _PlainTime._create(regulateTime(timeFields(propertyBag(fields), this._time), overflowOption(opts)));
+  }
+
+  add(duration: DurationLike): _PlainTime {
+    const ns = BigInt(timeNs(this._time)) + _Duration.from(duration)._timeNs();
+    return _PlainTime._create(fromTimeNs(Number(floorMod(ns, DAY_NS))));
+  }
+
+  subtract(duration: DurationLike): _PlainTime {
+    return this.add(_Duration.from(duration).negated());
+  }
+
+  until(other: _PlainTime | string, opts?: object): _Duration {
+    const largest = largestUnitOption(opts, TIME_UNITS, "hour");
+    const ns = BigInt(timeNs(_PlainTime.from(other)._time) - timeNs(this._time));
+    return durationFromParts([0, 0, 0, 0], balanceTime(ns, largest));
+  }
+
+  since(other: _PlainTime | string, opts?: object): _Duration {
+    return _PlainTime.from(other).until(this, opts);
+  }
+
+  equals(other: _PlainTime | string): boolean {
+    return _PlainTime.compare(this, other) === 0;
+  }
+
+  toString(): string {
+    return formatTime(this._time);
+  }
+
+  toJSON(): string {
+    return this.toString();
+  }
+
+  valueOf(): never {
+    return noValueOf();
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Temporal.PlainTime";
+  }
+}
+
+/** The calendar fields shared by dates, date-times and zoned date-times. */
+abstract class CalendarFields {
+  /** @internal */
+  abstract get _isoDate(): IsoDate;
+
+  get calendarId(): string {
+    return "iso8601";
+  }
+  get year(): number {
+    return this._isoDate.year;
+  }
+  get month(): number {
+    return this._isoDate.month;
+  }
+  get monthCode(): string {
+    return `M${pad(this._isoDate.month)}`;
+  }
+  get day(): number {
+    return this._isoDate.day;
+  }
+  get dayOfWeek(): number {
+    return ((((epochDays(this._isoDate) + 3) % 7) + 7) % 7) + 1;
+  }
+  get dayOfYear(): number {
+    const { year } = this._isoDate;
+    return epochDays(this._isoDate) - epochDays({ year, month: 1, day: 1 }) + 1;
+  }
+  get daysInWeek(): number {
+    return 7;
+  }
+  get daysInMonth(): number {
+    return daysInMonth(this._isoDate.year, this._isoDate.month);
+  }
+  get daysInYear(): number {
+    return isLeapYear(this._isoDate.year) ? 366 : 365;
+  }
+  get monthsInYear(): number {
+    return 12;
+  }
+  get inLeapYear(): boolean {
+    return isLeapYear(this._isoDate.year);
+  }
+
+  toJSON(): string {
+    return this.toString();
+  }
+
+  valueOf(): never {
+    return noValueOf();
+  }
+}
+
+class _PlainDate extends CalendarFields {
+  private _date: IsoDate;
+
+  constructor(year: number, month: number, day: number) {
+    super();
+    this._date = regulateDate(toInteger(year, "year"), toInteger(month, "month"), toInteger(day, "day"), "reject");
+  }
+
+  /** @internal */
+  static _create(date: IsoDate): _PlainDate {
+    const plain = Object.create(_PlainDate.prototype) as _PlainDate;
+    plain._date = date;
+    return plain;
+  }
+
+  static from(item: _PlainDate | _PlainDateTime | string | Record<string, unknown>, opts?: object): _PlainDate {
+    const overflow = overflowOption(opts);
+    if (item instanceof CalendarFields) return _PlainDate._create(item._isoDate);
+    if (typeof item === "string") return _PlainDate._create(parsePlainDateTime(item).date);
+    const [year, month, day] = dateFields(propertyBag(item));
+    return _PlainDate._create(regulateDate(year, month, day, overflow));
+  }
+
+  static compare(one: _PlainDate | string, two: _PlainDate | string): number {
+    return compareDate(_PlainDate.from(one)._date, _PlainDate.from(two)._date);
+  }
+
+  /** @internal */
+  get _isoDate(): IsoDate {
+    return this._date;
+  }
+
+  with(fields: Record<string, unknown>, opts?: object): _PlainDate {
+    const [year, month, day] = dateFields(propertyBag(fields), this._date);
+    return _PlainDate._create(regulateDate(year, month, day, overflowOption(opts)));
+  }
+
+  add(duration: DurationLike, opts?: object): _PlainDate {
+    const d = _Duration.from(duration);
+    const days = d.weeks * 7 + d.days + Number(d._timeNs() / DAY_NS);
+    return _PlainDate._create(addDate(this._date, d.years, d.months, days, overflowOption(opts)));
+  }
+
+  subtract(duration: DurationLike, opts?: object): _PlainDate {
+    return this.add(_Duration.from(duration).negated(), opts);
+  }
+
+  until(other: _PlainDate | string, opts?: object): _Duration {
+    const largest = largestUnitOption(opts, DATE_UNITS, "day");
+    return new _Duration(...differenceDate(this._date, _PlainDate.from(other)._date, largest));
+  }
+
+  since(other: _PlainDate | string, opts?: object): _Duration {
+    return _PlainDate.from(other).until(this, opts);
+  }
+
+  equals(other: _PlainDate | string): boolean {
+    return _PlainDate.compare(this, other) === 0;
+  }
+
+  toPlainDateTime(time?: _PlainTime | string): _PlainDateTime {
+    const isoTime = time === undefined ? MIDNIGHT : _PlainTime.from(time)._isoTime;
+    return _PlainDateTime._create(this._date, isoTime);
+  }
+
+  toZonedDateTime(item: string | { timeZone: string; plainTime?: _PlainTime | string }): _ZonedDateTime {
+    const timeZone = timeZoneId(typeof item === "string" ? item : item.timeZone);
+    const time = typeof item === "object" && item.plainTime !== undefined ? item.plainTime : undefined;
+    if (time === undefined) {
+      return _ZonedDateTime._startOfDay(this._date, timeZone);
+    }
+    return this.toPlainDateTime(time).toZonedDateTime(timeZone);
+  }
+
+  toString(): string {
+    return formatDate(this._date);
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Temporal.PlainDate";
+  }
+}
+
+class _PlainDateTime extends CalendarFields {
+  private _date: IsoDate;
+  private _time: IsoTime;
+
+  constructor(
+    year: number,
+    month: number,
+    day: number,
+    hour = 0,
+    minute = 0,
+    second = 0,
+    millisecond = 0,
+    microsecond = 0,
+    nanosecond = 0,
+  ) {
+    super();
+    this._date = new _PlainDate(year, month, day)._isoDate;
+    this._time = new _PlainTime(hour, minute, second, millisecond, microsecond, nanosecond)._isoTime;
+  }
+
+  /** @internal */
+  static _create(date: IsoDate, time: IsoTime): _PlainDateTime {
+    const plain = Object.create(_PlainDateTime.prototype) as _PlainDateTime;
+    plain._date = date;
+    plain._time = time;
+    return plain;
+  }
+
+  static from(item: _PlainDateTime | _PlainDate | string | Record<string, unknown>, opts?: object): _PlainDateTime {
+    const overflow = overflowOption(opts);
+    if (item instanceof _PlainDateTime || item instanceof _ZonedDateTime) {
+      return _PlainDateTime._create(item._isoDate, item._isoTime);
+    }
+    if (item instanceof _PlainDate) return _PlainDateTime._create(item._isoDate, MIDNIGHT);
+    if (typeof item === "string") {
+      const { date, time } = parsePlainDateTime(item);
+      return _PlainDateTime._create(date, time ?? MIDNIGHT);
+    }
+    const o = propertyBag(item);
+    const [year, month, day] = dateFields(o);
+    return _PlainDateTime._create(
+      regulateDate(year, month, day, overflow),
+      regulateTime(timeFields(o), overflow),
+    );
+  }
+
+  static compare(one: _PlainDateTime | string, two: _PlainDateTime | string): number {
+    const a = _PlainDateTime.from(one);
+    const b = _PlainDateTime.from(two);
+    return sign(wallNs(a._date, a._time) - wallNs(b._date, b._time));
+  }
+
+  /** @internal */
+  get _isoDate(): IsoDate {
+    return this._date;
+  }
+
+  /** @internal */
+  get _isoTime(): IsoTime {
+    return this._time;
+  }
+
+  get hour() {
+    return this._time.hour;
+  }
+  get minute() {
+    return this._time.minute;
+  }
+  get second() {
+    return this._time.second;
+  }
+  get millisecond() {
+    return this._time.millisecond;
+  }
+  get microsecond() {
+    return this._time.microsecond;
+  }
+  get nanosecond() {
+    return this._time.nanosecond;
+  }
+
+  with(fields: Record<string, unknown>, opts?: object): _PlainDateTime {
+    const o = propertyBag(fields);
+    const overflow = overflowOption(opts);
+    const [year, month, day] = dateFields(o, this._date);
+    return _PlainDateTime._create(regulateDate(year, month, day, overflow), regulateTime(timeFields(o, this._time), overflow));
+  }
+
+  withPlainTime(time?: _PlainTime | string): _PlainDateTime {
+    return _PlainDateTime._create(this._date, time === undefined ? MIDNIGHT : _PlainTime.from(time)._isoTime);
+  }
+
+  add(duration: DurationLike, opts?: object): _PlainDateTime {
+    const d = _Duration.from(duration);
+    const ns = BigInt(timeNs(this._time)) + d._timeNs();
+    const days = d.weeks * 7 + d.days + Number(floorDiv(ns, DAY_NS));
+    const date = addDate(this._date, d.years, d.months, days, overflowOption(opts));
+    return _PlainDateTime._create(date, fromTimeNs(Number(floorMod(ns, DAY_NS))));
+  }
+
+  subtract(duration: DurationLike, opts?: object): _PlainDateTime {
+    return this.add(_Duration.from(duration).negated(), opts);
+  }
+
+  until(other: _PlainDateTime | string, opts?: object): _Duration {
+    const largest = largestUnitOption(opts, [...DATE_UNITS, ...TIME_UNITS], "day");
+    const end = _PlainDateTime.from(other);
+    const ns = wallNs(end._date, end._time) - wallNs(this._date, this._time);
+    if (!["year", "month", "week"].includes(largest)) {
+      return durationFromParts([0, 0, 0, 0], balanceTime(ns, largest));
+    }
+
+    // borrow a day when the time of day goes the other way
+    const direction = sign(ns);
+    let endDate = end._date;
+    let time = BigInt(timeNs(end._time) - timeNs(this._time));
+    if (sign(time) === -direction) {
+      endDate = fromEpochDays(epochDays(endDate) - direction);
+      time += BigInt(direction) * DAY_NS;
+    }
+    return durationFromParts(differenceDate(this._date, endDate, largest), balanceTime(time, "hour"));
+  }
+
+  since(other: _PlainDateTime | string, opts?: object): _Duration {
+    return _PlainDateTime.from(other).until(this, opts);
+  }
+
+  equals(other: _PlainDateTime | string): boolean {
+    return _PlainDateTime.compare(this, other) === 0;
+  }
+
+  toPlainDate(): _PlainDate {
+    return _PlainDate._create(this._date);
+  }
+
+  toPlainTime(): _PlainTime {
+    return _PlainTime._create(this._time);
+  }
+
+  toZonedDateTime(timeZone: string, opts?: object): _ZonedDateTime {
+    const id = timeZoneId(timeZone);
+    const disambiguation = disambiguationOption(options(opts));
+    return new _ZonedDateTime(epochNsFromWallClock(id, wallNs(this._date, this._time), disambiguation), id);
+  }
+
+  toString(): string {
+    return `${formatDate(this._date)}T${formatTime(this._time)}`;
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Temporal.PlainDateTime";
+  }
+}
+
+class _Instant {
+  private _epochNs: bigint;
+
+  constructor(epochNanoseconds: bigint) {
+    this._epochNs = checkEpochNs(BigInt(epochNanoseconds));
+  }
+
+  static from(item: _Instant | string): _Instant {
+    if (item instanceof _Instant) return new _Instant(item._epochNs);
+    const parsed = parseDateTime(String(item));
+    if (!parsed.z && parsed.offset === undefined) {
+      throw new RangeError(`An instant needs a UTC offset or Z: ${item}`);
+    }
+    const offset = parsed.offset === undefined ? 0 : parseOffset(parsed.offset);
+    return new _Instant(wallNs(parsed.date, parsed.time ?? MIDNIGHT) - BigInt(offset) * 1000000000n);
+  }
+
+  static fromEpochMilliseconds(epochMilliseconds: number): _Instant {
+    return new _Instant(BigInt(toInteger(epochMilliseconds, "epochMilliseconds")) * MS_NS);
+  }
+
+  static fromEpochNanoseconds(epochNanoseconds: bigint): _Instant {
+    return new _Instant(epochNanoseconds);
+  }
+
+  static compare(one: _Instant | string, two: _Instant | string): number {
+    return sign(_Instant.from(one)._epochNs - _Instant.from(two)._epochNs);
+  }
+
+  get epochMilliseconds(): number {
+    return Number(floorDiv(this._epochNs, MS_NS));
+  }
+
+  get epochNanoseconds(): bigint {
+    return this._epochNs;
+  }
+
+  add(duration: DurationLike): _Instant {
+    const d = _Duration.from(duration);
+    if (d.years || d.months || d.weeks || d.days) {
+      throw new RangeError("Instants can only be moved by hours or smaller units");
+    }
+    return new _Instant(this._epochNs + d._timeNs());
+  }
+
+  subtract(duration: DurationLike): _Instant {
+    return this.add(_Duration.from(duration).negated());
+  }
+
+  until(other: _Instant | string, opts?: object): _Duration {
+    const largest = largestUnitOption(opts, TIME_UNITS, "second");
+    return durationFromParts([0, 0, 0, 0], balanceTime(_Instant.from(other)._epochNs - this._epochNs, largest));
+  }
+
+  since(other: _Instant | string, opts?: object): _Duration {
+    return _Instant.from(other).until(this, opts);
+  }
+
+  equals(other: _Instant | string): boolean {
+    return _Instant.compare(this, other) === 0;
+  }
+
+  toZonedDateTimeISO(timeZone: string): _ZonedDateTime {
+    return new _ZonedDateTime(this._epochNs, timeZoneId(timeZone));
+  }
+
+  toString(opts?: { timeZone?: string }): string {
+    const timeZone = options(opts).timeZone;
+    if (timeZone === undefined) {
+      const [date, time] = wallClock(this._epochNs, "UTC");
+      return `${formatDate(date)}T${formatTime(time)}Z`;
+    }
+    const [date, time, offset] = wallClock(this._epochNs, timeZoneId(timeZone));
+    return `${formatDate(date)}T${formatTime(time)}${formatOffset(offset)}`;
+  }
+
+  toJSON(): string {
+    return this.toString();
+  }
+
+  toLocaleString(locales?: string | string[], opts?: object): string {
+    return new Date(this.epochMilliseconds).toLocaleString(locales, opts);
+  }
+
+  valueOf(): never {
+    return noValueOf();
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Temporal.Instant";
+  }
+}
+
+class _ZonedDateTime extends CalendarFields {
+  private _epochNs: bigint;
+  private _timeZone: string;
+  private _date: IsoDate;
+  private _time: IsoTime;
+  private _offset: number;
+
+  constructor(epochNanoseconds: bigint, timeZone: string) {
+    super();
+    this._epochNs = checkEpochNs(BigInt(epochNanoseconds));
+    this._timeZone = timeZoneId(timeZone);
+    [this._date, this._time, this._offset] = wallClock(this._epochNs, this._timeZone);
+  }
+
+  /** @internal */
+  static _startOfDay(date: IsoDate, timeZone: string): _ZonedDateTime {
+    return new _ZonedDateTime(epochNsFromWallClock(timeZone, wallNs(date, MIDNIGHT), "compatible"), timeZone);
+  }
+
+  static from(item: _ZonedDateTime | string | Record<string, unknown>, opts?: object): _ZonedDateTime {
+    const o = options(opts);
+    const disambiguation = disambiguationOption(o);
+    const offsetOption = getOption(o, "offset", ["use", "prefer", "ignore", "reject"] as const, "reject");
+    if (item instanceof _ZonedDateTime) return new _ZonedDateTime(item._epochNs, item._timeZone);
+
+    let date: IsoDate;
+    let time: IsoTime;
+    let timeZone: string;
+    let offset: string | undefined;
+    if (typeof item === "string") {
+      const parsed = parseDateTime(item);
+      if (parsed.timeZone === undefined) {
+        throw new RangeError(`A zoned date-time needs a time zone annotation: ${item}`);
+      }
+      ({ date, offset } = parsed);
+      time = parsed.time ?? MIDNIGHT;
+      timeZone = timeZoneId(parsed.timeZone);
+      if (parsed.z) offset = "+00:00";
+    } else {
+      const bag = propertyBag(item);
+      const overflow = overflowOption(opts);
+      const [year, month, day] = dateFields(bag);
+      date = regulateDate(year, month, day, overflow);
+      time = regulateTime(timeFields(bag), overflow);
+      timeZone = timeZoneId(bag.timeZone);
+      offset = bag.offset === undefined ? undefined : String(bag.offset);
+    }
+    return _ZonedDateTime._resolve(date, time, timeZone, offset, disambiguation, offsetOption);
+  }
+
+  /**
+   * Finds the instant for a wall-clock time, using `offset` when it's one the
+   * time zone can have there.
+   * @internal
+   */
+  static _resolve(
+    date: IsoDate,
+    time: IsoTime,
+    timeZone: string,
+    offset: string | undefined,
+    disambiguation: Disambiguation,
+    offsetOption: "use" | "prefer" | "ignore" | "reject",
+  ): _ZonedDateTime {
+    const wall = wallNs(date, time);
+    if (offset !== undefined && offsetOption !== "ignore") {
+      const seconds = parseOffset(offset);
+      const epochNs = wall - BigInt(seconds) * 1000000000n;
+      if (offsetOption === "use" || offsetSeconds(timeZone, epochNs) === seconds) {
+        return new _ZonedDateTime(epochNs, timeZone);
+      }
+      if (offsetOption === "reject") {
+        throw new RangeError(`The offset ${offset} is not valid in ${timeZone} at that time`);
+      }
+    }
+    return new _ZonedDateTime(epochNsFromWallClock(timeZone, wall, disambiguation), timeZone);
+  }
+
+  static compare(one: _ZonedDateTime | string, two: _ZonedDateTime | string): number {
+    return sign(_ZonedDateTime.from(one)._epochNs - _ZonedDateTime.from(two)._epochNs);
+  }
+
+  /** @internal */
+  get _isoDate(): IsoDate {
+    return this._date;
+  }
+
+  /** @internal */
+  get _isoTime(): IsoTime {
+    return this._time;
+  }
+
+  get timeZoneId(): string {
+    return this._timeZone;
+  }
+  get hour() {
+    return this._time.hour;
+  }
+  get minute() {
+    return this._time.minute;
+  }
+  get second() {
+    return this._time.second;
+  }
+  get millisecond() {
+    return this._time.millisecond;
+  }
+  get microsecond() {
+    return this._time.microsecond;
+  }
+  get nanosecond() {
+    return this._time.nanosecond;
+  }
+  get offset(): string {
+    return formatOffset(this._offset);
+  }
+  get offsetNanoseconds(): number {
+    return this._offset * 1e9;
+  }
+  get epochMilliseconds(): number {
+    return Number(floorDiv(this._epochNs, MS_NS));
+  }
+  get epochNanoseconds(): bigint {
+    return this._epochNs;
+  }
+
+  get hoursInDay(): number {
+    const start = _ZonedDateTime._startOfDay(this._date, this._timeZone);
+    const next = _ZonedDateTime._startOfDay(fromEpochDays(epochDays(this._date) + 1), this._timeZone);
+    return Number(next._epochNs - start._epochNs) / 3.6e12;
+  }
+
+  with(fields: Record<string, unknown>, opts?: object): _ZonedDateTime {
+    const bag = propertyBag(fields);
+    const o = options(opts);
+    const overflow = overflowOption(opts);
+    const [year, month, day] = dateFields(bag, this._date);
+    return _ZonedDateTime._resolve(
+      regulateDate(year, month, day, overflow),
+      regulateTime(timeFields(bag, this._time), overflow),
+      this._timeZone,
+      bag.offset === undefined ? this.offset : String(bag.offset),
+      disambiguationOption(o),
+      getOption(o, "offset", ["use", "prefer", "ignore", "reject"] as const, "prefer"),
+    );
+  }
+
+  withPlainTime(time?: _PlainTime | string): _ZonedDateTime {
+    if (time === undefined) return this.startOfDay();
+    return this.toPlainDateTime().withPlainTime(time).toZonedDateTime(this._timeZone);
+  }
+
+  withTimeZone(timeZone: string): _ZonedDateTime {
+    return new _ZonedDateTime(this._epochNs, timeZone);
+  }
+
+  startOfDay(): _ZonedDateTime {
+    return _ZonedDateTime._startOfDay(this._date, this._timeZone);
+  }
+
+  /**
+   * Years, months, weeks and days move the wall clock and keep the time of
+   * day where possible; hours and smaller units are exact elapsed time.
+   */
+  add(duration: DurationLike, opts?: object): _ZonedDateTime {
+    const d = _Duration.from(duration);
+    let epochNs = this._epochNs;
+    if (d.years || d.months || d.weeks || d.days) {
+      const date = addDate(this._date, d.years, d.months, d.weeks * 7 + d.days, overflowOption(opts));
+      epochNs = epochNsFromWallClock(this._timeZone, wallNs(date, this._time), "compatible");
+    }
+    return new _ZonedDateTime(epochNs + d._timeNs(), this._timeZone);
+  }
+
+  subtract(duration: DurationLike, opts?: object): _ZonedDateTime {
+    return this.add(_Duration.from(duration).negated(), opts);
+  }
+
+  until(other: _ZonedDateTime | string, opts?: object): _Duration {
+    const largest = largestUnitOption(opts, [...DATE_UNITS, ...TIME_UNITS], "hour");
+    const end = _ZonedDateTime.from(other);
+    const ns = end._epochNs - this._epochNs;
+    if (TIME_UNITS.includes(largest) || ns === 0n) {
+      return durationFromParts([0, 0, 0, 0], balanceTime(ns, TIME_UNITS.includes(largest) ? largest : "hour"));
+    }
+    if (end._timeZone !== this._timeZone) {
+      throw new RangeError("Dates in different time zones can only be compared in hours or smaller units");
+    }
+
+    // count whole days on the wall clock, then the exact time left over
+    const direction = sign(ns);
+    let endDate = end._date;
+    if (sign(timeNs(end._time) - timeNs(this._time)) === -direction) {
+      endDate = fromEpochDays(epochDays(endDate) - direction);
+    }
+    for (;;) {
+      const middle = epochNsFromWallClock(this._timeZone, wallNs(endDate, this._time), "compatible");
+      const time = end._epochNs - middle;
+      if (sign(time) !== -direction) {
+        return durationFromParts(differenceDate(this._date, endDate, largest), balanceTime(time, "hour"));
+      }
+      endDate = fromEpochDays(epochDays(endDate) - direction);
+    }
+  }
+
+  since(other: _ZonedDateTime | string, opts?: object): _Duration {
+    return _ZonedDateTime.from(other).until(this, opts);
+  }
+
+  equals(other: _ZonedDateTime | string): boolean {
+    const zoned = _ZonedDateTime.from(other);
+    return zoned._epochNs === this._epochNs && zoned._timeZone === this._timeZone;
+  }
+
+  toInstant(): _Instant {
+    return new _Instant(this._epochNs);
+  }
+
+  toPlainDate(): _PlainDate {
+    return _PlainDate._create(this._date);
+  }
+
+  toPlainTime(): _PlainTime {
+    return _PlainTime._create(this._time);
+  }
+
+  toPlainDateTime(): _PlainDateTime {
+    return _PlainDateTime._create(this._date, this._time);
+  }
+
+  toString(): string {
+    return `${formatDate(this._date)}T${formatTime(this._time)}${this.offset}[${this._timeZone}]`;
+  }
+
+  toLocaleString(locales?: string | string[], opts?: object): string {
+    return new Date(this.epochMilliseconds).toLocaleString(locales, { timeZone: this._timeZone, ...opts });
+  }
+
+  get [Symbol.toStringTag]() {
+    return "Temporal.ZonedDateTime";
+  }
+}
+
+function now(): _Instant {
+  return _Instant.fromEpochMilliseconds(Date.now());
+}
+
+function nowZoned(timeZone?: string): _ZonedDateTime {
+  return now().toZonedDateTimeISO(timeZone ?? __localTimeZone());
+}
+
+(globalThis as any).Temporal = {
+  Duration: _Duration,
+  Instant: _Instant,
+  PlainDate: _PlainDate,
+  PlainDateTime: _PlainDateTime,
+  PlainTime: _PlainTime,
+  ZonedDateTime: _ZonedDateTime,
+  Now: {
+    instant: now,
+    timeZoneId: () => __localTimeZone(),
+    zonedDateTimeISO: nowZoned,
+    plainDateTimeISO: (timeZone?: string) => nowZoned(timeZone).toPlainDateTime(),
+    plainDateISO: (timeZone?: string) => nowZoned(timeZone).toPlainDate(),
+    plainTimeISO: (timeZone?: string) => nowZoned(timeZone).toPlainTime(),
+  },
+};
+
+(Date.prototype as any).toTemporalInstant = function (this: Date) {
+  return _Instant.fromEpochMilliseconds(this.getTime());
+};
+
+export {};
@@ -9,29 +9,54 @@ declare global {
     fatal: boolean,
     ignoreBOM: boolean
   ): string;
+
+  /**
+   * Streaming decoders for every encoding, backed by encoding_rs
+   * @internal
+   */
+  var __textDecoding: {
+    encodingForLabel(label: string): string | null;
+    open(encoding: string, ignoreBOM: boolean, fatal: boolean): number;
+    decode(
+      id: number,
+      input: ArrayBufferLike,
+      byteOffset: number,
+      byteLength: number,
+      stream: boolean
+    ): string;
+  };
 }
 
+const UTF8_LABELS = [
+  "utf-8",
+  "utf8",
+  "unicode-1-1-utf-8",
+  "unicode11utf8",
+  "unicode20utf8",
+  "x-unicode20utf8",
+];
+
 class TextDecoder implements globalThis.TextDecoder {
   readonly encoding: string;
   readonly fatal: boolean;
   readonly ignoreBOM: boolean;
 
+  /**
+   * The native decoder kept open between `{ stream: true }` calls
+   * @internal
+   */
+  _decoder?: number;
+
   constructor(label: string = "utf-8", options: TextDecoderOptions = {}) {
-    label = label.trim().toLowerCase();
-    const acceptedLabels = [
-      "utf-8",
-      "utf8",
-      "unicode-1-1-utf-8",
-      "unicode11utf8",
-      "unicode20utf8",
-      "x-unicode20utf8",
-    ];
-    if (!acceptedLabels.includes(label)) {
-      // Not spec-compliant behaviour
-      throw new RangeError("The encoding label provided must be utf-8");
+    label = String(label).trim().toLowerCase();
+    const encoding = UTF8_LABELS.includes(label)
+      ? "utf-8"
+      : __textDecoding.encodingForLabel(label);
+    if (!encoding) {
+      throw new RangeError(`The encoding label provided ('${label}') is invalid`);
     }
 
-    this.encoding = "utf-8";
+    this.encoding = encoding;
     this.fatal = !!options.fatal;
     this.ignoreBOM = !!options.ignoreBOM;
   }
@@ -40,12 +65,13 @@ class TextDecoder implements globalThis.TextDecoder {
     input?: AllowSharedBufferSource,
     options: TextDecodeOptions = {}
   ): string {
+    const stream = !!options.stream;
     if (input === undefined) {
-      return "";
-    }
-
-    if (options.stream) {
-      throw new Error("Streaming decode is not supported");
+      // a final call without input flushes an open stream
+      if (this._decoder === undefined) {
+        return "";
+      }
+      input = new ArrayBuffer(0);
     }
 
     // backing buffer would not have byteOffset and may have different byteLength
@@ -62,13 +88,30 @@ class TextDecoder implements globalThis.TextDecoder {
       );
     }
 
-    return __decodeUtf8BufferToString(
-      input,
-      byteOffset,
-      byteLength,
-      this.fatal,
-      this.ignoreBOM
-    );
+    if (this.encoding === "utf-8" && !stream && this._decoder === undefined) {
+      return __decodeUtf8BufferToString(
+        input,
+        byteOffset,
+        byteLength,
+        this.fatal,
+        this.ignoreBOM
+      );
+    }
+
+    if (this._decoder === undefined) {
+      this._decoder = __textDecoding.open(this.encoding, this.ignoreBOM, this.fatal);
+    }
+    const id = this._decoder;
+    // the native side frees the decoder after a non-streaming call or an error
+    if (!stream) {
+      this._decoder = undefined;
+    }
+    try {
+      return __textDecoding.decode(id, input, byteOffset, byteLength, stream);
+    } catch (e) {
+      this._decoder = undefined;
+      throw new TypeError((e as Error).message);
+    }
   }
 }
 
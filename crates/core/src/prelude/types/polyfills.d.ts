@@ -267,6 +267,301 @@ interface Performance {
 
 declare var performance: Performance;
 
+interface QueuingStrategy<T = any> {
+  highWaterMark?: number;
+  size?: (chunk: T) => number;
+}
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/CountQueuingStrategy) */
+declare var CountQueuingStrategy: {
+  prototype: QueuingStrategy;
+  new (init: { highWaterMark: number }): QueuingStrategy;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/ByteLengthQueuingStrategy) */
+declare var ByteLengthQueuingStrategy: {
+  prototype: QueuingStrategy<ArrayBufferView>;
+  new (init: { highWaterMark: number }): QueuingStrategy<ArrayBufferView>;
+};
+
+type ReadableStreamReadResult<T> =
+  | { done: false; value: T }
+  | { done: true; value: undefined };
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/ReadableStreamDefaultController) */
+interface ReadableStreamDefaultController<R = any> {
+  /** Always `null`: byte streams are served as default streams. */
+  readonly byobRequest: null;
+  readonly desiredSize: number | null;
+  close(): void;
+  enqueue(chunk: R): void;
+  error(reason?: any): void;
+}
+
+declare var ReadableStreamDefaultController: {
+  prototype: ReadableStreamDefaultController;
+};
+
+interface UnderlyingDefaultSource<R = any> {
+  cancel?: (reason?: any) => void | PromiseLike<void>;
+  pull?: (controller: ReadableStreamDefaultController<R>) => void | PromiseLike<void>;
+  start?: (controller: ReadableStreamDefaultController<R>) => any;
+  type?: undefined;
+}
+
+interface UnderlyingByteSource {
+  autoAllocateChunkSize?: number;
+  cancel?: (reason?: any) => void | PromiseLike<void>;
+  pull?: (controller: ReadableStreamDefaultController<Uint8Array>) => void | PromiseLike<void>;
+  start?: (controller: ReadableStreamDefaultController<Uint8Array>) => any;
+  type: "bytes";
+}
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/ReadableStreamDefaultReader) */
+interface ReadableStreamDefaultReader<R = any> {
+  readonly closed: Promise<undefined>;
+  cancel(reason?: any): Promise<void>;
+  read(): Promise<ReadableStreamReadResult<R>>;
+  releaseLock(): void;
+}
+
+declare var ReadableStreamDefaultReader: {
+  prototype: ReadableStreamDefaultReader;
+  new <R = any>(stream: ReadableStream<R>): ReadableStreamDefaultReader<R>;
+};
+
+interface StreamPipeOptions {
+  preventAbort?: boolean;
+  preventCancel?: boolean;
+  preventClose?: boolean;
+  signal?: AbortSignal;
+}
+
+/**
+ * Byte streams (`type: "bytes"`) are accepted but behave like default
+ * streams; BYOB readers are not supported.
+ *
+ * [MDN Reference](https://developer.mozilla.org/docs/Web/API/ReadableStream)
+ */
+interface ReadableStream<R = any> {
+  readonly locked: boolean;
+  cancel(reason?: any): Promise<void>;
+  getReader(): ReadableStreamDefaultReader<R>;
+  pipeThrough<T>(
+    transform: { writable: WritableStream<R>; readable: ReadableStream<T> },
+    options?: StreamPipeOptions,
+  ): ReadableStream<T>;
+  pipeTo(destination: WritableStream<R>, options?: StreamPipeOptions): Promise<void>;
+  tee(): [ReadableStream<R>, ReadableStream<R>];
+  values(options?: { preventCancel?: boolean }): AsyncIterableIterator<R>;
+  [Symbol.asyncIterator](options?: { preventCancel?: boolean }): AsyncIterableIterator<R>;
+}
+
+declare var ReadableStream: {
+  prototype: ReadableStream;
+  new (source: UnderlyingByteSource, strategy?: { highWaterMark?: number }): ReadableStream<Uint8Array>;
+  new <R = any>(source?: UnderlyingDefaultSource<R>, strategy?: QueuingStrategy<R>): ReadableStream<R>;
+  from<R>(source: Iterable<R> | AsyncIterable<R>): ReadableStream<R>;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/WritableStreamDefaultController) */
+interface WritableStreamDefaultController {
+  readonly signal: AbortSignal;
+  error(reason?: any): void;
+}
+
+declare var WritableStreamDefaultController: {
+  prototype: WritableStreamDefaultController;
+};
+
+interface UnderlyingSink<W = any> {
+  abort?: (reason?: any) => void | PromiseLike<void>;
+  close?: () => void | PromiseLike<void>;
+  start?: (controller: WritableStreamDefaultController) => any;
+  write?: (chunk: W, controller: WritableStreamDefaultController) => void | PromiseLike<void>;
+}
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/WritableStreamDefaultWriter) */
+interface WritableStreamDefaultWriter<W = any> {
+  readonly closed: Promise<undefined>;
+  readonly desiredSize: number | null;
+  readonly ready: Promise<undefined>;
+  abort(reason?: any): Promise<void>;
+  close(): Promise<void>;
+  releaseLock(): void;
+  write(chunk: W): Promise<void>;
+}
+
+declare var WritableStreamDefaultWriter: {
+  prototype: WritableStreamDefaultWriter;
+  new <W = any>(stream: WritableStream<W>): WritableStreamDefaultWriter<W>;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/WritableStream) */
+interface WritableStream<W = any> {
+  readonly locked: boolean;
+  abort(reason?: any): Promise<void>;
+  close(): Promise<void>;
+  getWriter(): WritableStreamDefaultWriter<W>;
+}
+
+declare var WritableStream: {
+  prototype: WritableStream;
+  new <W = any>(sink?: UnderlyingSink<W>, strategy?: QueuingStrategy<W>): WritableStream<W>;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/TransformStreamDefaultController) */
+interface TransformStreamDefaultController<O = any> {
+  readonly desiredSize: number | null;
+  enqueue(chunk: O): void;
+  error(reason?: any): void;
+  terminate(): void;
+}
+
+interface Transformer<I = any, O = any> {
+  cancel?: (reason?: any) => void | PromiseLike<void>;
+  flush?: (controller: TransformStreamDefaultController<O>) => void | PromiseLike<void>;
+  start?: (controller: TransformStreamDefaultController<O>) => any;
+  transform?: (chunk: I, controller: TransformStreamDefaultController<O>) => void | PromiseLike<void>;
+}
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/TransformStream) */
+interface TransformStream<I = any, O = any> {
+  readonly readable: ReadableStream<O>;
+  readonly writable: WritableStream<I>;
+}
+
+declare var TransformStream: {
+  prototype: TransformStream;
+  new <I = any, O = any>(
+    transformer?: Transformer<I, O>,
+    writableStrategy?: QueuingStrategy<I>,
+    readableStrategy?: QueuingStrategy<O>,
+  ): TransformStream<I, O>;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/TextDecoderStream) */
+interface TextDecoderStream extends TransformStream<AllowSharedBufferSource, string> {
+  readonly encoding: string;
+  readonly fatal: boolean;
+  readonly ignoreBOM: boolean;
+}
+
+declare var TextDecoderStream: {
+  prototype: TextDecoderStream;
+  new (label?: string, options?: TextDecoderOptions): TextDecoderStream;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/TextEncoderStream) */
+interface TextEncoderStream extends TransformStream<string, Uint8Array> {
+  readonly encoding: string;
+}
+
+declare var TextEncoderStream: {
+  prototype: TextEncoderStream;
+  new (): TextEncoderStream;
+};
+
+type CompressionFormat = "deflate" | "deflate-raw" | "gzip";
+
+/**
+ * Only defined when the engine is built with the `compression` feature.
+ *
+ * [MDN Reference](https://developer.mozilla.org/docs/Web/API/CompressionStream)
+ */
+interface CompressionStream extends TransformStream<AllowSharedBufferSource, Uint8Array> {}
+
+declare var CompressionStream: {
+  prototype: CompressionStream;
+  new (format: CompressionFormat): CompressionStream;
+};
+
+/**
+ * Only defined when the engine is built with the `compression` feature.
+ *
+ * [MDN Reference](https://developer.mozilla.org/docs/Web/API/DecompressionStream)
+ */
+interface DecompressionStream extends TransformStream<AllowSharedBufferSource, Uint8Array> {}
+
+declare var DecompressionStream: {
+  prototype: DecompressionStream;
+  new (format: CompressionFormat): DecompressionStream;
+};
+
+type BlobPart = string | ArrayBufferLike | ArrayBufferView | Blob;
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob) */
+interface Blob {
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob/size) */
+  readonly size: number;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob/type) */
+  readonly type: string;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob/arrayBuffer) */
+  arrayBuffer(): Promise<ArrayBuffer>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob/bytes) */
+  bytes(): Promise<Uint8Array>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob/slice) */
+  slice(start?: number, end?: number, contentType?: string): Blob;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob/stream) */
+  stream(): ReadableStream<Uint8Array>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Blob/text) */
+  text(): Promise<string>;
+}
+
+declare var Blob: {
+  prototype: Blob;
+  new (parts?: BlobPart[], options?: { type?: string }): Blob;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/File) */
+interface File extends Blob {
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/File/lastModified) */
+  readonly lastModified: number;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/File/name) */
+  readonly name: string;
+}
+
+declare var File: {
+  prototype: File;
+  new (
+    parts: BlobPart[],
+    name: string,
+    options?: { type?: string; lastModified?: number },
+  ): File;
+};
+
+type FormDataEntryValue = string | File;
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/FormData) */
+interface FormData {
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/FormData/append) */
+  append(name: string, value: string | Blob, filename?: string): void;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/FormData/delete) */
+  delete(name: string): void;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/FormData/get) */
+  get(name: string): FormDataEntryValue | null;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/FormData/getAll) */
+  getAll(name: string): FormDataEntryValue[];
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/FormData/has) */
+  has(name: string): boolean;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/FormData/set) */
+  set(name: string, value: string | Blob, filename?: string): void;
+  forEach(
+    callback: (value: FormDataEntryValue, key: string, parent: FormData) => void,
+  ): void;
+  entries(): IterableIterator<[string, FormDataEntryValue]>;
+  keys(): IterableIterator<string>;
+  values(): IterableIterator<FormDataEntryValue>;
+  [Symbol.iterator](): IterableIterator<[string, FormDataEntryValue]>;
+}
+
+declare var FormData: {
+  prototype: FormData;
+  new (): FormData;
+};
+
+type HeadersInit = Headers | Record<string, string> | [string, string][];
+
 /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Headers) */
 interface Headers {
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Headers/append) */
@@ -275,6 +570,8 @@ interface Headers {
   delete(name: string): void;
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Headers/get) */
   get(name: string): string | null;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Headers/getSetCookie) */
+  getSetCookie(): string[];
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Headers/has) */
   has(name: string): boolean;
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Headers/set) */
@@ -286,59 +583,122 @@ interface Headers {
   entries(): [string, string][];
   keys(): string[];
   values(): string[];
+  [Symbol.iterator](): IterableIterator<[string, string]>;
 }
 
 declare var Headers: {
   prototype: Headers;
-  new (
-    init?: Record<string, string> | [string, string][] | Headers,
-  ): Headers;
+  new (init?: HeadersInit): Headers;
 };
 
-/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response) */
-interface Response {
-  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/body) */
+type BodyInit =
+  | string
+  | ArrayBufferLike
+  | ArrayBufferView
+  | Blob
+  | FormData
+  | ReadableStream<Uint8Array>
+  | URLSearchParams;
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request) */
+interface Body {
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/body) */
+  readonly body: ReadableStream<Uint8Array> | null;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/bodyUsed) */
   readonly bodyUsed: boolean;
-  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/headers) */
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/headers) */
   readonly headers: Headers;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/arrayBuffer) */
+  arrayBuffer(): Promise<ArrayBuffer>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/blob) */
+  blob(): Promise<Blob>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/bytes) */
+  bytes(): Promise<Uint8Array>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/formData) */
+  formData(): Promise<FormData>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/json) */
+  json(): Promise<any>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/text) */
+  text(): Promise<string>;
+}
+
+type RequestCache = "default" | "force-cache" | "no-cache" | "no-store" | "only-if-cached" | "reload";
+type RequestCredentials = "include" | "omit" | "same-origin";
+type RequestMode = "cors" | "navigate" | "no-cors" | "same-origin";
+type RequestRedirect = "error" | "follow" | "manual";
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request) */
+interface Request extends Body {
+  readonly cache: RequestCache;
+  readonly credentials: RequestCredentials;
+  readonly integrity: string;
+  readonly keepalive: boolean;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/method) */
+  readonly method: string;
+  readonly mode: RequestMode;
+  readonly redirect: RequestRedirect;
+  readonly referrer: string;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/signal) */
+  readonly signal: AbortSignal;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/url) */
+  readonly url: string;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Request/clone) */
+  clone(): Request;
+}
+
+declare var Request: {
+  prototype: Request;
+  new (input: string | URL | Request, init?: RequestInit): Request;
+};
+
+type ResponseType = "basic" | "cors" | "default" | "error" | "opaque" | "opaqueredirect";
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response) */
+interface Response extends Body {
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/ok) */
   readonly ok: boolean;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/redirected) */
+  readonly redirected: boolean;
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/status) */
   readonly status: number;
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/statusText) */
   readonly statusText: string;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/type) */
+  readonly type: ResponseType;
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/url) */
   readonly url: string;
-  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/arrayBuffer) */
-  arrayBuffer(): Promise<ArrayBuffer>;
   /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/clone) */
   clone(): Response;
-  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/json) */
-  json(): Promise<any>;
-  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/text) */
-  text(): Promise<string>;
 }
 
 declare var Response: {
   prototype: Response;
-  new (body?: string | null, init?: ResponseInit): Response;
+  new (body?: BodyInit | null, init?: ResponseInit): Response;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/error_static) */
+  error(): Response;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/json_static) */
+  json(data: any, init?: ResponseInit): Response;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/Response/redirect_static) */
+  redirect(url: string | URL, status?: number): Response;
 };
 
 interface ResponseInit {
-  headers?: Headers | Record<string, string> | [string, string][];
+  headers?: HeadersInit;
   status?: number;
   statusText?: string;
 }
 
 interface RequestInit {
-  body?: string;
-  headers?: Headers | Record<string, string> | [string, string][];
+  body?: BodyInit | null;
+  headers?: HeadersInit;
   method?: string;
+  redirect?: RequestRedirect;
+  signal?: AbortSignal | null;
 }
 
 /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/fetch) */
 declare function fetch(
-  input: string | URL,
+  input: string | URL | Request,
   init?: RequestInit,
 ): Promise<Response>;
 
@@ -352,13 +712,239 @@ interface Crypto {
   readonly subtle: SubtleCrypto;
 }
 
-/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto) */
+interface Algorithm {
+  name: string;
+}
+
+type AlgorithmIdentifier = Algorithm | string;
+type HashAlgorithmIdentifier = AlgorithmIdentifier;
+type KeyFormat = "jwk" | "pkcs8" | "raw" | "spki";
+type KeyType = "private" | "public" | "secret";
+type KeyUsage = "decrypt" | "deriveBits" | "deriveKey" | "encrypt" | "sign" | "verify";
+
+interface KeyAlgorithm {
+  name: string;
+}
+
+interface HmacKeyAlgorithm extends KeyAlgorithm {
+  hash: KeyAlgorithm;
+  length: number;
+}
+
+interface HmacImportParams extends Algorithm {
+  hash: HashAlgorithmIdentifier;
+  length?: number;
+}
+
+interface HmacKeyGenParams extends Algorithm {
+  hash: HashAlgorithmIdentifier;
+  length?: number;
+}
+
+interface AesKeyAlgorithm extends KeyAlgorithm {
+  length: number;
+}
+
+interface AesKeyGenParams extends Algorithm {
+  length: number;
+}
+
+/** Only 96-bit IVs and tag lengths of 96 to 128 bits are supported. */
+interface AesGcmParams extends Algorithm {
+  additionalData?: ArrayBuffer | ArrayBufferView;
+  iv: ArrayBuffer | ArrayBufferView;
+  tagLength?: number;
+}
+
+interface AesCbcParams extends Algorithm {
+  iv: ArrayBuffer | ArrayBufferView;
+}
+
+interface AesCtrParams extends Algorithm {
+  counter: ArrayBuffer | ArrayBufferView;
+  length: number;
+}
+
+/** Only "P-256" and "P-384" are supported. */
+type NamedCurve = string;
+
+interface EcKeyImportParams extends Algorithm {
+  namedCurve: NamedCurve;
+}
+
+interface EcKeyAlgorithm extends KeyAlgorithm {
+  namedCurve: NamedCurve;
+}
+
+interface EcdsaParams extends Algorithm {
+  hash: HashAlgorithmIdentifier;
+}
+
+interface RsaHashedImportParams extends Algorithm {
+  hash: HashAlgorithmIdentifier;
+}
+
+interface RsaHashedKeyAlgorithm extends KeyAlgorithm {
+  hash: KeyAlgorithm;
+  modulusLength: number;
+  publicExponent: Uint8Array;
+}
+
+interface RsaPssParams extends Algorithm {
+  saltLength: number;
+}
+
+interface EcKeyGenParams extends Algorithm {
+  namedCurve: NamedCurve;
+}
+
+/** Used for both ECDH and X25519. */
+interface EcdhKeyDeriveParams extends Algorithm {
+  public: CryptoKey;
+}
+
+interface Pbkdf2Params extends Algorithm {
+  hash: HashAlgorithmIdentifier;
+  iterations: number;
+  salt: ArrayBuffer | ArrayBufferView;
+}
+
+interface HkdfParams extends Algorithm {
+  hash: HashAlgorithmIdentifier;
+  info: ArrayBuffer | ArrayBufferView;
+  salt: ArrayBuffer | ArrayBufferView;
+}
+
+interface AesDerivedKeyParams extends Algorithm {
+  length: number;
+}
+
+/** Only the members used by EC, OKP and RSA keys are read. */
+interface JsonWebKey {
+  alg?: string;
+  crv?: string;
+  d?: string;
+  e?: string;
+  ext?: boolean;
+  key_ops?: string[];
+  kty?: string;
+  n?: string;
+  p?: string;
+  q?: string;
+  x?: string;
+  y?: string;
+}
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/CryptoKey) */
+interface CryptoKey {
+  readonly algorithm: KeyAlgorithm;
+  readonly extractable: boolean;
+  readonly type: KeyType;
+  readonly usages: KeyUsage[];
+}
+
+declare var CryptoKey: {
+  prototype: CryptoKey;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/CryptoKeyPair) */
+interface CryptoKeyPair {
+  privateKey: CryptoKey;
+  publicKey: CryptoKey;
+}
+
+/**
+ * Everything but `digest` requires an engine built with the `webcrypto`
+ * feature, and throws a `NotSupportedError` otherwise.
+ *
+ * [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto)
+ */
 interface SubtleCrypto {
-  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/digest) */
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/decrypt) */
+  decrypt(
+    algorithm: AlgorithmIdentifier | AesGcmParams | AesCbcParams | AesCtrParams,
+    key: CryptoKey,
+    data: ArrayBuffer | ArrayBufferView,
+  ): Promise<ArrayBuffer>;
+  /**
+   * The length may be left out, or `null`, for ECDH and X25519 to get the
+   * whole shared secret.
+   *
+   * [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/deriveBits)
+   */
+  deriveBits(
+    algorithm: Pbkdf2Params | HkdfParams | EcdhKeyDeriveParams,
+    baseKey: CryptoKey,
+    length?: number | null,
+  ): Promise<ArrayBuffer>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/deriveKey) */
+  deriveKey(
+    algorithm: Pbkdf2Params | HkdfParams | EcdhKeyDeriveParams,
+    baseKey: CryptoKey,
+    derivedKeyType: HmacImportParams | AesDerivedKeyParams,
+    extractable: boolean,
+    keyUsages: KeyUsage[],
+  ): Promise<CryptoKey>;
+  /**
+   * SHA-1, SHA-256, SHA-384, SHA-512, and SHA3-256, SHA3-384, SHA3-512 when
+   * the engine is built with the `hashes` feature. Other hashes and checksums
+   * are available through `Host.createHash`.
+   *
+   * [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/digest)
+   */
   digest(
     algorithm: string | { name: string },
     data: ArrayBuffer | ArrayBufferView,
   ): Promise<ArrayBuffer>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/encrypt) */
+  encrypt(
+    algorithm: AlgorithmIdentifier | AesGcmParams | AesCbcParams | AesCtrParams,
+    key: CryptoKey,
+    data: ArrayBuffer | ArrayBufferView,
+  ): Promise<ArrayBuffer>;
+  /**
+   * Secret keys and EC and Ed25519 public keys export as "raw", RSA keys as
+   * "spki" or "pkcs8".
+   *
+   * [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/exportKey)
+   */
+  exportKey(format: KeyFormat, key: CryptoKey): Promise<ArrayBuffer>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/generateKey) */
+  generateKey(
+    algorithm: "X25519" | { name: "X25519" } | EcKeyGenParams,
+    extractable: boolean,
+    keyUsages: KeyUsage[],
+  ): Promise<CryptoKeyPair>;
+  generateKey(
+    algorithm: AlgorithmIdentifier | HmacKeyGenParams | AesKeyGenParams,
+    extractable: boolean,
+    keyUsages: KeyUsage[],
+  ): Promise<CryptoKey>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/importKey) */
+  importKey(
+    format: KeyFormat,
+    keyData: ArrayBuffer | ArrayBufferView | JsonWebKey,
+    algorithm: AlgorithmIdentifier | HmacImportParams | EcKeyImportParams | RsaHashedImportParams,
+    extractable: boolean,
+    keyUsages: KeyUsage[],
+  ): Promise<CryptoKey>;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/sign) */
+  sign(
+    algorithm: AlgorithmIdentifier | EcdsaParams | RsaPssParams,
+    key: CryptoKey,
+    data: ArrayBuffer | ArrayBufferView,
+  ): Promise<ArrayBuffer>;
+  /**
+   * HMAC signatures are compared in constant time.
+   *
+   * [MDN Reference](https://developer.mozilla.org/docs/Web/API/SubtleCrypto/verify)
+   */
+  verify(
+    algorithm: AlgorithmIdentifier | EcdsaParams | RsaPssParams,
+    key: CryptoKey,
+    signature: ArrayBuffer | ArrayBufferView,
+    data: ArrayBuffer | ArrayBufferView,
+  ): Promise<boolean>;
 }
 
 /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/crypto) */
@@ -414,6 +1000,60 @@ declare var EventTarget: {
   new (): EventTarget;
 };
 
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/PromiseRejectionEvent) */
+interface PromiseRejectionEvent extends Event {
+  readonly promise: Promise<unknown>;
+  readonly reason: unknown;
+}
+
+declare var PromiseRejectionEvent: {
+  prototype: PromiseRejectionEvent;
+  new (
+    type: string,
+    eventInitDict: { promise: Promise<unknown>; reason?: unknown; bubbles?: boolean; cancelable?: boolean },
+  ): PromiseRejectionEvent;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortSignal) */
+interface AbortSignal extends EventTarget {
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortSignal/aborted) */
+  readonly aborted: boolean;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortSignal/reason) */
+  readonly reason: any;
+  onabort: ((this: AbortSignal, event: Event) => any) | null;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortSignal/throwIfAborted) */
+  throwIfAborted(): void;
+}
+
+declare var AbortSignal: {
+  prototype: AbortSignal;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortSignal/abort_static) */
+  abort(reason?: any): AbortSignal;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortSignal/any_static) */
+  any(signals: AbortSignal[]): AbortSignal;
+  /**
+   * The signal aborts once the timeout is due while the engine is waiting on
+   * the export's Promise, or when the signal is read. Synchronous code that
+   * never reads it is not interrupted.
+   *
+   * [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortSignal/timeout_static)
+   */
+  timeout(milliseconds: number): AbortSignal;
+};
+
+/** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortController) */
+interface AbortController {
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortController/signal) */
+  readonly signal: AbortSignal;
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/AbortController/abort) */
+  abort(reason?: any): void;
+}
+
+declare var AbortController: {
+  prototype: AbortController;
+  new (): AbortController;
+};
+
 /** [MDN Reference](https://developer.mozilla.org/docs/Web/API/queueMicrotask) */
 declare function queueMicrotask(callback: () => void): void;
 
@@ -532,3 +1172,292 @@ type BufferEncoding =
   | "ucs-2"
   | "utf16le"
   | "utf-16le";
+
+/**
+ * Dates and times for the ISO 8601 calendar, with time zones from the tz
+ * database compiled into the engine. Rounding options and other calendars are
+ * not supported.
+ *
+ * [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal)
+ */
+declare namespace Temporal {
+  type DateUnit = "year" | "month" | "week" | "day";
+  type TimeUnit = "hour" | "minute" | "second" | "millisecond" | "microsecond" | "nanosecond";
+  type PluralUnit<T extends string> = T | `${T}s`;
+
+  interface OverflowOptions {
+    overflow?: "constrain" | "reject";
+  }
+
+  interface ToInstantOptions {
+    disambiguation?: "compatible" | "earlier" | "later" | "reject";
+  }
+
+  interface ZonedDateTimeOptions extends OverflowOptions, ToInstantOptions {
+    offset?: "use" | "prefer" | "ignore" | "reject";
+  }
+
+  interface DifferenceOptions<T extends string> {
+    largestUnit?: "auto" | PluralUnit<T>;
+  }
+
+  interface DurationLike {
+    years?: number;
+    months?: number;
+    weeks?: number;
+    days?: number;
+    hours?: number;
+    minutes?: number;
+    seconds?: number;
+    milliseconds?: number;
+    microseconds?: number;
+    nanoseconds?: number;
+  }
+
+  interface PlainTimeLike {
+    hour?: number;
+    minute?: number;
+    second?: number;
+    millisecond?: number;
+    microsecond?: number;
+    nanosecond?: number;
+  }
+
+  interface PlainDateLike {
+    year?: number;
+    month?: number;
+    monthCode?: string;
+    day?: number;
+  }
+
+  interface PlainDateTimeLike extends PlainDateLike, PlainTimeLike {}
+
+  interface ZonedDateTimeLike extends PlainDateTimeLike {
+    timeZone?: string;
+    offset?: string;
+  }
+
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal/Duration) */
+  class Duration {
+    constructor(
+      years?: number,
+      months?: number,
+      weeks?: number,
+      days?: number,
+      hours?: number,
+      minutes?: number,
+      seconds?: number,
+      milliseconds?: number,
+      microseconds?: number,
+      nanoseconds?: number,
+    );
+    static from(item: Duration | DurationLike | string): Duration;
+    /** Only durations without years, months or weeks can be compared. */
+    static compare(one: Duration | DurationLike | string, two: Duration | DurationLike | string): number;
+    readonly years: number;
+    readonly months: number;
+    readonly weeks: number;
+    readonly days: number;
+    readonly hours: number;
+    readonly minutes: number;
+    readonly seconds: number;
+    readonly milliseconds: number;
+    readonly microseconds: number;
+    readonly nanoseconds: number;
+    readonly sign: -1 | 0 | 1;
+    readonly blank: boolean;
+    with(fields: DurationLike): Duration;
+    negated(): Duration;
+    abs(): Duration;
+    /** Only durations without years, months or weeks can be added. */
+    add(other: Duration | DurationLike | string): Duration;
+    subtract(other: Duration | DurationLike | string): Duration;
+    /** `unit` must be days or smaller. */
+    total(unit: PluralUnit<"day" | TimeUnit> | { unit: PluralUnit<"day" | TimeUnit> }): number;
+    toString(): string;
+    toJSON(): string;
+    toLocaleString(): string;
+    valueOf(): never;
+  }
+
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal/Instant) */
+  class Instant {
+    constructor(epochNanoseconds: bigint);
+    static from(item: Instant | string): Instant;
+    static fromEpochMilliseconds(epochMilliseconds: number): Instant;
+    static fromEpochNanoseconds(epochNanoseconds: bigint): Instant;
+    static compare(one: Instant | string, two: Instant | string): number;
+    readonly epochMilliseconds: number;
+    readonly epochNanoseconds: bigint;
+    add(duration: Duration | DurationLike | string): Instant;
+    subtract(duration: Duration | DurationLike | string): Instant;
+    until(other: Instant | string, options?: DifferenceOptions<TimeUnit>): Duration;
+    since(other: Instant | string, options?: DifferenceOptions<TimeUnit>): Duration;
+    equals(other: Instant | string): boolean;
+    toZonedDateTimeISO(timeZone: string): ZonedDateTime;
+    toString(options?: { timeZone?: string }): string;
+    toJSON(): string;
+    toLocaleString(locales?: string | string[], options?: Intl.DateTimeFormatOptions): string;
+    valueOf(): never;
+  }
+
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal/PlainTime) */
+  class PlainTime {
+    constructor(
+      hour?: number,
+      minute?: number,
+      second?: number,
+      millisecond?: number,
+      microsecond?: number,
+      nanosecond?: number,
+    );
+    static from(item: PlainTime | PlainTimeLike | string, options?: OverflowOptions): PlainTime;
+    static compare(one: PlainTime | string, two: PlainTime | string): number;
+    readonly hour: number;
+    readonly minute: number;
+    readonly second: number;
+    readonly millisecond: number;
+    readonly microsecond: number;
+    readonly nanosecond: number;
+    with(fields: PlainTimeLike, options?: OverflowOptions): PlainTime;
+    add(duration: Duration | DurationLike | string): PlainTime;
+    subtract(duration: Duration | DurationLike | string): PlainTime;
+    until(other: PlainTime | string, options?: DifferenceOptions<TimeUnit>): Duration;
+    since(other: PlainTime | string, options?: DifferenceOptions<TimeUnit>): Duration;
+    equals(other: PlainTime | string): boolean;
+    toString(): string;
+    toJSON(): string;
+    valueOf(): never;
+  }
+
+  interface CalendarFields {
+    readonly calendarId: "iso8601";
+    readonly year: number;
+    readonly month: number;
+    readonly monthCode: string;
+    readonly day: number;
+    readonly dayOfWeek: number;
+    readonly dayOfYear: number;
+    readonly daysInWeek: number;
+    readonly daysInMonth: number;
+    readonly daysInYear: number;
+    readonly monthsInYear: number;
+    readonly inLeapYear: boolean;
+    toJSON(): string;
+    valueOf(): never;
+  }
+
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal/PlainDate) */
+  interface PlainDate extends CalendarFields {}
+  class PlainDate {
+    constructor(year: number, month: number, day: number);
+    static from(
+      item: PlainDate | PlainDateTime | ZonedDateTime | PlainDateLike | string,
+      options?: OverflowOptions,
+    ): PlainDate;
+    static compare(one: PlainDate | string, two: PlainDate | string): number;
+    with(fields: PlainDateLike, options?: OverflowOptions): PlainDate;
+    add(duration: Duration | DurationLike | string, options?: OverflowOptions): PlainDate;
+    subtract(duration: Duration | DurationLike | string, options?: OverflowOptions): PlainDate;
+    until(other: PlainDate | string, options?: DifferenceOptions<DateUnit>): Duration;
+    since(other: PlainDate | string, options?: DifferenceOptions<DateUnit>): Duration;
+    equals(other: PlainDate | string): boolean;
+    toPlainDateTime(time?: PlainTime | string): PlainDateTime;
+    toZonedDateTime(item: string | { timeZone: string; plainTime?: PlainTime | string }): ZonedDateTime;
+    toString(): string;
+  }
+
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal/PlainDateTime) */
+  interface PlainDateTime extends CalendarFields {}
+  class PlainDateTime {
+    constructor(
+      year: number,
+      month: number,
+      day: number,
+      hour?: number,
+      minute?: number,
+      second?: number,
+      millisecond?: number,
+      microsecond?: number,
+      nanosecond?: number,
+    );
+    static from(
+      item: PlainDateTime | PlainDate | ZonedDateTime | PlainDateTimeLike | string,
+      options?: OverflowOptions,
+    ): PlainDateTime;
+    static compare(one: PlainDateTime | string, two: PlainDateTime | string): number;
+    readonly hour: number;
+    readonly minute: number;
+    readonly second: number;
+    readonly millisecond: number;
+    readonly microsecond: number;
+    readonly nanosecond: number;
+    with(fields: PlainDateTimeLike, options?: OverflowOptions): PlainDateTime;
+    withPlainTime(time?: PlainTime | string): PlainDateTime;
+    add(duration: Duration | DurationLike | string, options?: OverflowOptions): PlainDateTime;
+    subtract(duration: Duration | DurationLike | string, options?: OverflowOptions): PlainDateTime;
+    until(other: PlainDateTime | string, options?: DifferenceOptions<DateUnit | TimeUnit>): Duration;
+    since(other: PlainDateTime | string, options?: DifferenceOptions<DateUnit | TimeUnit>): Duration;
+    equals(other: PlainDateTime | string): boolean;
+    toPlainDate(): PlainDate;
+    toPlainTime(): PlainTime;
+    toZonedDateTime(timeZone: string, options?: ToInstantOptions): ZonedDateTime;
+    toString(): string;
+  }
+
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal/ZonedDateTime) */
+  interface ZonedDateTime extends CalendarFields {}
+  class ZonedDateTime {
+    constructor(epochNanoseconds: bigint, timeZone: string);
+    static from(item: ZonedDateTime | ZonedDateTimeLike | string, options?: ZonedDateTimeOptions): ZonedDateTime;
+    static compare(one: ZonedDateTime | string, two: ZonedDateTime | string): number;
+    readonly timeZoneId: string;
+    readonly hour: number;
+    readonly minute: number;
+    readonly second: number;
+    readonly millisecond: number;
+    readonly microsecond: number;
+    readonly nanosecond: number;
+    /** The UTC offset, such as "+01:00" */
+    readonly offset: string;
+    readonly offsetNanoseconds: number;
+    readonly epochMilliseconds: number;
+    readonly epochNanoseconds: bigint;
+    readonly hoursInDay: number;
+    with(fields: PlainDateTimeLike & { offset?: string }, options?: ZonedDateTimeOptions): ZonedDateTime;
+    withPlainTime(time?: PlainTime | string): ZonedDateTime;
+    withTimeZone(timeZone: string): ZonedDateTime;
+    startOfDay(): ZonedDateTime;
+    /**
+     * Years, months, weeks and days move the wall clock; hours and smaller
+     * units are exact elapsed time.
+     */
+    add(duration: Duration | DurationLike | string, options?: OverflowOptions): ZonedDateTime;
+    subtract(duration: Duration | DurationLike | string, options?: OverflowOptions): ZonedDateTime;
+    until(other: ZonedDateTime | string, options?: DifferenceOptions<DateUnit | TimeUnit>): Duration;
+    since(other: ZonedDateTime | string, options?: DifferenceOptions<DateUnit | TimeUnit>): Duration;
+    equals(other: ZonedDateTime | string): boolean;
+    toInstant(): Instant;
+    toPlainDate(): PlainDate;
+    toPlainTime(): PlainTime;
+    toPlainDateTime(): PlainDateTime;
+    toString(): string;
+    toLocaleString(locales?: string | string[], options?: Intl.DateTimeFormatOptions): string;
+  }
+
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Temporal/Now) */
+  namespace Now {
+    function instant(): Instant;
+    /** The plugin's time zone, set with the `TZ` config key. Defaults to "UTC". */
+    function timeZoneId(): string;
+    function zonedDateTimeISO(timeZone?: string): ZonedDateTime;
+    function plainDateTimeISO(timeZone?: string): PlainDateTime;
+    function plainDateISO(timeZone?: string): PlainDate;
+    function plainTimeISO(timeZone?: string): PlainTime;
+  }
+}
+
+interface Date {
+  /** [MDN Reference](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Date/toTemporalInstant) */
+  toTemporalInstant(): Temporal.Instant;
+}
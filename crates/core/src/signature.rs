@@ -0,0 +1,12 @@
+//! How host function signatures are handed from the shim to the core. The CLI
+//! includes this file too, so both sides agree on the layout.
+//!
+//! A signature is packed into a u64: the parameter count, the result count,
+//! then one type code per parameter followed by one per result.
+
+/// Bits used by the parameter and result counts at the bottom of a packed signature
+pub(crate) const SIGNATURE_COUNT_BITS: u32 = 4;
+/// Bits used by each type code in a packed signature
+pub(crate) const SIGNATURE_TYPE_BITS: u32 = 3;
+/// Parameters `__invokeHostFunc` carries, as one i64 each, after the function index
+pub(crate) const MAX_HOST_PARAMS: usize = 5;
@@ -0,0 +1,162 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{DateTime, LocalResult, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::{OffsetName, Tz};
+use rquickjs::{function::MutFn, prelude::*, Ctx, Function, Object, Value};
+
+use crate::globals::{number_arg, string_arg, to_js_error};
+
+/// A time zone from the tz database, or a fixed UTC offset such as "+05:30".
+enum Zone {
+    Named(Tz),
+    Fixed(i32),
+}
+
+impl Zone {
+    /// Looks up a time zone, ignoring case the way `Intl` and `Temporal` do.
+    fn new(name: &str) -> anyhow::Result<Zone> {
+        if let Some(offset) = parse_offset(name) {
+            return Ok(Zone::Fixed(offset));
+        }
+        Tz::from_str(name)
+            .or_else(|_| Tz::from_str_insensitive(name))
+            .map(Zone::Named)
+            .map_err(|_| anyhow!("Invalid time zone: {}", name))
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Zone::Named(zone) => zone.name().to_string(),
+            Zone::Fixed(offset) => {
+                let sign = if *offset < 0 { '-' } else { '+' };
+                let minutes = offset.abs() / 60;
+                format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+
+    /// The UTC offset in seconds at the instant `epoch_ms`.
+    fn offset(&self, epoch_ms: f64) -> anyhow::Result<i32> {
+        match self {
+            Zone::Named(zone) => {
+                let offset = zone.offset_from_utc_datetime(&naive(epoch_ms)?);
+                Ok(offset.fix().local_minus_utc())
+            }
+            Zone::Fixed(offset) => Ok(*offset),
+        }
+    }
+
+    /// The UTC offsets in seconds that the wall-clock time `wall_ms` can have:
+    /// one usually, two when the clocks were set back over it and none when
+    /// they skipped it.
+    fn possible_offsets(&self, wall_ms: f64) -> anyhow::Result<Vec<i32>> {
+        let zone = match self {
+            Zone::Named(zone) => zone,
+            Zone::Fixed(offset) => return Ok(vec![*offset]),
+        };
+        let offsets = match zone.offset_from_local_datetime(&naive(wall_ms)?) {
+            LocalResult::Single(offset) => vec![offset.fix().local_minus_utc()],
+            LocalResult::Ambiguous(a, b) => {
+                vec![a.fix().local_minus_utc(), b.fix().local_minus_utc()]
+            }
+            LocalResult::None => vec![],
+        };
+        Ok(offsets)
+    }
+
+    /// The zone's abbreviation at `epoch_ms`, such as "CET", if it has one.
+    fn abbreviation(&self, epoch_ms: f64) -> anyhow::Result<Option<String>> {
+        match self {
+            Zone::Named(zone) => {
+                let offset = zone.offset_from_utc_datetime(&naive(epoch_ms)?);
+                Ok(offset.abbreviation().map(str::to_string))
+            }
+            Zone::Fixed(_) => Ok(None),
+        }
+    }
+}
+
+/// Parses "+05:30", "-0800" or "+01" into seconds east of UTC.
+fn parse_offset(name: &str) -> Option<i32> {
+    let sign = match name.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits: String = name[1..].chars().filter(|c| *c != ':').collect();
+    if !matches!(digits.len(), 2 | 4) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = if digits.len() == 4 {
+        digits[2..].parse().ok()?
+    } else {
+        0
+    };
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// `epoch_ms` as a UTC date time. For wall-clock times this is the wall clock
+/// as if it were UTC.
+fn naive(epoch_ms: f64) -> anyhow::Result<NaiveDateTime> {
+    DateTime::from_timestamp_millis(epoch_ms.floor() as i64)
+        .map(|date| date.naive_utc())
+        .ok_or_else(|| anyhow!("Time value out of range: {}", epoch_ms))
+}
+
+/// Builds the `__tz` object `Date` and `Temporal` use for time zone lookups.
+/// The tz database is compiled in by chrono-tz.
+pub(crate) fn build_tz_object<'js>(this: Ctx<'js>) -> anyhow::Result<Object<'js>> {
+    let canonicalize = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let name = string_arg(&cx, &args, 0, "time zone")?;
+            let zone = Zone::new(&name).map_err(|e| to_js_error(cx.clone(), e))?;
+            Ok::<_, rquickjs::Error>(zone.name())
+        }),
+    )?;
+
+    let get_offset = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let name = string_arg(&cx, &args, 0, "time zone")?;
+            let epoch_ms = number_arg(&cx, &args, 1, "epoch milliseconds")?;
+            Zone::new(&name)
+                .and_then(|zone| zone.offset(epoch_ms))
+                .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let get_possible_offsets = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let name = string_arg(&cx, &args, 0, "time zone")?;
+            let wall_ms = number_arg(&cx, &args, 1, "wall-clock milliseconds")?;
+            Zone::new(&name)
+                .and_then(|zone| zone.possible_offsets(wall_ms))
+                .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let abbreviation = Function::new(
+        this.clone(),
+        MutFn::new(move |cx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let name = string_arg(&cx, &args, 0, "time zone")?;
+            let epoch_ms = number_arg(&cx, &args, 1, "epoch milliseconds")?;
+            Zone::new(&name)
+                .and_then(|zone| zone.abbreviation(epoch_ms))
+                .map_err(|e| to_js_error(cx.clone(), e))
+        }),
+    )?;
+
+    let tz = Object::new(this)?;
+    tz.set("canonicalize", canonicalize)?;
+    tz.set("offset", get_offset)?;
+    tz.set("possibleOffsets", get_possible_offsets)?;
+    tz.set("abbreviation", abbreviation)?;
+    Ok(tz)
+}
@@ -1,3 +1,3 @@
 declare module "main" {
-  export function greet(): I32;
+  export function greet(): I32 | void;
 }
@@ -0,0 +1,12 @@
+// Fails to build: the top level throws while the plug-in is initialized.
+// extism-js prints the error with the line it came from and exits with 2.
+
+const settings = { retries: 3 };
+
+function greet() {
+  Host.outputString(`retries: ${settings.retries}`);
+}
+
+settings.timeout.seconds = 30;
+
+module.exports = { greet };
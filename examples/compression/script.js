@@ -0,0 +1,75 @@
+// Requires an engine built with the `compression` feature:
+//   make cli CORE_FEATURES=compression
+
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`compression: ${message}`);
+  }
+}
+
+// "hello world", compressed by another implementation
+const GZIP_HELLO = new Uint8Array([
+  31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 203, 72, 205, 201, 201, 87, 40, 207, 47, 202, 73, 1, 0, 133, 17,
+  74, 13, 11, 0, 0, 0,
+]);
+
+// made before any call, so it only opens its codec once a call writes to it
+const early = new CompressionStream("deflate");
+
+function decode(bytes) {
+  return new TextDecoder().decode(bytes);
+}
+
+async function greet() {
+  assert(decode(Host.decompress(GZIP_HELLO, "gzip")) === "hello world", "should decode foreign gzip");
+
+  const text = "stream me ".repeat(1000);
+  for (const format of ["gzip", "deflate", "deflate-raw"]) {
+    const compressed = Host.compress(text, format);
+    assert(compressed.byteLength < text.length / 10, `${format} should shrink repetitive input`);
+    assert(decode(Host.decompress(compressed, format)) === text, `${format} should round-trip`);
+  }
+
+  let caught = null;
+  try {
+    Host.compress("x", "brotli");
+  } catch (e) {
+    caught = e;
+  }
+  assert(caught instanceof TypeError, "unsupported formats should throw a TypeError");
+
+  caught = null;
+  try {
+    Host.decompress(GZIP_HELLO.subarray(0, 20), "gzip");
+  } catch (e) {
+    caught = e;
+  }
+  assert(caught !== null, "truncated input should fail to decompress");
+
+  // streams, fed in small chunks
+  const chunks = [];
+  for (let i = 0; i < text.length; i += 100) {
+    chunks.push(text.slice(i, i + 100));
+  }
+  const compressed = await new Response(
+    ReadableStream.from(chunks)
+      .pipeThrough(new TextEncoderStream())
+      .pipeThrough(new CompressionStream("gzip")),
+  ).bytes();
+  assert(decode(Host.decompress(compressed, "gzip")) === text, "CompressionStream output should be valid gzip");
+
+  const roundTrip = await new Response(
+    new Blob([compressed]).stream().pipeThrough(new DecompressionStream("gzip")),
+  ).text();
+  assert(roundTrip === text, "DecompressionStream should undo CompressionStream");
+
+  const writer = early.writable.getWriter();
+  writer.write(new TextEncoder().encode(text));
+  writer.close();
+  const earlyOutput = await new Response(early.readable).bytes();
+  assert(decode(Host.decompress(earlyOutput, "deflate")) === text, "a stream made at the top level should work in a call");
+
+  Host.outputString("compression: all tests passed");
+}
+
+module.exports = { greet };
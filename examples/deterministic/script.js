@@ -0,0 +1,28 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`deterministic: ${message}`);
+  }
+}
+
+// run with js.seed=42 and js.fixed_time=2024-01-15T12:00:00Z
+const FIXED_TIME = 1705320000000;
+
+function greet() {
+  // the clock stands still at js.fixed_time
+  assert(Date.now() === FIXED_TIME, `Date.now() returned ${Date.now()}`);
+  assert(new Date().toISOString() === "2024-01-15T12:00:00.000Z", `new Date() was ${new Date().toISOString()}`);
+  assert(performance.timeOrigin === FIXED_TIME, `performance.timeOrigin was ${performance.timeOrigin}`);
+  assert(performance.now() === 0, `performance.now() returned ${performance.now()}`);
+  assert(Temporal.Now.instant().epochMilliseconds === FIXED_TIME, "Temporal.Now should use the fixed time");
+
+  // randomness comes from a generator seeded with js.seed
+  const random = Math.random();
+  assert(random === 0.7415648787718233, `Math.random() returned ${random}`);
+  const bytes = Array.from(crypto.getRandomValues(new Uint8Array(8)));
+  assert(bytes.join() === "3,241,102,178,51,227,239,40", `getRandomValues gave ${bytes}`);
+  assert(/^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$/.test(crypto.randomUUID()), "randomUUID should still be a v4 UUID");
+
+  Host.outputString("deterministic: all tests passed");
+}
+
+module.exports = { greet };
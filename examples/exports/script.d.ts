@@ -1,5 +1,6 @@
 declare module 'main' {
   export function add3(a: I32, b: I32, c: I32): I32;
   export function appendString(a: PTR, b: PTR): PTR;
+  export function divMod(a: I32, b: I32): [I32, I32];
 }
 
@@ -12,4 +12,8 @@ function appendString(a, b) {
   return Memory.fromString(a + b).offset;
 }
 
-module.exports = { add3, appendString };
+function divMod(a, b) {
+  return [Math.trunc(a / b), a % b];
+}
+
+module.exports = { add3, appendString, divMod };
@@ -119,8 +119,149 @@ async function greet() {
     pass = false;
   }
 
+  // byte bodies are kept as-is, not round-tripped through a string
+  var binary = new Response(new Uint8Array([0xff, 0x00, 0x80]));
+  var binaryBytes = new Uint8Array(await binary.arrayBuffer());
+  if (binaryBytes.length !== 3 || binaryBytes[0] !== 0xff || binaryBytes[1] !== 0x00 || binaryBytes[2] !== 0x80) {
+    console.error("FAIL: Response.arrayBuffer() with a byte body, got:", Array.from(binaryBytes).join(","));
+    pass = false;
+  }
+
+  // Headers iteration is sorted, with each Set-Cookie separate
+  var h4 = new Headers([["B", "2"], ["a", "1"], ["Set-Cookie", "x=1"], ["Set-Cookie", "y=2"]]);
+  var iterated = [];
+  for (var pair of h4) {
+    iterated.push(pair[0] + "=" + pair[1]);
+  }
+  if (iterated.join("&") !== "a=1&b=2&set-cookie=x=1&set-cookie=y=2") {
+    console.error("FAIL: Headers iteration, got:", iterated.join("&"));
+    pass = false;
+  }
+  if (h4.getSetCookie().length !== 2 || h4.get("set-cookie") !== "x=1, y=2") {
+    console.error("FAIL: Headers.getSetCookie()");
+    pass = false;
+  }
+
   console.log("Response tests: OK");
 
+  // --- Test Request, Blob and FormData ---
+  var req = new Request("http://example.com/api", {
+    method: "post",
+    body: new URLSearchParams({ q: "extism js" }),
+  });
+  if (req.method !== "POST" || req.url !== "http://example.com/api") {
+    console.error("FAIL: Request method/url");
+    pass = false;
+  }
+  if (req.headers.get("content-type") !== "application/x-www-form-urlencoded;charset=UTF-8") {
+    console.error("FAIL: Request Content-Type for URLSearchParams, got:", req.headers.get("content-type"));
+    pass = false;
+  }
+  var reqClone = req.clone();
+  if ((await req.text()) !== "q=extism+js" || (await reqClone.formData()).get("q") !== "extism js") {
+    console.error("FAIL: Request body");
+    pass = false;
+  }
+
+  var blob = new Blob(["hello ", new Uint8Array([119, 111, 114, 108, 100])], { type: "Text/Plain" });
+  if (blob.size !== 11 || blob.type !== "text/plain" || (await blob.slice(6).text()) !== "world") {
+    console.error("FAIL: Blob");
+    pass = false;
+  }
+
+  var form = new FormData();
+  form.append("name", "extism");
+  form.append("file", blob, "hello.txt");
+  var multipart = new Response(form);
+  var contentType = multipart.headers.get("content-type");
+  if (contentType.indexOf("multipart/form-data; boundary=") !== 0) {
+    console.error("FAIL: FormData Content-Type, got:", contentType);
+    pass = false;
+  }
+  var parsed = await multipart.formData();
+  var file = parsed.get("file");
+  if (parsed.get("name") !== "extism" || !(file instanceof File) || file.name !== "hello.txt" || (await file.text()) !== "hello world") {
+    console.error("FAIL: FormData multipart round trip");
+    pass = false;
+  }
+
+  // --- Test Response statics ---
+  var jsonRes = Response.json({ ok: true }, { status: 201 });
+  if (jsonRes.status !== 201 || jsonRes.headers.get("content-type") !== "application/json" || !(await jsonRes.json()).ok) {
+    console.error("FAIL: Response.json()");
+    pass = false;
+  }
+  var redirect = Response.redirect("http://example.com/next", 301);
+  if (redirect.status !== 301 || redirect.headers.get("location") !== "http://example.com/next") {
+    console.error("FAIL: Response.redirect()");
+    pass = false;
+  }
+  var errorRes = Response.error();
+  if (errorRes.type !== "error" || errorRes.status !== 0 || errorRes.ok) {
+    console.error("FAIL: Response.error()");
+    pass = false;
+  }
+
+  console.log("Request tests: OK");
+
+  // --- Test AbortController / AbortSignal ---
+  var controller = new AbortController();
+  var abortEvents = 0;
+  controller.signal.addEventListener("abort", function () { abortEvents++; });
+  var combined = AbortSignal.any([controller.signal, new AbortController().signal]);
+  controller.abort();
+  controller.abort();
+  if (!controller.signal.aborted || abortEvents !== 1 || controller.signal.reason.name !== "AbortError") {
+    console.error("FAIL: AbortController.abort()");
+    pass = false;
+  }
+  if (!combined.aborted || combined.reason !== controller.signal.reason) {
+    console.error("FAIL: AbortSignal.any()");
+    pass = false;
+  }
+
+  var abortError = null;
+  try {
+    await fetch("http://example.com", { signal: controller.signal });
+  } catch (e) {
+    abortError = e;
+  }
+  if (!(abortError instanceof DOMException) || abortError.name !== "AbortError") {
+    console.error("FAIL: fetch with an aborted signal should reject with AbortError");
+    pass = false;
+  }
+
+  var timeout = AbortSignal.timeout(0);
+  if (!timeout.aborted || timeout.reason.name !== "TimeoutError") {
+    console.error("FAIL: AbortSignal.timeout()");
+    pass = false;
+  }
+
+  // nothing reads this signal, so it has to fire while the call waits on it
+  var timedOut = await new Promise(function (resolve) {
+    AbortSignal.timeout(20).addEventListener("abort", function (event) {
+      resolve(event.target.reason);
+    });
+  });
+  if (timedOut.name !== "TimeoutError") {
+    console.error("FAIL: AbortSignal.timeout() while awaiting");
+    pass = false;
+  }
+
+  // a timeout is waited for when a signal made from it is listened to
+  var anyReason = await new Promise(function (resolve) {
+    var signal = AbortSignal.any([AbortSignal.timeout(20)]);
+    signal.onabort = function () {
+      resolve(signal.reason);
+    };
+  });
+  if (anyReason.name !== "TimeoutError") {
+    console.error("FAIL: AbortSignal.any() of a timeout while awaiting");
+    pass = false;
+  }
+
+  console.log("Abort tests: OK");
+
   // --- Test fetch() with real HTTP (Promise-based .then) ---
   var thenContent = "";
   var thenPass = false;
@@ -183,6 +324,14 @@ async function asyncFetchTest() {
     throw new Error("FAIL: await fetch body missing expected content");
   }
   console.log("fetch GET (await): OK");
+
+  var raw = Http.request({ url: "http://example.com" });
+  if (!(raw.bodyBytes instanceof ArrayBuffer) || raw.bodyBytes.byteLength === 0) {
+    throw new Error("FAIL: Http.request bodyBytes");
+  }
+  if (raw.body !== new TextDecoder().decode(raw.bodyBytes)) {
+    throw new Error("FAIL: Http.request body should decode bodyBytes");
+  }
   return { ok: true, asyncContent: body }
 }
 
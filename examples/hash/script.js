@@ -0,0 +1,82 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`hash: ${message}`);
+  }
+}
+
+function toHex(bytes) {
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+// published test vectors
+const HELLO = {
+  "SHA3-256": "3338be694f50c5f338814986cdf0686453a888b84f424d792af4b9202398f392",
+  "SHA3-512":
+    "75d527c368f2efe848ecf6b073a36767800805e9eef2b1857d5f984f036eb6df891d75f72d9b154518c1cd58835286d1da9a38deba3de98b5a53e5ed78a84976",
+  BLAKE2b:
+    "e4cfa39a3d37be31c59609e807970799caa68a19bfaa15135f165085e01d41a65ba1e1b146aeb6bd0092b49eac214c103ccfa3a365954bbbe52f74a2b3620c94",
+  MD5: "5d41402abc4b2a76b9719d911017c592",
+  CRC32: "3610a686",
+};
+const EMPTY = {
+  BLAKE3: "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+  XXH64: "ef46db3751d8e999",
+  XXH3: "2d06800538d394c2",
+};
+
+async function greet() {
+  for (const [algorithm, expected] of Object.entries(HELLO)) {
+    const actual = toHex(Host.hash(algorithm, "hello"));
+    assert(actual === expected, `${algorithm} gave ${actual}`);
+  }
+  for (const [algorithm, expected] of Object.entries(EMPTY)) {
+    const actual = toHex(Host.hash(algorithm, new Uint8Array(0)));
+    assert(actual === expected, `${algorithm} of nothing gave ${actual}`);
+  }
+  assert(toHex(Host.hash("md5", "hello")) === HELLO.MD5, "names should be case-insensitive");
+
+  // feeding a large input in chunks matches hashing it in one go
+  const chunk = new Uint8Array(10000).fill(0x61);
+  for (const algorithm of ["SHA3-256", "BLAKE3", "CRC32", "XXH64", "XXH3", "SHA-256"]) {
+    const hash = Host.createHash(algorithm);
+    for (let i = 0; i < 10; i++) {
+      hash.update(chunk);
+    }
+    const whole = new Uint8Array(100000).fill(0x61);
+    assert(toHex(hash.digest()) === toHex(Host.hash(algorithm, whole)), `incremental ${algorithm} should match`);
+  }
+  const chunked = Host.createHash("SHA3-256");
+  for (let i = 0; i < 10; i++) {
+    chunked.update(chunk.subarray(0, 5000)).update(chunk.subarray(5000));
+  }
+  assert(
+    toHex(chunked.digest()) === "7c772c3f1ef6bb4320c0bf1597c3ddbdc4862bfd2ea2d9664208fd3ee17342c2",
+    "chunked SHA3-256 mismatch",
+  );
+
+  const done = Host.createHash("MD5");
+  done.digest();
+  let threw = false;
+  try {
+    done.update("more");
+  } catch (e) {
+    threw = true;
+  }
+  assert(threw, "a digested hash can't be updated");
+
+  threw = false;
+  try {
+    Host.createHash("SHA-0");
+  } catch (e) {
+    threw = e instanceof TypeError;
+  }
+  assert(threw, "unknown algorithms should throw a TypeError");
+
+  // the standard names also work with crypto.subtle
+  const sha3 = await crypto.subtle.digest("sha3-256", new TextEncoder().encode("hello"));
+  assert(toHex(new Uint8Array(sha3)) === HELLO["SHA3-256"], "crypto.subtle.digest should support SHA3-256");
+
+  Host.outputString("hash: all tests passed");
+}
+
+module.exports = { greet };
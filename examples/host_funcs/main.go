@@ -77,6 +77,17 @@ func voidInputs(ctx context.Context, p *extism.CurrentPlugin, stack []uint64) {
 	}
 }
 
+// Return two values at once (Host function for `divMod`)
+func divMod(ctx context.Context, p *extism.CurrentPlugin, stack []uint64) {
+	a := int32(stack[0])
+	b := int32(stack[1])
+
+	fmt.Println("Go Host: divMod received:", a, b)
+
+	stack[0] = uint64(a / b)
+	stack[1] = uint64(a % b)
+}
+
 func main() {
 	if len(os.Args) < 2 {
 		fmt.Println("Usage: go run main.go <wasm_file>")
@@ -103,6 +114,7 @@ func main() {
 		extism.NewHostFunctionWithStack("floatInputs", floatInputs, []extism.ValueType{extism.ValueTypeF64, extism.ValueTypeF32}, []extism.ValueType{extism.ValueTypeI32}),
 		extism.NewHostFunctionWithStack("floatOutput", floatOutput, []extism.ValueType{extism.ValueTypeI32}, []extism.ValueType{extism.ValueTypeF64}),
 		extism.NewHostFunctionWithStack("voidInputs", voidInputs, []extism.ValueType{extism.ValueTypeI32, extism.ValueTypeI64, extism.ValueTypeF32, extism.ValueTypeF64, extism.ValueTypeI32}, []extism.ValueType{}),
+		extism.NewHostFunctionWithStack("divMod", divMod, []extism.ValueType{extism.ValueTypeI32, extism.ValueTypeI32}, []extism.ValueType{extism.ValueTypeI32, extism.ValueTypeI32}),
 	})
 
 	if err != nil {
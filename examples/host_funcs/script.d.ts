@@ -1,5 +1,5 @@
 declare module 'main' {
-  export function greet(): I32;
+  export function greet(): I32 | void;
 }
 
 declare module 'extism:host' {
@@ -8,5 +8,6 @@ declare module 'extism:host' {
     floatInputs(p1: F64, p2: F32): I32;
     floatOutput(p1: I32): F64;
     voidInputs(p1: I32, p2: I64, p3: F32, p4: F64, p5: I32): void;
+    divMod(p1: I32, p2: I32): [I32, I32];
   }
 }
@@ -1,6 +1,6 @@
 // Extract host functions by name.
 // Note: these must be declared in the d.ts file
-const { capitalize, floatInputs, floatOutput, voidInputs } = Host.getFunctions()
+const { capitalize, floatInputs, floatOutput, voidInputs, divMod } = Host.getFunctions()
 
 function greet() {
   const name = Host.inputString();
@@ -28,6 +28,12 @@ function greet() {
 
   voidInputs(i32, i64, f32, f64, i32);
 
+  const [quotient, remainder] = divMod(17, 5);
+  console.log(`divMod result: ${quotient}, ${remainder}`);
+  if (quotient !== 3 || remainder !== 2) {
+    throw new Error(`Unexpected divMod result: ${quotient}, ${remainder}. Expected: 3, 2`);
+  }
+
   console.log("All tests passed!");
   Host.outputString(`Hello, ${capName}!`);
 }
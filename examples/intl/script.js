@@ -0,0 +1,89 @@
+// Requires an engine built with the `intl` feature and English locale data:
+//   make cli CORE_FEATURES=intl INTL_LOCALES=en
+
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`intl: ${message}`);
+  }
+}
+
+function equal(actual, expected, message) {
+  assert(actual === expected, `${message}: expected ${JSON.stringify(expected)}, got ${JSON.stringify(actual)}`);
+}
+
+function throws(fn, type, message) {
+  let caught = null;
+  try {
+    fn();
+  } catch (e) {
+    caught = e;
+  }
+  assert(caught instanceof type, message);
+}
+
+function greet() {
+  // locales
+  equal(Intl.getCanonicalLocales("EN-us")[0], "en-US", "locales should be canonicalized");
+  equal(Intl.NumberFormat.supportedLocalesOf(["en-GB", "zz"]).join(), "en-GB", "only built-in languages are supported");
+  throws(() => Intl.getCanonicalLocales("not a locale"), RangeError, "malformed locales should throw a RangeError");
+
+  // numbers
+  equal(new Intl.NumberFormat("en-US").format(1234.5), "1,234.5", "decimals should be grouped");
+  equal(Intl.NumberFormat("en-US").format(-0.12345), "-0.123", "at most three fraction digits by default");
+  equal(new Intl.NumberFormat("en-US", { useGrouping: false }).format(1234567), "1234567", "grouping can be turned off");
+  equal(
+    new Intl.NumberFormat("en-US", { minimumFractionDigits: 2 }).format(5),
+    "5.00",
+    "fraction digits should be padded",
+  );
+  equal(
+    new Intl.NumberFormat("en-US", { style: "currency", currency: "USD" }).format(1234.5),
+    "$1,234.50",
+    "currencies should use their minor digits",
+  );
+  equal(new Intl.NumberFormat("en-US", { style: "percent" }).format(0.256), "26%", "percentages should be scaled");
+  equal(new Intl.NumberFormat("en-US").format(12345678901234567890n), "12,345,678,901,234,567,890", "bigints");
+  equal((1234.5).toLocaleString("en-US"), "1,234.5", "Number#toLocaleString should use Intl");
+  throws(() => new Intl.NumberFormat("en-US", { style: "currency" }), TypeError, "currency style needs a currency");
+
+  // collation
+  const collator = new Intl.Collator("en");
+  equal(["b", "C", "a"].sort(collator.compare).join(), "a,b,C", "collation should ignore case order");
+  equal(
+    ["10", "9", "1"].sort(new Intl.Collator("en", { numeric: true }).compare).join(),
+    "1,9,10",
+    "numeric collation should compare digits as numbers",
+  );
+  equal(new Intl.Collator("en", { sensitivity: "base" }).compare("a", "A"), 0, "base sensitivity ignores case");
+  assert("résumé".localeCompare("resume", "en") > 0, "String#localeCompare should use Intl");
+
+  // plurals
+  const cardinal = new Intl.PluralRules("en");
+  equal(cardinal.select(1), "one", "1 is singular");
+  equal(cardinal.select(2), "other", "2 is plural");
+  const ordinal = new Intl.PluralRules("en", { type: "ordinal" });
+  equal(
+    [1, 2, 3, 4, 11, 22].map((n) => ordinal.select(n)).join(),
+    "one,two,few,other,other,two",
+    "ordinals should follow English suffixes",
+  );
+
+  // dates, in UTC unless a time zone is given
+  const date = new Date(Date.UTC(2024, 0, 15, 13, 5, 9));
+  equal(date.toLocaleDateString("en-US", { dateStyle: "long" }), "January 15, 2024", "long dates");
+  equal(new Intl.DateTimeFormat("en-US", { dateStyle: "medium" }).format(date), "Jan 15, 2024", "medium dates");
+  equal(new Intl.DateTimeFormat("en-US", { timeZone: "UTC" }).resolvedOptions().timeZone, "UTC", "UTC time zone");
+  const auckland = new Intl.DateTimeFormat("en-US", { dateStyle: "medium", timeZone: "pacific/auckland" });
+  equal(auckland.format(date), "Jan 16, 2024", "dates should be formatted in the time zone");
+  equal(auckland.resolvedOptions().timeZone, "Pacific/Auckland", "time zones should be canonicalized");
+  throws(
+    () => new Intl.DateTimeFormat("en-US", { timeZone: "Mars/Olympus_Mons" }),
+    RangeError,
+    "unknown time zones should throw a RangeError",
+  );
+  throws(() => new Intl.DateTimeFormat("en-US").format(NaN), RangeError, "invalid dates should throw a RangeError");
+
+  Host.outputString("intl: all tests passed");
+}
+
+module.exports = { greet };
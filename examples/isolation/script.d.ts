@@ -0,0 +1,4 @@
+declare module "main" {
+  export function isolated(): I32 | void;
+  export function shared(): I32 | void;
+}
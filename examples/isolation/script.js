@@ -0,0 +1,17 @@
+// module-level state that would leak between calls
+let calls = 0;
+
+// runs against a fresh copy of the initialized state every time
+function isolated() {
+  calls++;
+  Host.outputString(`isolated call ${calls}`);
+}
+isolated.isolated = true;
+
+// keeps its state between calls, as exports do by default
+function shared() {
+  calls++;
+  Host.outputString(`shared call ${calls}`);
+}
+
+module.exports = { isolated, shared };
@@ -0,0 +1,5 @@
+declare module "main" {
+  export function greet(): I32 | void;
+  export function denied(): I32 | void;
+  export function late(): I32 | void;
+}
@@ -0,0 +1,44 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`lifecycle: ${message}`);
+  }
+}
+
+let initCount = 0;
+let greeting;
+const calls = [];
+
+// called once, before the first export, when config can be read
+function onInit() {
+  initCount++;
+  greeting = Config.get("greeting");
+  // defined here, and isolated, so it's only found once onInit has run
+  module.exports.late = Object.assign(
+    () => Host.outputString("lifecycle: late export ran"),
+    { isolated: true },
+  );
+}
+
+// wraps every export
+async function onCall(name, next) {
+  calls.push(name);
+  if (name === "denied") {
+    throw new Error("lifecycle: denied is not allowed");
+  }
+  const result = await next();
+  calls.push(`${name} done`);
+  return result;
+}
+
+function greet() {
+  assert(initCount === 1, `onInit ran ${initCount} times`);
+  assert(greeting === "Howdy", `onInit read ${greeting}`);
+  assert(calls.join() === "greet", `onCall saw ${calls.join()}`);
+  Host.outputString("lifecycle: all tests passed");
+}
+
+function denied() {
+  Host.outputString("denied should not run");
+}
+
+module.exports = { greet, denied, onInit, onCall };
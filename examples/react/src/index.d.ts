@@ -1,4 +1,4 @@
 declare module "main" {
-  export function setState(): I32;
-  export function render(): I32;
+  export function setState(): I32 | void;
+  export function render(): I32 | void;
 }
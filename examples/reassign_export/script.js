@@ -0,0 +1,16 @@
+// The function found for an export is kept until the export is reassigned,
+// which takes effect on the next call. Keys the d.ts doesn't declare aren't
+// exports, so adding one doesn't change which function is called.
+
+function greet() {
+  Host.outputString("original export ran");
+  // sorts before `greet`, but isn't declared in the d.ts
+  module.exports.aaa = function () {
+    Host.outputString("wrong export ran");
+  };
+  module.exports.greet = function () {
+    Host.outputString("reassigned export ran");
+  };
+}
+
+module.exports = { greet };
@@ -0,0 +1,26 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`resume: ${message}`);
+  }
+}
+
+// evaluated while the plugin is wizened, then frozen into the binary
+const buildOrigin = performance.timeOrigin;
+const buildTime = Date.now();
+
+function greet() {
+  // performance is measured from when the instance started, not the build
+  assert(performance.timeOrigin >= buildTime, "timeOrigin should be reset after the snapshot");
+  assert(performance.timeOrigin > buildOrigin, "timeOrigin should move past the build");
+  const now = performance.now();
+  assert(now >= 0 && now < 60000, `performance.now() returned ${now}`);
+
+  const random = Math.random();
+  assert(random >= 0 && random < 1, `Math.random() returned ${random}`);
+
+  // the Makefile runs this twice, with js.seed set, and expects different
+  // numbers: only deterministic engines honor the seed
+  Host.outputString(`resume: all tests passed ${random}`);
+}
+
+module.exports = { greet };
@@ -0,0 +1,7 @@
+declare module "main" {
+  export function asyncGreet(): I32 | void;
+  export function badI32(): I32;
+  export function hugeI64(): I64;
+  export function missingI32(): I32;
+  export function wideI32(): I32;
+}
@@ -0,0 +1,27 @@
+// Exports declared with a numeric result must return a value of that type.
+// Anything else is reported as an error instead of silently becoming 0.
+
+function badI32() {
+  return "not a number";
+}
+
+// declared `I32`, so returning nothing means 0 with a deprecation warning
+function missingI32() {
+  Host.outputString("returned nothing");
+}
+
+function wideI32() {
+  return 2 ** 31;
+}
+
+function hugeI64() {
+  return 2n ** 64n - 1n;
+}
+
+// declared `I32 | void`, so returning nothing means success
+async function asyncGreet() {
+  await Promise.resolve();
+  Host.outputString("return_types: all tests passed");
+}
+
+module.exports = { asyncGreet, badI32, hugeI64, missingI32, wideI32 };
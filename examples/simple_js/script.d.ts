@@ -1,4 +1,4 @@
 declare module "main" {
-  export function goodbye(): I32;
-  export function greet(): I32;
+  export function goodbye(): I32 | void;
+  export function greet(): I32 | void;
 }
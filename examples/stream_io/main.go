@@ -0,0 +1,58 @@
+package main
+
+import (
+	"context"
+	"fmt"
+	"os"
+	"strings"
+
+	extism "github.com/extism/go-sdk"
+)
+
+// Calls an export that throws with its output writer still open, then
+// checks the next call on the same plugin can write output of its own.
+func main() {
+	if len(os.Args) < 2 {
+		fmt.Println("Usage: go run main.go <wasm_file>")
+		os.Exit(1)
+	}
+
+	wasmFile := os.Args[1]
+	data, err := os.ReadFile(wasmFile)
+	if err != nil {
+		fmt.Printf("Failed to read wasm file: %v\n", err)
+		os.Exit(1)
+	}
+
+	manifest := extism.Manifest{
+		Wasm: []extism.Wasm{extism.WasmData{Data: data}},
+	}
+
+	ctx := context.Background()
+	config := extism.PluginConfig{EnableWasi: true}
+	plugin, err := extism.NewPlugin(ctx, manifest, config, []extism.HostFunction{})
+	if err != nil {
+		fmt.Printf("Failed to initialize plugin: %v\n", err)
+		os.Exit(1)
+	}
+
+	_, _, err = plugin.Call("abandon", []byte("Benjamin"))
+	if err == nil || !strings.Contains(err.Error(), "abandoned mid-stream") {
+		fmt.Printf("Expected abandon to fail, got: %v\n", err)
+		os.Exit(1)
+	}
+
+	for i := 0; i < 2; i++ {
+		_, result, err := plugin.Call("greet", []byte("Benjamin"))
+		if err != nil {
+			fmt.Printf("Plugin call failed after an abandoned writer: %v\n", err)
+			os.Exit(1)
+		}
+		if string(result) != "Hello, Benjamin!" {
+			fmt.Printf("Unexpected output after an abandoned writer: %q\n", result)
+			os.Exit(1)
+		}
+	}
+
+	fmt.Println("stream_io: abandoned writer test passed")
+}
@@ -0,0 +1,4 @@
+declare module "main" {
+  export function greet(): I32 | void;
+  export function abandon(): I32 | void;
+}
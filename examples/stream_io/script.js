@@ -0,0 +1,44 @@
+// Reads the input through Host.inputReader() and writes the output through
+// Host.outputWriter() without materializing either in one buffer.
+
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`stream_io: ${message}`);
+  }
+}
+
+function greet() {
+  const input = Host.inputReader();
+  assert(input.length === 8, `expected 8 input bytes, got ${input.length}`);
+
+  const head = input.read(0, 3);
+  assert(new TextDecoder().decode(head) === "Ben", "read(0, 3) should return the first bytes");
+
+  const tail = input.read(6, 10);
+  assert(tail.length === 2, "read past the end should be truncated");
+
+  const target = new Uint8Array(8);
+  const copied = input.readInto(target.subarray(4), 2);
+  assert(copied === 4, `readInto should fill the view, copied ${copied}`);
+  assert(new TextDecoder().decode(target.subarray(4)) === "njam", "readInto should honour the view offset");
+
+  const writer = Host.outputWriter(4);
+  writer.write("Hello, ");
+  for (const chunk of input.chunks(3)) {
+    writer.write(chunk);
+  }
+  writer.write(new TextEncoder().encode("!").buffer);
+  const written = writer.close();
+  assert(written === 16, `expected 16 output bytes, got ${written}`);
+
+  assert(input.text() === "Benjamin", "text() should decode the whole input");
+}
+
+// Throws with the writer still open. The next call must be able to open its own.
+function abandon() {
+  const writer = Host.outputWriter();
+  writer.write("partial output");
+  throw new Error("abandoned mid-stream");
+}
+
+module.exports = { greet, abandon };
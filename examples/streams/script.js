@@ -0,0 +1,106 @@
+// Exercises ReadableStream, WritableStream and TransformStream, and pipes the
+// result into Host.outputStream().
+
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`streams: ${message}`);
+  }
+}
+
+async function collect(stream) {
+  const chunks = [];
+  for await (const chunk of stream) {
+    chunks.push(chunk);
+  }
+  return chunks;
+}
+
+async function greet() {
+  // reader and pull-based source
+  let next = 0;
+  const counter = new ReadableStream({
+    pull(controller) {
+      if (next === 3) {
+        controller.close();
+      } else {
+        controller.enqueue(next++);
+      }
+    },
+  });
+  const reader = counter.getReader();
+  let result = await reader.read();
+  assert(result.value === 0 && !result.done, "first read should return 0");
+  assert(counter.locked, "stream should be locked while a reader is held");
+  reader.releaseLock();
+  assert(!counter.locked, "releaseLock should unlock the stream");
+  const rest = await collect(counter);
+  assert(rest.join(",") === "1,2", `async iteration returned ${rest}`);
+
+  // tee
+  const [left, right] = ReadableStream.from(["a", "b"]).tee();
+  const both = await Promise.all([collect(left), collect(right)]);
+  assert(both[0].join("") === "ab" && both[1].join("") === "ab", "tee should copy every chunk");
+
+  // pipeThrough a TransformStream
+  const doubled = ReadableStream.from([1, 2, 3]).pipeThrough(
+    new TransformStream({
+      transform(chunk, controller) {
+        controller.enqueue(chunk * 2);
+      },
+      flush(controller) {
+        controller.enqueue(0);
+      },
+    }),
+  );
+  assert((await collect(doubled)).join(",") === "2,4,6,0", "transform and flush should run in order");
+
+  // pipeTo a WritableStream
+  const written = [];
+  await ReadableStream.from(["x", "y"]).pipeTo(
+    new WritableStream({
+      write(chunk) {
+        written.push(chunk);
+      },
+    }),
+  );
+  assert(written.join("") === "xy", "pipeTo should write every chunk");
+
+  // errors reach the reader
+  const failing = new ReadableStream({
+    start(controller) {
+      controller.error(new Error("boom"));
+    },
+  });
+  let caught = null;
+  try {
+    await failing.getReader().read();
+  } catch (e) {
+    caught = e;
+  }
+  assert(caught && caught.message === "boom", "read should reject with the stream error");
+
+  // a multibyte character split across chunks
+  const euro = new TextEncoder().encode("€uro");
+  const decoded = await collect(
+    ReadableStream.from([euro.slice(0, 1), euro.slice(1, 2), euro.slice(2)]).pipeThrough(new TextDecoderStream()),
+  );
+  assert(decoded.join("") === "€uro", `TextDecoderStream returned ${decoded.join("")}`);
+
+  const encoded = await collect(ReadableStream.from(["\ud83d", "\ude00"]).pipeThrough(new TextEncoderStream()));
+  const bytes = encoded.reduce((total, chunk) => total + chunk.byteLength, 0);
+  assert(bytes === 4, `a surrogate pair split across chunks should encode to 4 bytes, got ${bytes}`);
+
+  // Blob and Response bodies
+  const blob = new Blob(["stream", " body"]);
+  assert((await new Response(blob.stream()).text()) === "stream body", "Response should read a stream body");
+
+  const response = new Response("hello");
+  assert(response.body instanceof ReadableStream, "Response.body should be a ReadableStream");
+  const bodyChunks = await collect(response.body);
+  assert(new TextDecoder().decode(bodyChunks[0]) === "hello", "Response.body should yield the bytes");
+  assert(response.bodyUsed, "reading body should mark it used");
+
+  await ReadableStream.from(["streams: ", "all tests passed"]).pipeTo(Host.outputStream());
+}
+
+module.exports = { greet };
@@ -0,0 +1,101 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`subtle_aes: ${message}`);
+  }
+}
+
+function toHex(buffer) {
+  return Array.from(new Uint8Array(buffer), (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function range(n) {
+  return new Uint8Array(Array.from({ length: n }, (_, i) => i));
+}
+
+async function rejects(promise, name) {
+  try {
+    await promise;
+  } catch (e) {
+    return e.name === name;
+  }
+  return false;
+}
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+const message = encoder.encode("tenant payload");
+
+async function importKey(name, bytes) {
+  return crypto.subtle.importKey("raw", range(bytes), name, true, ["encrypt", "decrypt"]);
+}
+
+async function greet() {
+  // known answers computed by another implementation, with key and iv 0, 1, 2...
+  const gcm = await importKey("AES-GCM", 16);
+  assert(gcm.algorithm.name === "AES-GCM" && gcm.algorithm.length === 128, "unexpected key algorithm");
+  const gcmParams = { name: "AES-GCM", iv: range(12), additionalData: encoder.encode("aad") };
+  const sealed = await crypto.subtle.encrypt(gcmParams, gcm, message);
+  assert(toHex(sealed) === "e709c9af086fd7242aab0de557c75069e6ee3bffb4503a1426caa76dafd0", `AES-GCM gave ${toHex(sealed)}`);
+  assert(decoder.decode(await crypto.subtle.decrypt(gcmParams, gcm, sealed)) === "tenant payload", "AES-GCM should decrypt");
+
+  const tampered = new Uint8Array(sealed);
+  tampered[0] ^= 1;
+  assert(await rejects(crypto.subtle.decrypt(gcmParams, gcm, tampered), "OperationError"), "a tampered message should fail");
+  assert(
+    await rejects(crypto.subtle.decrypt({ ...gcmParams, additionalData: undefined }, gcm, sealed), "OperationError"),
+    "missing additional data should fail",
+  );
+
+  const gcm256 = await importKey("AES-GCM", 32);
+  const sealed256 = await crypto.subtle.encrypt({ name: "AES-GCM", iv: range(12) }, gcm256, message);
+  assert(toHex(sealed256) === "3367b87aab91e26bec38fbe4d08d83197a7418de79ae11ee85202e05636e", "AES-256-GCM mismatch");
+
+  const short = await crypto.subtle.encrypt({ name: "AES-GCM", iv: range(12), tagLength: 96 }, gcm256, message);
+  assert(short.byteLength === message.byteLength + 12, "tagLength should set the tag size");
+
+  const cbc = await importKey("AES-CBC", 16);
+  const cbcParams = { name: "AES-CBC", iv: range(16) };
+  const cbcSealed = await crypto.subtle.encrypt(cbcParams, cbc, message);
+  assert(toHex(cbcSealed) === "46057a566cafc061f086f7bc098ed465", `AES-CBC gave ${toHex(cbcSealed)}`);
+  assert(decoder.decode(await crypto.subtle.decrypt(cbcParams, cbc, cbcSealed)) === "tenant payload", "AES-CBC should decrypt");
+  assert(await rejects(crypto.subtle.encrypt({ name: "AES-CBC", iv: range(8) }, cbc, message), "OperationError"), "short iv");
+
+  const ctr = await importKey("AES-CTR", 16);
+  const long = new Uint8Array([...message, ...message, ...message]);
+  const ctrSealed = await crypto.subtle.encrypt({ name: "AES-CTR", counter: range(16), length: 64 }, ctr, long);
+  assert(
+    toHex(ctrSealed) === "7ef165d42f1ad03590baf837a7379e3f6c0282e0466813eff6b59c6b3fc161bd7459b4c37065c997dca6",
+    `AES-CTR gave ${toHex(ctrSealed)}`,
+  );
+
+  // with an 8-bit counter only the last byte counts up, so 0xff wraps to 0x00
+  const wrapCounter = new Uint8Array(16);
+  wrapCounter[15] = 0xff;
+  const wrapped = await crypto.subtle.encrypt({ name: "AES-CTR", counter: wrapCounter, length: 8 }, ctr, new Uint8Array(32));
+  assert(
+    toHex(wrapped) === "39bbd9edf829063d5e7e702ebea40a38c6a13b37878f5b826f4f8162a1c8d879",
+    `AES-CTR counter wrap gave ${toHex(wrapped)}`,
+  );
+
+  // generated keys round-trip through exportKey
+  const generated = await crypto.subtle.generateKey({ name: "AES-GCM", length: 256 }, true, ["encrypt", "decrypt"]);
+  const raw = await crypto.subtle.exportKey("raw", generated);
+  assert(raw.byteLength === 32, "a 256-bit key should export 32 bytes");
+  const reimported = await crypto.subtle.importKey("raw", raw, "AES-GCM", false, ["decrypt"]);
+  const iv = crypto.getRandomValues(new Uint8Array(12));
+  const roundTrip = await crypto.subtle.decrypt(
+    { name: "AES-GCM", iv },
+    reimported,
+    await crypto.subtle.encrypt({ name: "AES-GCM", iv }, generated, message),
+  );
+  assert(decoder.decode(roundTrip) === "tenant payload", "re-imported keys should decrypt");
+
+  assert(await rejects(crypto.subtle.encrypt({ name: "AES-GCM", iv }, reimported, message), "InvalidAccessError"), "decrypt-only key");
+  assert(await rejects(crypto.subtle.importKey("raw", range(20), "AES-GCM", false, ["encrypt"]), "DataError"), "bad key size");
+  assert(await rejects(crypto.subtle.generateKey({ name: "AES-CBC", length: 100 }, false, ["encrypt"]), "OperationError"), "bad length");
+  assert(await rejects(crypto.subtle.sign("AES-GCM", generated, message), "NotSupportedError"), "AES can't sign");
+
+  Host.outputString("subtle_aes: all tests passed");
+}
+
+module.exports = { greet };
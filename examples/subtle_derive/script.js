@@ -0,0 +1,143 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`subtle_derive: ${message}`);
+  }
+}
+
+function toHex(buffer) {
+  return Array.from(new Uint8Array(buffer), (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function fromHex(hex) {
+  return new Uint8Array(hex.match(/../g).map((byte) => parseInt(byte, 16)));
+}
+
+function fromBase64(text) {
+  return Uint8Array.from(atob(text), (c) => c.charCodeAt(0));
+}
+
+async function rejects(promise, name) {
+  try {
+    await promise;
+  } catch (e) {
+    return e.name === name;
+  }
+  return false;
+}
+
+const encoder = new TextEncoder();
+
+// keys generated by another implementation
+const P256_PKCS8 = fromBase64(
+  "MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAERGhRANC" +
+    "AAQNzHZIx4oxGPJhKGb+g+8Z9AME9iM5nhIRoQ8rPRwFzzDoAnZ3vLkSlD6Z/3EUDqm8JHE74bdcMf9AVHJMT50A",
+);
+const P256_PEER = fromBase64(
+  "BKYvBI82c1mAnC1GwgSdfXvyaMPAc8RydTyxiiSorSCxysz4EEtmZ5XH812sncREs8LGGXgZjEmFmVW5mVbaXts=",
+);
+// Alice's private key and Bob's public key from RFC 7748
+const X25519_PKCS8 = fromBase64("MC4CAQAwBQYDK2VuBCIEIHcHbQpzGKV9PBbBclGyZkXfTC+H68CZKrF3+6UduSwq");
+const X25519_PEER = fromHex("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+
+async function greet() {
+  const password = await crypto.subtle.importKey("raw", encoder.encode("password"), "PBKDF2", false, [
+    "deriveBits",
+    "deriveKey",
+  ]);
+  const pbkdf2 = { name: "PBKDF2", hash: "SHA-256", salt: encoder.encode("salt"), iterations: 100000 };
+  const stretched = await crypto.subtle.deriveBits(pbkdf2, password, 256);
+  assert(
+    toHex(stretched) === "0394a2ede332c9a13eb82e9b24631604c31df978b4e2f0fbd2c549944f9d79a5",
+    `PBKDF2 gave ${toHex(stretched)}`,
+  );
+  const sha1 = await crypto.subtle.deriveBits({ ...pbkdf2, hash: "SHA-1", iterations: 1 }, password, 160);
+  assert(toHex(sha1) === "0c60c80f961f0e71f3a9b524af6012062fe037a6", `PBKDF2-SHA-1 gave ${toHex(sha1)}`);
+
+  // a password-derived key encrypts and decrypts like any other AES key
+  const aes = await crypto.subtle.deriveKey(pbkdf2, password, { name: "AES-GCM", length: 256 }, true, [
+    "encrypt",
+    "decrypt",
+  ]);
+  assert(aes.algorithm.name === "AES-GCM" && aes.algorithm.length === 256, "unexpected derived key");
+  assert(toHex(await crypto.subtle.exportKey("raw", aes)) === toHex(stretched), "deriveKey should match deriveBits");
+  const iv = new Uint8Array(12);
+  const sealed = await crypto.subtle.encrypt({ name: "AES-GCM", iv }, aes, encoder.encode("envelope"));
+  const opened = await crypto.subtle.decrypt({ name: "AES-GCM", iv }, aes, sealed);
+  assert(new TextDecoder().decode(opened) === "envelope", "derived AES key should round-trip");
+
+  // RFC 5869 test case 1
+  const ikm = await crypto.subtle.importKey("raw", new Uint8Array(22).fill(0x0b), "HKDF", false, ["deriveBits"]);
+  const hkdf = {
+    name: "HKDF",
+    hash: "SHA-256",
+    salt: fromHex("000102030405060708090a0b0c"),
+    info: fromHex("f0f1f2f3f4f5f6f7f8f9"),
+  };
+  const okm = await crypto.subtle.deriveBits(hkdf, ikm, 42 * 8);
+  assert(
+    toHex(okm) === "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+    `HKDF gave ${toHex(okm)}`,
+  );
+  assert(await rejects(crypto.subtle.deriveBits(hkdf, ikm, 255 * 32 * 8 + 8), "OperationError"), "HKDF output is limited");
+  assert(await rejects(crypto.subtle.deriveKey(hkdf, ikm, { name: "HMAC", hash: "SHA-256" }, false, ["sign"]), "InvalidAccessError"), "key usages are checked");
+
+  const ecdh = { name: "ECDH", namedCurve: "P-256" };
+  const ours = await crypto.subtle.importKey("pkcs8", P256_PKCS8, ecdh, false, ["deriveBits"]);
+  const theirs = await crypto.subtle.importKey("raw", P256_PEER, ecdh, true, []);
+  const shared = await crypto.subtle.deriveBits({ name: "ECDH", public: theirs }, ours, 256);
+  assert(
+    toHex(shared) === "19cfbcaa5b6f16b6d0d45634acd7aaa05b764a17e520771158a8db8688ba1f87",
+    `ECDH gave ${toHex(shared)}`,
+  );
+  const truncated = await crypto.subtle.deriveBits({ name: "ECDH", public: theirs }, ours, 12);
+  assert(toHex(truncated) === "19c0", `truncated ECDH gave ${toHex(truncated)}`);
+
+  const x25519 = await crypto.subtle.importKey("pkcs8", X25519_PKCS8, "X25519", false, ["deriveBits"]);
+  const x25519Peer = await crypto.subtle.importKey("raw", X25519_PEER, "X25519", true, []);
+  const xShared = await crypto.subtle.deriveBits({ name: "X25519", public: x25519Peer }, x25519, null);
+  assert(
+    toHex(xShared) === "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742",
+    `X25519 gave ${toHex(xShared)}`,
+  );
+  assert(
+    await rejects(crypto.subtle.deriveBits({ name: "ECDH", public: x25519Peer }, ours, 256), "InvalidAccessError"),
+    "keys from another algorithm should be rejected",
+  );
+
+  // both sides of a generated exchange agree on the same wrapping key
+  for (const algorithm of [ecdh, "X25519"]) {
+    const alice = await crypto.subtle.generateKey(algorithm, false, ["deriveKey"]);
+    const bob = await crypto.subtle.generateKey(algorithm, false, ["deriveKey"]);
+    assert(alice.publicKey.extractable && alice.publicKey.usages.length === 0, "public keys have no usages");
+    const name = alice.privateKey.algorithm.name;
+    const hmac = { name: "HMAC", hash: "SHA-256", length: 256 };
+    const aliceKey = await crypto.subtle.deriveKey({ name, public: bob.publicKey }, alice.privateKey, hmac, true, ["sign"]);
+    const bobKey = await crypto.subtle.deriveKey({ name, public: alice.publicKey }, bob.privateKey, hmac, true, ["sign"]);
+    assert(
+      toHex(await crypto.subtle.exportKey("raw", aliceKey)) === toHex(await crypto.subtle.exportKey("raw", bobKey)),
+      `${name} peers should agree`,
+    );
+    const publicRaw = await crypto.subtle.exportKey("raw", alice.publicKey);
+    assert(publicRaw.byteLength === (name === "ECDH" ? 65 : 32), `${name} public keys export as raw`);
+  }
+
+  assert(
+    await rejects(crypto.subtle.importKey("raw", encoder.encode("password"), "PBKDF2", true, ["deriveBits"]), "SyntaxError"),
+    "PBKDF2 keys can't be extractable",
+  );
+  assert(
+    await rejects(crypto.subtle.deriveBits({ ...pbkdf2, iterations: 0 }, password, 256), "OperationError"),
+    "PBKDF2 needs iterations",
+  );
+  assert(
+    await rejects(crypto.subtle.deriveBits({ ...pbkdf2, iterations: 0.5 }, password, 256), "OperationError"),
+    "PBKDF2 iterations must be a whole number",
+  );
+  assert(await rejects(crypto.subtle.deriveBits(pbkdf2, password, 0), "OperationError"), "PBKDF2 needs a length");
+  assert(await rejects(crypto.subtle.deriveBits(pbkdf2, password, 2 ** 40), "OperationError"), "PBKDF2 length is limited");
+  assert(await rejects(crypto.subtle.deriveBits(hkdf, ikm, 0), "OperationError"), "HKDF needs a length");
+
+  Host.outputString("subtle_derive: all tests passed");
+}
+
+module.exports = { greet };
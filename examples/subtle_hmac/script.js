@@ -0,0 +1,84 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`subtle_hmac: ${message}`);
+  }
+}
+
+function toHex(buffer) {
+  return Array.from(new Uint8Array(buffer), (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function fromHex(hex) {
+  return new Uint8Array(hex.match(/../g).map((byte) => parseInt(byte, 16)));
+}
+
+async function rejects(promise, name) {
+  try {
+    await promise;
+  } catch (e) {
+    return e.name === name;
+  }
+  return false;
+}
+
+const encoder = new TextEncoder();
+const message = encoder.encode("The quick brown fox jumps over the lazy dog");
+
+// HMAC("key", message) for each hash
+const EXPECTED = {
+  "SHA-1": "de7c9b85b8b78aa6bc8a7a36f70a90701c9db4d9",
+  "SHA-256": "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+  "SHA-384":
+    "d7f4727e2c0b39ae0f1e40cc96f60242d5b7801841cea6fc592c5d3e1ae50700582a96cf35e1e554995fe4e03381c237",
+  "SHA-512":
+    "b42af09057bac1e2d41708e48a902e09b5ff7f12ab428a4fe86653c73dd248fb82f948a549f7b791a5b41915ee4d1ec3935357e4e2317250d0372afa2ebeeb3a",
+};
+
+async function greet() {
+  for (const hash of Object.keys(EXPECTED)) {
+    const key = await crypto.subtle.importKey("raw", encoder.encode("key"), { name: "HMAC", hash }, false, [
+      "sign",
+      "verify",
+    ]);
+    assert(key instanceof CryptoKey, "importKey should return a CryptoKey");
+    assert(key.algorithm.hash.name === hash && key.algorithm.length === 24, `unexpected algorithm for ${hash}`);
+
+    const signature = await crypto.subtle.sign("HMAC", key, message);
+    assert(toHex(signature) === EXPECTED[hash], `${hash} signature was ${toHex(signature)}`);
+    assert(await crypto.subtle.verify("HMAC", key, signature, message), `${hash} should verify`);
+  }
+
+  // webhook-style check against a hex signature header
+  const secret = await crypto.subtle.importKey(
+    "raw",
+    encoder.encode("key"),
+    { name: "hmac", hash: { name: "sha-256" } },
+    false,
+    ["verify"],
+  );
+  const header = fromHex(EXPECTED["SHA-256"]);
+  assert(await crypto.subtle.verify({ name: "HMAC" }, secret, header, message), "header signature should verify");
+  header[0] ^= 1;
+  assert(!(await crypto.subtle.verify("HMAC", secret, header, message)), "a tampered signature should fail");
+  assert(!(await crypto.subtle.verify("HMAC", secret, header.subarray(1), message)), "a short signature should fail");
+  assert(await rejects(crypto.subtle.sign("HMAC", secret, message), "InvalidAccessError"), "verify-only keys can't sign");
+
+  const generated = await crypto.subtle.generateKey({ name: "HMAC", hash: "SHA-512" }, true, ["sign"]);
+  assert(generated.algorithm.length === 1024, "generated keys default to the hash block size");
+  const raw = await crypto.subtle.exportKey("raw", generated);
+  assert(raw.byteLength === 128, `exported ${raw.byteLength} bytes`);
+  assert(await rejects(crypto.subtle.exportKey("raw", secret), "InvalidAccessError"), "non-extractable keys can't be exported");
+
+  assert(
+    await rejects(crypto.subtle.importKey("raw", raw, { name: "HMAC", hash: "MD5" }, false, ["sign"]), "NotSupportedError"),
+    "unsupported hashes should be rejected",
+  );
+  assert(
+    await rejects(crypto.subtle.importKey("raw", raw, { name: "HMAC", hash: "SHA-256" }, false, ["encrypt"]), "SyntaxError"),
+    "invalid usages should be rejected",
+  );
+
+  Host.outputString("subtle_hmac: all tests passed");
+}
+
+module.exports = { greet };
@@ -0,0 +1,170 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`subtle_sign: ${message}`);
+  }
+}
+
+function toHex(buffer) {
+  return Array.from(new Uint8Array(buffer), (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function fromBase64(text) {
+  return Uint8Array.from(atob(text), (c) => c.charCodeAt(0));
+}
+
+async function rejects(promise, name) {
+  try {
+    await promise;
+  } catch (e) {
+    return e.name === name;
+  }
+  return false;
+}
+
+const message = new TextEncoder().encode("tenant payload");
+
+// keys and signatures generated by another implementation
+const ES256_SPKI = fromBase64(
+  "MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEn62Erq4Iu+9/AQAU2CzvagneKwz4cbXODE8dE6WaWTQHy0V2nxBw" +
+  "4sJHD+Wxv+YxM8CwzcZOpL83kajsKgf9Tw==",
+);
+const ES256_PKCS8 = fromBase64(
+  "MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAEjRWeJCr" +
+  "ze+hRANCAASfrYSurgi7738BABTYLO9qCd4rDPhxtc4MTx0TpZpZNAfLRXafEHDiwkcP5bG/5jEzwLDNxk6kvzeR" +
+  "qOwqB/1P",
+);
+const ES256_SIGNATURE = fromBase64("y7z4o8YDj7GB866p2UZEa9VUUn5JbM2dZrN5OfTcOzEN0gIhODrUK+YPaUS6v2HQNRxcLB+B27zaOuncIF8Zvg==");
+const ES256_JWK = {
+  kty: "EC",
+  crv: "P-256",
+  x: "n62Erq4Iu-9_AQAU2CzvagneKwz4cbXODE8dE6WaWTQ",
+  y: "B8tFdp8QcOLCRw_lsb_mMTPAsM3GTqS_N5Go7CoH_U8",
+  d: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAEjRWeJCrze8",
+};
+const ES384_RAW = fromBase64(
+  "BP2oWfgr+qoFlua74UAkRg48LhnAOrsmvZqTpaRhzNdrX09xi1FljjkUIlzQYFMVIwFf2O5UHA8UVRpWKfm1wXgD" +
+  "zs8749M+KdYWfri6tNfcuuCHasd8tGf9IO2wRvauDQ==",
+);
+const ES384_SIGNATURE = fromBase64(
+  "o/B5V2OF9AGlVubuoHYeDZzObIVV0DV9UvLXbjZKuqXu4TyLm/NNObXE7xbiYEveElOFbGVzdktVkKK3tonj4B74" +
+  "z2FwBkRcPykztQcq399w+65O/oHrlQWjh5iunY30",
+);
+const ED25519_PKCS8 = fromBase64("MC4CAQAwBQYDK2VwBCIEIAABAgMEBQYHCAkKCwwNDg8QERITFBUWFxgZGhscHR4f");
+const ED25519_RAW = fromBase64("A6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg=");
+const ED25519_SIGNATURE =
+  "90567dc98dcf289e1c3b404cd36734011d647f7ed7a1fa24597efe86013447f893578de3e58803c7" +
+  "cd8e5c6d2c5762828db53358735501318b41ac3728244406";
+const RSA_PKCS8 = fromBase64(
+  "MIICdwIBADANBgkqhkiG9w0BAQEFAASCAmEwggJdAgEAAoGBAK34upDAPJ/ZWg8tQDKQNa45QHROyXa0uleN3St2" +
+  "GCu82FsdGmrn08Xyq53CNa3EZw+Gaw8DjJwr1rLbuYpOkd8hSNC47ml0Hs3f46Z5NgP9y8X+8L4lQne6tFPgDruC" +
+  "h7e/BonsR2t356cP3N7e8K3GZt2Um37x9iKLgPM2QdQ9AgMBAAECgYBjvKkYu10GN1GOSd74HfzoeSIei9ysFUiP" +
+  "UWFuvTF2Qbm34qN2jpm2bj09IE4t5N9A40sVPG+sSOW5fIaOaCTv2My7Y3mUd2xhTYq7A4b1MkrI9/Rca9ZcsSVI" +
+  "RBCzpGf3oShviEuQ4wp6WKMtHVF4Fz0rWpvExptg98Pi4j8m8QJBANlJ+hHjvqrBRppydjRChKyh69J0XSjjMc0x" +
+  "ojHMB/xtH8wRlYvMx39IPyr6TFfDCSR1iYtmj2KzJgTnYo8rdsMCQQDM9ybHYs7dT0vz5Z2EP+LlWG9YaSl3pt3f" +
+  "wslh/w7YeBpad+gwb5Ez0sxOGop1F0/r2Q3igvgZ7kFuOonTFtj/AkBtznuoVL5DDn8+h7+Cc2cW0pEY/qMhiYbe" +
+  "WknL/n6+qurZtr0rM+ZRRVJexrST2UUifp9uqfql90CsU74z+dP/AkEAtrNwG7bV0qwGdgetGt+frvPffJ1w2zkg" +
+  "+X416GZko0yboRzVGae5dMpoVw6NcMYw9XhrSgeYg/S+2jTP+huZnQJBAIXXwvglweOqgKmpXZmL2DZLf5GxPVqD" +
+  "Mg/KaLROHysYJJrhtj3dWAPR+tjQGQTh2tZ6vWx0qafFLukNDATByfI=",
+);
+const RSA_SPKI = fromBase64(
+  "MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQCt+LqQwDyf2VoPLUAykDWuOUB0Tsl2tLpXjd0rdhgrvNhbHRpq" +
+  "59PF8qudwjWtxGcPhmsPA4ycK9ay27mKTpHfIUjQuO5pdB7N3+OmeTYD/cvF/vC+JUJ3urRT4A67goe3vwaJ7Edr" +
+  "d+enD9ze3vCtxmbdlJt+8fYii4DzNkHUPQIDAQAB",
+);
+const RS256_SIGNATURE =
+  "4291ef5c7b9fea26d2245e7bd47fae2844b87333d109048333c71eab4be5dcc825c119693217041f" +
+  "41332e59da822ea003563345d8ad4041db47076839dfc2f619e10303c4406971a8d301b948ff9b55" +
+  "2964d88bcb06d79898e280a295628919532c9350251d8528090bdd250a0be6945d043463bb4a8354" +
+  "7b1a553de4b3c67b";
+const PS256_SIGNATURE = fromBase64(
+  "mV5rYi9hRBzyfNLqVg9kTArNurMXxQgmHw+k6EcYAg284cyLoJcjcuekSmrq3CZycxGB29PAtVa5bBOTPzAshtEk" +
+  "NYxEAZVe70wSSMXra5UQmaWATc7diG2aY6bvFqC7KHvz0FadHk0a9b2tawywVLZT8JBHODYqjVFr0Q2vm6A=",
+);
+const RSA_JWK_N =
+  "rfi6kMA8n9laDy1AMpA1rjlAdE7JdrS6V43dK3YYK7zYWx0aaufTxfKrncI1rcRnD4ZrDwOMnCvWstu5" +
+  "ik6R3yFI0LjuaXQezd_jpnk2A_3Lxf7wviVCd7q0U-AOu4KHt78GiexHa3fnpw_c3t7wrcZm3ZSbfvH2" +
+  "IouA8zZB1D0";
+
+async function greet() {
+  const es256 = { name: "ECDSA", namedCurve: "P-256" };
+  const es256Public = await crypto.subtle.importKey("spki", ES256_SPKI, es256, true, ["verify"]);
+  assert(es256Public.type === "public" && es256Public.algorithm.namedCurve === "P-256", "unexpected ECDSA key");
+  const ecdsaSha256 = { name: "ECDSA", hash: "SHA-256" };
+  assert(await crypto.subtle.verify(ecdsaSha256, es256Public, ES256_SIGNATURE, message), "ES256 signature should verify");
+
+  const tampered = ES256_SIGNATURE.slice();
+  tampered[10] ^= 1;
+  assert(!(await crypto.subtle.verify(ecdsaSha256, es256Public, tampered, message)), "a tampered signature should not verify");
+  assert(!(await crypto.subtle.verify(ecdsaSha256, es256Public, tampered.slice(1), message)), "a short signature should not verify");
+
+  // ECDSA signatures are randomized, so check that fresh ones verify
+  const es256Private = await crypto.subtle.importKey("pkcs8", ES256_PKCS8, es256, false, ["sign"]);
+  const signed = await crypto.subtle.sign(ecdsaSha256, es256Private, message);
+  assert(signed.byteLength === 64, "ES256 signatures should be r || s");
+  assert(await crypto.subtle.verify(ecdsaSha256, es256Public, signed, message), "a fresh ES256 signature should verify");
+
+  const jwkPrivate = await crypto.subtle.importKey("jwk", ES256_JWK, es256, false, ["sign"]);
+  const { d, ...publicJwk } = ES256_JWK;
+  const jwkPublic = await crypto.subtle.importKey("jwk", publicJwk, es256, true, ["verify"]);
+  const jwkSigned = await crypto.subtle.sign(ecdsaSha256, jwkPrivate, message);
+  assert(await crypto.subtle.verify(ecdsaSha256, jwkPublic, jwkSigned, message), "JWK keys should round-trip");
+  assert(
+    toHex(await crypto.subtle.exportKey("raw", jwkPublic)) === toHex(await crypto.subtle.exportKey("raw", es256Public)),
+    "JWK and SPKI public keys should match",
+  );
+  assert(
+    await rejects(crypto.subtle.importKey("jwk", { ...publicJwk, crv: "P-384" }, es256, true, ["verify"]), "DataError"),
+    "a JWK for another curve should be rejected",
+  );
+
+  const es384 = await crypto.subtle.importKey("raw", ES384_RAW, { name: "ECDSA", namedCurve: "P-384" }, true, ["verify"]);
+  assert(
+    await crypto.subtle.verify({ name: "ECDSA", hash: "SHA-384" }, es384, ES384_SIGNATURE, message),
+    "ES384 signature should verify",
+  );
+
+  const ed25519Private = await crypto.subtle.importKey("pkcs8", ED25519_PKCS8, "Ed25519", false, ["sign"]);
+  const ed25519Public = await crypto.subtle.importKey("raw", ED25519_RAW, "Ed25519", true, ["verify"]);
+  const edSigned = await crypto.subtle.sign("Ed25519", ed25519Private, message);
+  assert(toHex(edSigned) === ED25519_SIGNATURE, `Ed25519 gave ${toHex(edSigned)}`);
+  assert(await crypto.subtle.verify("Ed25519", ed25519Public, edSigned, message), "Ed25519 signature should verify");
+
+  const rs256 = { name: "RSASSA-PKCS1-v1_5", hash: "SHA-256" };
+  const rsaPrivate = await crypto.subtle.importKey("pkcs8", RSA_PKCS8, rs256, false, ["sign"]);
+  const rsaPublic = await crypto.subtle.importKey("spki", RSA_SPKI, rs256, true, ["verify"]);
+  assert(rsaPublic.algorithm.modulusLength === 1024, "unexpected modulus length");
+  assert(toHex(rsaPublic.algorithm.publicExponent) === "010001", "unexpected public exponent");
+  const rsSigned = await crypto.subtle.sign("RSASSA-PKCS1-v1_5", rsaPrivate, message);
+  assert(toHex(rsSigned) === RS256_SIGNATURE, `RS256 gave ${toHex(rsSigned)}`);
+  assert(await crypto.subtle.verify("RSASSA-PKCS1-v1_5", rsaPublic, rsSigned, message), "RS256 signature should verify");
+  assert(toHex(await crypto.subtle.exportKey("spki", rsaPublic)) === toHex(RSA_SPKI), "SPKI export should round-trip");
+
+  const rsaJwk = await crypto.subtle.importKey("jwk", { kty: "RSA", n: RSA_JWK_N, e: "AQAB" }, rs256, true, ["verify"]);
+  assert(await crypto.subtle.verify("RSASSA-PKCS1-v1_5", rsaJwk, rsSigned, message), "RSA JWK should verify");
+
+  const ps256 = { name: "RSA-PSS", hash: "SHA-256" };
+  const pssPublic = await crypto.subtle.importKey("spki", RSA_SPKI, ps256, true, ["verify"]);
+  const pss = { name: "RSA-PSS", saltLength: 32 };
+  assert(await crypto.subtle.verify(pss, pssPublic, PS256_SIGNATURE, message), "PS256 signature should verify");
+  const pssPrivate = await crypto.subtle.importKey("pkcs8", RSA_PKCS8, ps256, false, ["sign"]);
+  const pssSigned = await crypto.subtle.sign(pss, pssPrivate, message);
+  assert(await crypto.subtle.verify(pss, pssPublic, pssSigned, message), "a fresh PS256 signature should verify");
+
+  assert(
+    await rejects(crypto.subtle.verify(ecdsaSha256, rsaPublic, signed, message), "InvalidAccessError"),
+    "keys should only be used with their own algorithm",
+  );
+  assert(
+    await rejects(crypto.subtle.importKey("spki", RSA_SPKI, es256, true, ["verify"]), "DataError"),
+    "an RSA key is not an EC key",
+  );
+  assert(
+    await rejects(crypto.subtle.importKey("spki", ES256_SPKI, es256, true, ["sign"]), "SyntaxError"),
+    "public keys can't sign",
+  );
+  assert(await rejects(crypto.subtle.exportKey("pkcs8", es256Public), "NotSupportedError"), "unsupported export");
+
+  Host.outputString("subtle_sign: all tests passed");
+}
+
+module.exports = { greet };
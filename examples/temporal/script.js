@@ -0,0 +1,77 @@
+// Run with the plugin's time zone set through config:
+//   extism call examples/temporal.wasm greet --wasi --config TZ=America/New_York
+
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`temporal: ${message}`);
+  }
+}
+
+function equal(actual, expected, message) {
+  assert(actual === expected, `${message}: expected ${String(expected)}, got ${String(actual)}`);
+}
+
+function throws(fn, type, message) {
+  let caught = null;
+  try {
+    fn();
+  } catch (e) {
+    caught = e;
+  }
+  assert(caught instanceof type, message);
+}
+
+function greet() {
+  // Date uses the configured time zone for local time
+  equal(Temporal.Now.timeZoneId(), "America/New_York", "TZ config should set the local time zone");
+  const winter = new Date(Date.UTC(2024, 0, 15, 13, 5, 9));
+  equal(winter.getTimezoneOffset(), 300, "EST is five hours behind UTC");
+  equal(winter.getHours(), 8, "local getters should use the time zone");
+  equal(winter.toString(), "Mon Jan 15 2024 08:05:09 GMT-0500 (EST)", "toString should show the offset");
+  equal(new Date(Date.UTC(2024, 6, 1)).getTimezoneOffset(), 240, "EDT is four hours behind UTC");
+  equal(new Date(2024, 6, 1, 10).toISOString(), "2024-07-01T14:00:00.000Z", "components are local time");
+  equal(new Date("2024-07-01T10:00").toISOString(), "2024-07-01T14:00:00.000Z", "ISO date-times are local time");
+  equal(new Date("2024-07-01").toISOString(), "2024-07-01T00:00:00.000Z", "ISO dates are UTC");
+  const skipped = new Date(2024, 2, 10, 2, 30);
+  equal(skipped.getHours(), 3, "times skipped by DST should move forward");
+
+  // Temporal
+  const date = Temporal.PlainDate.from("2024-01-31");
+  equal(date.add({ months: 1 }).toString(), "2024-02-29", "month arithmetic should clamp the day");
+  equal(
+    date.until("2025-03-15", { largestUnit: "years" }).toString(),
+    "P1Y1M15D",
+    "date differences should balance to years",
+  );
+  equal(Temporal.PlainTime.from("23:30").add({ hours: 1 }).toString(), "00:30:00", "times should wrap");
+  equal(Temporal.Duration.from("PT1H30M").total("minutes"), 90, "durations should total");
+
+  const instant = Temporal.Instant.from("2024-03-10T06:30:00.123456789Z");
+  equal(instant.epochNanoseconds, 1710052200123456789n, "instants keep nanoseconds");
+  equal(instant.toString({ timeZone: "Asia/Kolkata" }), "2024-03-10T12:00:00.123456789+05:30", "instants format in zones");
+
+  const evening = Temporal.ZonedDateTime.from("2024-03-09T02:30[America/New_York]");
+  equal(evening.add({ days: 1 }).toString(), "2024-03-10T03:30:00-04:00[America/New_York]", "days skip the DST gap");
+  equal(evening.add({ hours: 24 }).hour, 3, "hours are exact");
+  equal(evening.add({ days: 1 }).hoursInDay, 23, "the DST day is 23 hours long");
+  equal(evening.withTimeZone("Europe/Paris").toString(), "2024-03-09T08:30:00+01:00[Europe/Paris]", "zone conversion");
+  equal(
+    evening.until("2024-03-11T02:30[America/New_York]", { largestUnit: "days" }).toString(),
+    "P2D",
+    "calendar days across DST",
+  );
+  equal(evening.until("2024-03-11T02:30[America/New_York]").toString(), "PT47H", "hours across DST");
+  equal(
+    Temporal.ZonedDateTime.from("2024-11-03T01:30-05:00[America/New_York]").offset,
+    "-05:00",
+    "offsets pick the repeated hour",
+  );
+  equal(Temporal.ZonedDateTime.from("2024-06-01T12:00[+05:30]").offsetNanoseconds, 19800e9, "fixed offset zones");
+  equal(winter.toTemporalInstant().epochMilliseconds, winter.getTime(), "Date converts to Instant");
+  throws(() => Temporal.Now.zonedDateTimeISO("Mars/Olympus_Mons"), RangeError, "unknown zones throw RangeError");
+  throws(() => Temporal.PlainDate.from("2024-02-30"), RangeError, "invalid dates throw RangeError");
+
+  Host.outputString("temporal: all tests passed");
+}
+
+module.exports = { greet };
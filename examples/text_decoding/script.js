@@ -0,0 +1,89 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`text_decoding: ${message}`);
+  }
+}
+
+function decode(label, bytes, options) {
+  return new TextDecoder(label, options).decode(new Uint8Array(bytes));
+}
+
+function throws(fn, type) {
+  try {
+    fn();
+  } catch (e) {
+    return e instanceof type;
+  }
+  return false;
+}
+
+async function greet() {
+  // labels resolve to the standard encoding names
+  assert(new TextDecoder("latin1").encoding === "windows-1252", "latin1 should be windows-1252");
+  assert(new TextDecoder(" Shift_JIS ").encoding === "shift_jis", "labels should be trimmed and case-insensitive");
+  assert(new TextDecoder("utf-16").encoding === "utf-16le", "utf-16 should be utf-16le");
+  assert(new TextDecoder().encoding === "utf-8", "the default should be utf-8");
+  assert(throws(() => new TextDecoder("ebcdic"), RangeError), "unknown labels should throw a RangeError");
+  assert(throws(() => new TextDecoder("iso-2022-kr"), RangeError), "the replacement encoding should be rejected");
+
+  const cases = [
+    ["windows-1252", [0x63, 0x61, 0x66, 0xe9, 0x20, 0x80], "café €"],
+    ["iso-8859-2", [0xb1, 0xe6], "ąć"],
+    ["shift_jis", [0x93, 0xfa, 0x96, 0x7b], "日本"],
+    ["euc-jp", [0xc6, 0xfc, 0xcb, 0xdc], "日本"],
+    ["gbk", [0xd6, 0xd0, 0xce, 0xc4], "中文"],
+    ["big5", [0xa4, 0xa4, 0xa4, 0xe5], "中文"],
+    ["utf-16be", [0x00, 0x68, 0x00, 0x69], "hi"],
+  ];
+  for (const [label, bytes, expected] of cases) {
+    const actual = decode(label, bytes);
+    assert(actual === expected, `${label} decoded to ${actual}`);
+  }
+
+  // a BOM for the decoder's encoding is stripped unless ignoreBOM is set
+  const utf16 = [0xff, 0xfe, 0x68, 0x00, 0x69, 0x00];
+  assert(decode("utf-16le", utf16) === "hi", "the UTF-16LE BOM should be stripped");
+  assert(decode("utf-16le", utf16, { ignoreBOM: true }) === "﻿hi", "ignoreBOM should keep the BOM");
+
+  // invalid input is replaced, or throws a TypeError when fatal
+  assert(decode("shift_jis", [0x61, 0x82]) === "a�", "invalid input should be replaced");
+  assert(
+    throws(() => decode("shift_jis", [0x61, 0x82], { fatal: true }), TypeError),
+    "fatal decoders should throw a TypeError"
+  );
+  assert(
+    throws(() => decode("utf-16le", [0x68, 0x00, 0x69], { fatal: true }), TypeError),
+    "a truncated UTF-16 code unit should be fatal"
+  );
+
+  // streaming keeps sequences split across calls
+  const decoder = new TextDecoder("shift_jis");
+  let text = decoder.decode(new Uint8Array([0x93]), { stream: true });
+  text += decoder.decode(new Uint8Array([0xfa, 0x96]), { stream: true });
+  text += decoder.decode(new Uint8Array([0x7b]));
+  assert(text === "日本", `streamed shift_jis decoded to ${text}`);
+  assert(decoder.decode(new Uint8Array([0x93, 0xfa])) === "日", "a decoder should be reusable after a stream");
+
+  const utf8 = new TextDecoder();
+  text = utf8.decode(new Uint8Array([0xe2, 0x82]), { stream: true });
+  text += utf8.decode(new Uint8Array([0xac]), { stream: true });
+  text += utf8.decode();
+  assert(text === "€", `streamed utf-8 decoded to ${text}`);
+
+  const chunks = [];
+  const stream = new ReadableStream({
+    start(controller) {
+      controller.enqueue(new Uint8Array([0xff, 0xfe, 0x68]));
+      controller.enqueue(new Uint8Array([0x00, 0x69, 0x00]));
+      controller.close();
+    },
+  }).pipeThrough(new TextDecoderStream("utf-16le"));
+  for await (const chunk of stream) {
+    chunks.push(chunk);
+  }
+  assert(chunks.join("") === "hi", `TextDecoderStream decoded ${chunks.join("")}`);
+
+  Host.outputString("text_decoding: all tests passed");
+}
+
+module.exports = { greet };
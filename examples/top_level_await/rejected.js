@@ -0,0 +1,13 @@
+// Fails to build: the top level awaits a promise that rejects.
+
+async function loadConfig() {
+  throw new Error("config unavailable");
+}
+
+const config = await loadConfig();
+
+function greet() {
+  Host.outputString(config);
+}
+
+module.exports = { greet };
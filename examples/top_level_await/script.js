@@ -0,0 +1,35 @@
+// Top-level await and promise-based setup finish before the snapshot is
+// taken, so exports see the initialized state on their first call.
+
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`Assertion failed: ${message}`);
+  }
+}
+
+async function loadTable() {
+  await null;
+  const table = new Map();
+  for (const word of ["alpha", "beta", "gamma"]) {
+    table.set(word, word.length);
+  }
+  return table;
+}
+
+const table = await loadTable();
+
+// setup that is started but not awaited still runs to completion
+let cache = null;
+Promise.resolve()
+  .then(() => new TextEncoder().encode("cached"))
+  .then((bytes) => {
+    cache = bytes;
+  });
+
+function greet() {
+  assert(table.get("gamma") === 5, "awaited setup ran");
+  assert(cache instanceof Uint8Array && cache.length === 6, "pending jobs ran");
+  Host.outputString("top_level_await: all tests passed");
+}
+
+module.exports = { greet };
@@ -0,0 +1,36 @@
+function assert(condition, message) {
+  if (!condition) {
+    throw new Error(`unhandled_rejection: ${message}`);
+  }
+}
+
+const seen = [];
+
+// listeners can mark a rejection as handled
+addEventListener("unhandledrejection", (event) => {
+  assert(event instanceof PromiseRejectionEvent, "listeners should get a PromiseRejectionEvent");
+  if (event.reason.message === "ignored") {
+    event.preventDefault();
+  }
+});
+
+// the events fire after the export returns, in the order the promises were rejected
+onunhandledrejection = (event) => {
+  seen.push(event.reason.message);
+  if (event.reason.message === "logged") {
+    assert(seen.join() === "ignored,logged", `saw ${seen.join()}`);
+    Host.outputString("unhandled_rejection: all tests passed");
+  }
+};
+
+async function greet() {
+  Promise.reject(new Error("ignored"));
+  Promise.reject(new Error("logged"));
+
+  // handled before the call ends, so it isn't reported
+  const late = Promise.reject(new Error("handled later"));
+  await null;
+  late.catch(() => {});
+}
+
+module.exports = { greet };